@@ -308,6 +308,13 @@ def setup_logging():
         "YAKULINGO_NO_AUTO_OPEN=%s",
         no_auto_open if no_auto_open is not None else "(unset)",
     )
+    instance_name = os.environ.get("YAKULINGO_INSTANCE_NAME")
+    if instance_name:
+        logger.info(
+            "Named instance: %s (profile: %s)",
+            instance_name,
+            os.environ.get("YAKULINGO_PROFILE_DIR") or "(unset)",
+        )
 
     # Log file location information
     if file_handler:
@@ -532,6 +539,112 @@ def _try_focus_existing_window() -> None:
         return
 
 
+def _get_instance_mutex_name() -> str:
+    """Mutex name resolved by the native launcher (singleton scope).
+
+    Without the launcher, use the name of its default (per-machine) scope so
+    an app started directly and a launcher-started one still see each other.
+    """
+    return os.environ.get("YAKULINGO_INSTANCE_MUTEX") or "Global\\YakuLingoSingleton"
+
+
+def _get_app_port() -> int:
+    """Port resolved by the native launcher, falling back to the default."""
+    try:
+        port = int(os.environ.get("YAKULINGO_PORT", ""))
+    except ValueError:
+        return 8765
+    if 0 < port < 65536:
+        return port
+    return 8765
+
+
+_heartbeat_handoff = None
+_heartbeat_task = None
+
+
+def _start_heartbeat() -> bool:
+    """Touch the launcher's heartbeat file periodically (if requested).
+
+    A thread beats while the app starts up. Once the server's event loop runs,
+    _hand_heartbeat_to_event_loop() moves the beat onto that loop, so a hung
+    loop stops the heartbeat and the launcher can restart the app.
+    """
+    path = os.environ.get("YAKULINGO_HEARTBEAT_FILE")
+    if not path:
+        return False
+    try:
+        interval = max(1, int(os.environ.get("YAKULINGO_HEARTBEAT_INTERVAL_SEC", "10")))
+    except ValueError:
+        interval = 10
+
+    import threading
+
+    heartbeat = Path(path)
+    stop = threading.Event()
+
+    def _touch() -> None:
+        try:
+            heartbeat.parent.mkdir(parents=True, exist_ok=True)
+            heartbeat.touch()
+        except OSError:
+            pass
+
+    def _beat() -> None:
+        while not stop.is_set():
+            _touch()
+            stop.wait(interval)
+
+    def _handoff() -> None:
+        import asyncio
+
+        global _heartbeat_task
+
+        async def _loop_beat() -> None:
+            while True:
+                _touch()
+                await asyncio.sleep(interval)
+
+        stop.set()
+        _heartbeat_task = asyncio.get_running_loop().create_task(_loop_beat())
+
+    global _heartbeat_handoff
+    _heartbeat_handoff = _handoff
+    threading.Thread(target=_beat, name="yakulingo-heartbeat", daemon=True).start()
+    return True
+
+
+def _hand_heartbeat_to_event_loop() -> None:
+    """Beat from the running event loop from now on (no-op without a heartbeat)."""
+    if _heartbeat_handoff is not None:
+        _heartbeat_handoff()
+
+
+def _report_setup_progress(message: str, percent: float | None = None) -> None:
+    """Tell the launcher what a first run is doing (if requested).
+
+    The launcher names the file in YAKULINGO_SETUP_PROGRESS_FILE on first runs
+    only, logs each status and shows it on its splash until the window opens.
+    """
+    path = os.environ.get("YAKULINGO_SETUP_PROGRESS_FILE")
+    if not path:
+        return
+    import json
+
+    status: dict[str, object] = {"message": message}
+    if percent is not None:
+        status["percent"] = percent
+    target = Path(path)
+    # Replace atomically: the launcher polls the file while it is written.
+    partial = target.with_name(f"{target.name}.{os.getpid()}.tmp")
+    try:
+        target.parent.mkdir(parents=True, exist_ok=True)
+        partial.write_text(json.dumps(status), encoding="utf-8")
+        os.replace(partial, target)
+    except OSError:
+        pass
+
+
 def _ensure_single_instance() -> bool:
     """Return True if this is the primary instance (Windows only)."""
     if sys.platform != "win32":
@@ -550,7 +663,7 @@ def _ensure_single_instance() -> bool:
         ]
         kernel32.CreateMutexW.restype = wintypes.HANDLE
 
-        handle = kernel32.CreateMutexW(None, False, "Local\\YakuLingoSingleton")
+        handle = kernel32.CreateMutexW(None, False, _get_instance_mutex_name())
         if not handle:
             return True
         if ctypes.get_last_error() == 183:  # ERROR_ALREADY_EXISTS
@@ -583,6 +696,8 @@ def main():
         _try_focus_existing_window()
         raise SystemExit(11)
 
+    _start_heartbeat()
+
     _t_start = time.perf_counter()
 
     # Windows用: multiprocessing対策（pyinstallerでの実行時に必要）
@@ -620,6 +735,8 @@ def main():
             pass
 
     # Import UI module (NiceGUI is imported inside run_app() for faster startup)
+    # A first run compiles every module here, which can take minutes.
+    _report_setup_progress("Loading YakuLingo components")
     _t_import = time.perf_counter()
     try:
         from yakulingo.ui.app import run_app
@@ -638,11 +755,13 @@ def main():
         "[TIMING] yakulingo.ui.app import: %.2fs", time.perf_counter() - _t_import
     )
 
+    _report_setup_progress("Starting YakuLingo")
     try:
         run_app(
             host="127.0.0.1",
-            port=8765,
+            port=_get_app_port(),
             native=True,
+            on_server_start=_hand_heartbeat_to_event_loop,
         )
     except KeyboardInterrupt:
         # Normal shutdown via window close or Ctrl+C
@@ -0,0 +1,188 @@
+//! The app's recent stdout and stderr, saved to `app.log` after a crash.
+//!
+//! With `capture_app_output` both streams are piped into reader threads
+//! that keep the newest `APP_OUTPUT_BYTES` in one shared ring, interleaved
+//! as they arrive. The readers never stop draining, so a chatty app cannot
+//! block on a full pipe. On a crash exit the watchdog writes the ring to
+//! `app.log` next to `launcher.log`; other exits drop it. When
+//! `capture_stderr_tail` is also on, stderr reaches both through [`Tee`].
+
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::stderr_tail::DRAIN_WAIT_MS;
+
+/// Newest output kept for `app.log`.
+pub const APP_OUTPUT_BYTES: usize = 64 * 1024;
+pub const APP_OUTPUT_FILE_NAME: &str = "app.log";
+const READ_CHUNK: usize = 8 * 1024;
+
+/// Keeps the newest `capacity` bytes.
+#[derive(Debug)]
+pub struct ByteRing {
+    bytes: VecDeque<u8>,
+    capacity: usize,
+    truncated: bool,
+}
+
+impl ByteRing {
+    pub fn new(capacity: usize) -> Self {
+        ByteRing {
+            bytes: VecDeque::with_capacity(capacity),
+            capacity,
+            truncated: false,
+        }
+    }
+
+    pub fn push(&mut self, data: &[u8]) {
+        let data = &data[data.len().saturating_sub(self.capacity)..];
+        let excess = (self.bytes.len() + data.len()).saturating_sub(self.capacity);
+        if excess > 0 {
+            self.bytes.drain(..excess);
+            self.truncated = true;
+        }
+        self.bytes.extend(data);
+    }
+
+    /// The kept bytes, lossily decoded; once older output was dropped, the
+    /// partial first line is cut off too.
+    pub fn text(&self) -> String {
+        let (front, back) = self.bytes.as_slices();
+        let bytes = [front, back].concat();
+        let start = match bytes.iter().position(|&b| b == b'\n') {
+            Some(newline) if self.truncated => newline + 1,
+            _ => 0,
+        };
+        String::from_utf8_lossy(&bytes[start..]).into_owned()
+    }
+}
+
+type SharedRing = Arc<Mutex<ByteRing>>;
+
+/// A reader that copies everything read through it into the output ring.
+pub struct Tee<R> {
+    inner: R,
+    ring: SharedRing,
+}
+
+impl<R: Read> Read for Tee<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if let Ok(mut ring) = self.ring.lock() {
+            ring.push(&buf[..n]);
+        }
+        Ok(n)
+    }
+}
+
+/// Reader threads following one app process's stdout and stderr.
+pub struct AppOutput {
+    ring: SharedRing,
+    readers: Vec<JoinHandle<()>>,
+}
+
+impl AppOutput {
+    pub fn new(capacity: usize) -> Self {
+        AppOutput {
+            ring: Arc::new(Mutex::new(ByteRing::new(capacity))),
+            readers: Vec::new(),
+        }
+    }
+
+    /// Read `stream` to its end on a background thread.
+    pub fn drain(&mut self, stream: impl Read + Send + 'static) {
+        let mut tee = self.tee(stream);
+        self.readers.push(thread::spawn(move || {
+            let mut chunk = [0u8; READ_CHUNK];
+            while matches!(tee.read(&mut chunk), Ok(n) if n > 0) {}
+        }));
+    }
+
+    /// Wrap `stream` for a reader owned elsewhere (the stderr tail).
+    pub fn tee<R: Read>(&self, stream: R) -> Tee<R> {
+        Tee {
+            inner: stream,
+            ring: self.ring.clone(),
+        }
+    }
+
+    /// The captured output once the app has exited; `None` if it wrote
+    /// nothing. Waits briefly for the pipes to drain.
+    pub fn finish(self) -> Option<String> {
+        let deadline = Instant::now() + Duration::from_millis(DRAIN_WAIT_MS);
+        while !self.readers.iter().all(JoinHandle::is_finished) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let text = self.ring.lock().ok()?.text();
+        (!text.trim().is_empty()).then_some(text)
+    }
+}
+
+/// Replace `app.log` in `log_dir` with the output of the run that just
+/// crashed.
+pub fn write_app_output(
+    log_dir: &Path,
+    ts: u64,
+    exit_code: i32,
+    output: &str,
+) -> io::Result<PathBuf> {
+    fs::create_dir_all(log_dir)?;
+    let path = log_dir.join(APP_OUTPUT_FILE_NAME);
+    let header = format!(
+        "# YakuLingo output before exit code {} (unix time {}), last {} KB\n",
+        exit_code,
+        ts,
+        APP_OUTPUT_BYTES / 1024
+    );
+    fs::write(&path, header + output)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stderr_tail::StderrTail;
+    use crate::test_util::temp_dir;
+    use std::io::Cursor;
+
+    #[test]
+    fn ring_keeps_the_newest_bytes_from_a_line_start() {
+        let mut ring = ByteRing::new(16);
+        ring.push(b"one\ntwo\n");
+        assert_eq!(ring.text(), "one\ntwo\n");
+        ring.push(b"three\nfour\n");
+        assert_eq!(ring.text(), "two\nthree\nfour\n");
+        ring.push(&[b'x'; 40]);
+        assert_eq!(ring.text(), "x".repeat(16));
+    }
+
+    #[test]
+    fn both_streams_are_captured_beyond_the_pipe_buffer() {
+        let noise: String = (0..20_000).map(|n| format!("log {}\n", n)).collect();
+        let mut output = AppOutput::new(APP_OUTPUT_BYTES);
+        output.drain(Cursor::new(noise.into_bytes()));
+        while !output.readers[0].is_finished() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let tail = StderrTail::capture(output.tee(Cursor::new(b"ImportError: x\n".to_vec())), 5);
+        assert_eq!(tail.finish().as_deref(), Some("ImportError: x"));
+
+        let text = output.finish().unwrap();
+        assert!(text.len() <= APP_OUTPUT_BYTES);
+        assert!(text.starts_with("log "));
+        assert!(text.contains("log 19999\n"));
+        assert!(text.contains("ImportError: x\n"));
+
+        let dir = temp_dir("app-output");
+        let path = write_app_output(&dir, 1700000000, 1, &text).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(written.starts_with("# YakuLingo output before exit code 1"));
+        assert!(written.ends_with(&text));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
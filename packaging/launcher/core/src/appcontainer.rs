@@ -0,0 +1,555 @@
+//! Optional AppContainer sandbox for the Python process (Windows).
+//!
+//! When `app_container = true`, python.exe is created through `CreateProcessW`
+//! with a `PROC_THREAD_ATTRIBUTE_SECURITY_CAPABILITIES` attribute so it runs
+//! as the `YakuLingo.Sandbox` container SID with only [`APP_CONTAINER_CAPABILITIES`].
+//! The container cannot read the install dir or write the profile dir unless
+//! an administrator grants its SID access; see README for the ACLs.
+
+use std::ffi::c_void;
+use std::mem::size_of;
+
+/// Profile name the container SID is derived from.
+pub const APP_CONTAINER_NAME: &str = "YakuLingo.Sandbox";
+
+/// `ProcThreadAttributeValue(ProcThreadAttributeSecurityCapabilities, FALSE, TRUE, FALSE)`.
+pub const PROC_THREAD_ATTRIBUTE_SECURITY_CAPABILITIES: usize = 0x0002_0009;
+
+/// Win32 `SE_GROUP_ENABLED`.
+const SE_GROUP_ENABLED: u32 = 0x0000_0004;
+
+/// Capabilities granted to the container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    /// Outbound connections to the translation backend.
+    InternetClient,
+    /// The app's own HTTP server on 127.0.0.1 and the UI connecting to it.
+    PrivateNetworkClientServer,
+}
+
+impl Capability {
+    /// `WELL_KNOWN_SID_TYPE` value passed to `CreateWellKnownSid`.
+    pub fn well_known_sid_type(self) -> u32 {
+        match self {
+            Capability::InternetClient => 85,
+            Capability::PrivateNetworkClientServer => 87,
+        }
+    }
+}
+
+pub const APP_CONTAINER_CAPABILITIES: [Capability; 2] = [
+    Capability::InternetClient,
+    Capability::PrivateNetworkClientServer,
+];
+
+/// Layout-compatible with Win32 `SID_AND_ATTRIBUTES`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SidAndAttributes {
+    pub sid: *mut c_void,
+    pub attributes: u32,
+}
+
+/// Layout-compatible with Win32 `SECURITY_CAPABILITIES`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SecurityCapabilities {
+    pub app_container_sid: *mut c_void,
+    pub capabilities: *mut SidAndAttributes,
+    pub capability_count: u32,
+    pub reserved: u32,
+}
+
+/// The `PROC_THREAD_ATTRIBUTE_LIST` calls needed to start a process, behind
+/// a seam so the construction order can be tested without Win32.
+pub trait AttributeList {
+    /// Prepare the list to hold `attribute_count` attributes.
+    fn initialize(&mut self, attribute_count: u32) -> Result<(), String>;
+    /// Register `value` (`size` bytes, must outlive process creation).
+    fn update(&mut self, attribute: usize, value: *mut c_void, size: usize) -> Result<(), String>;
+}
+
+/// Wrap capability SIDs as enabled `SID_AND_ATTRIBUTES` entries.
+pub fn capability_entries(capability_sids: &[*mut c_void]) -> Vec<SidAndAttributes> {
+    capability_sids
+        .iter()
+        .map(|&sid| SidAndAttributes {
+            sid,
+            attributes: SE_GROUP_ENABLED,
+        })
+        .collect()
+}
+
+/// Build `SECURITY_CAPABILITIES` borrowing `container_sid` and `entries`.
+pub fn security_capabilities(
+    container_sid: *mut c_void,
+    entries: &mut [SidAndAttributes],
+) -> SecurityCapabilities {
+    SecurityCapabilities {
+        app_container_sid: container_sid,
+        capabilities: if entries.is_empty() {
+            std::ptr::null_mut()
+        } else {
+            entries.as_mut_ptr()
+        },
+        capability_count: entries.len() as u32,
+        reserved: 0,
+    }
+}
+
+/// Initialize `list` with the single security-capabilities attribute.
+pub fn build_attribute_list<L: AttributeList>(
+    list: &mut L,
+    capabilities: &mut SecurityCapabilities,
+) -> Result<(), String> {
+    list.initialize(1)?;
+    list.update(
+        PROC_THREAD_ATTRIBUTE_SECURITY_CAPABILITIES,
+        capabilities as *mut SecurityCapabilities as *mut c_void,
+        size_of::<SecurityCapabilities>(),
+    )
+}
+
+/// Message shown when the sandbox cannot be set up. The launch is not retried
+/// unsandboxed: the deployment asked for isolation.
+pub fn setup_error_message(detail: &str) -> String {
+    format!(
+        "Failed to start YakuLingo in the AppContainer sandbox: {}\n\n\
+         Ask your administrator to check the sandbox setup, or set\n\
+         app_container = false in launcher.toml.",
+        detail
+    )
+}
+
+#[cfg(windows)]
+pub use self::windows::{spawn_in_app_container, ContainerProcess};
+
+#[cfg(windows)]
+mod windows {
+    use std::env;
+    use std::ffi::{c_void, OsStr, OsString};
+    use std::io;
+    use std::iter::once;
+    use std::mem::{size_of, zeroed};
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::PathBuf;
+    use std::process::Command;
+    use std::ptr::{null, null_mut};
+
+    use winapi::shared::sddl::ConvertSidToStringSidW;
+    use winapi::shared::winerror::{ERROR_ALREADY_EXISTS, FAILED, HRESULT_FROM_WIN32};
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::{
+        CreateProcessW, DeleteProcThreadAttributeList, GetExitCodeProcess,
+        InitializeProcThreadAttributeList, TerminateProcess, UpdateProcThreadAttribute,
+        LPPROC_THREAD_ATTRIBUTE_LIST, PROCESS_INFORMATION,
+    };
+    use winapi::um::securitybaseapi::{CreateWellKnownSid, FreeSid};
+    use winapi::um::synchapi::WaitForSingleObject;
+    use winapi::um::userenv::{
+        CreateAppContainerProfile, DeriveAppContainerSidFromAppContainerName,
+    };
+    use winapi::um::winbase::{
+        LocalFree, CREATE_UNICODE_ENVIRONMENT, EXTENDED_STARTUPINFO_PRESENT, INFINITE,
+        STARTUPINFOEXW, WAIT_OBJECT_0,
+    };
+    use winapi::um::winnt::{HANDLE, PSID, SECURITY_MAX_SID_SIZE};
+
+    use super::{
+        build_attribute_list, capability_entries, security_capabilities, setup_error_message,
+        AttributeList, APP_CONTAINER_CAPABILITIES, APP_CONTAINER_NAME,
+    };
+    use crate::elevation::quote_windows_arg;
+    use crate::log::log_event;
+
+    fn wide(value: &OsStr) -> Vec<u16> {
+        value.encode_wide().chain(once(0)).collect()
+    }
+
+    /// `PROC_THREAD_ATTRIBUTE_LIST` backed by a heap buffer.
+    struct Win32AttributeList {
+        buffer: Vec<u8>,
+        initialized: bool,
+    }
+
+    impl Win32AttributeList {
+        fn as_ptr(&mut self) -> LPPROC_THREAD_ATTRIBUTE_LIST {
+            self.buffer.as_mut_ptr() as LPPROC_THREAD_ATTRIBUTE_LIST
+        }
+    }
+
+    impl AttributeList for Win32AttributeList {
+        fn initialize(&mut self, attribute_count: u32) -> Result<(), String> {
+            let mut size = 0usize;
+            unsafe {
+                InitializeProcThreadAttributeList(null_mut(), attribute_count, 0, &mut size);
+            }
+            self.buffer = vec![0u8; size];
+            let ok = unsafe {
+                InitializeProcThreadAttributeList(self.as_ptr(), attribute_count, 0, &mut size)
+            };
+            if ok == 0 {
+                return Err(format!(
+                    "InitializeProcThreadAttributeList failed: {}",
+                    io::Error::last_os_error()
+                ));
+            }
+            self.initialized = true;
+            Ok(())
+        }
+
+        fn update(
+            &mut self,
+            attribute: usize,
+            value: *mut c_void,
+            size: usize,
+        ) -> Result<(), String> {
+            let ok = unsafe {
+                UpdateProcThreadAttribute(
+                    self.as_ptr(),
+                    0,
+                    attribute,
+                    value as *mut _,
+                    size,
+                    null_mut(),
+                    null_mut(),
+                )
+            };
+            if ok == 0 {
+                return Err(format!(
+                    "UpdateProcThreadAttribute failed: {}",
+                    io::Error::last_os_error()
+                ));
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for Win32AttributeList {
+        fn drop(&mut self) {
+            if self.initialized {
+                unsafe { DeleteProcThreadAttributeList(self.as_ptr()) };
+            }
+        }
+    }
+
+    /// Container SID from `CreateAppContainerProfile` / derivation; freed with `FreeSid`.
+    struct ContainerSid(PSID);
+
+    impl Drop for ContainerSid {
+        fn drop(&mut self) {
+            unsafe { FreeSid(self.0) };
+        }
+    }
+
+    fn container_sid(capabilities: &mut [super::SidAndAttributes]) -> Result<ContainerSid, String> {
+        let name = wide(OsStr::new(APP_CONTAINER_NAME));
+        let mut sid: PSID = null_mut();
+        let hr = unsafe {
+            CreateAppContainerProfile(
+                name.as_ptr(),
+                name.as_ptr(),
+                name.as_ptr(),
+                capabilities.as_mut_ptr() as *mut _,
+                capabilities.len() as u32,
+                &mut sid,
+            )
+        };
+        if hr == HRESULT_FROM_WIN32(ERROR_ALREADY_EXISTS) {
+            let hr = unsafe { DeriveAppContainerSidFromAppContainerName(name.as_ptr(), &mut sid) };
+            if FAILED(hr) {
+                return Err(format!(
+                    "DeriveAppContainerSidFromAppContainerName failed (0x{:08X})",
+                    hr as u32
+                ));
+            }
+        } else if FAILED(hr) {
+            return Err(format!(
+                "CreateAppContainerProfile failed (0x{:08X})",
+                hr as u32
+            ));
+        }
+        Ok(ContainerSid(sid))
+    }
+
+    fn sid_string(sid: PSID) -> Option<String> {
+        let mut raw = null_mut();
+        if unsafe { ConvertSidToStringSidW(sid, &mut raw) } == 0 {
+            return None;
+        }
+        let text = unsafe {
+            let len = (0..).take_while(|&i| *raw.offset(i) != 0).count();
+            let slice = std::slice::from_raw_parts(raw, len);
+            String::from_utf16_lossy(slice)
+        };
+        unsafe { LocalFree(raw as *mut _) };
+        Some(text)
+    }
+
+    fn capability_sid_buffers() -> Result<Vec<Vec<u8>>, String> {
+        APP_CONTAINER_CAPABILITIES
+            .iter()
+            .map(|capability| {
+                let mut buffer = vec![0u8; SECURITY_MAX_SID_SIZE];
+                let mut size = buffer.len() as u32;
+                let ok = unsafe {
+                    CreateWellKnownSid(
+                        capability.well_known_sid_type(),
+                        null_mut(),
+                        buffer.as_mut_ptr() as PSID,
+                        &mut size,
+                    )
+                };
+                if ok == 0 {
+                    return Err(format!(
+                        "CreateWellKnownSid({:?}) failed: {}",
+                        capability,
+                        io::Error::last_os_error()
+                    ));
+                }
+                Ok(buffer)
+            })
+            .collect()
+    }
+
+    /// `CreateProcessW` command line for the program and arguments of `command`.
+    fn command_line(command: &Command) -> Vec<u16> {
+        let mut parts = vec![quote_windows_arg(&command.get_program().to_string_lossy())];
+        parts.extend(
+            command
+                .get_args()
+                .map(|arg| quote_windows_arg(&arg.to_string_lossy())),
+        );
+        wide(OsStr::new(&parts.join(" ")))
+    }
+
+    /// Inherited environment with the overrides of `command`, as a sorted
+    /// `CREATE_UNICODE_ENVIRONMENT` block.
+    fn environment_block(command: &Command) -> Vec<u16> {
+        let mut vars: Vec<(OsString, OsString)> = env::vars_os().collect();
+        for (key, value) in command.get_envs() {
+            vars.retain(|(existing, _)| !existing.eq_ignore_ascii_case(key));
+            if let Some(value) = value {
+                vars.push((key.to_os_string(), value.to_os_string()));
+            }
+        }
+        vars.sort_by_key(|(key, _)| key.to_ascii_uppercase());
+
+        let mut block = Vec::new();
+        for (key, value) in vars {
+            block.extend(key.encode_wide());
+            block.push('=' as u16);
+            block.extend(value.encode_wide());
+            block.push(0);
+        }
+        block.push(0);
+        block
+    }
+
+    /// Process started inside the container.
+    #[derive(Debug)]
+    pub struct ContainerProcess {
+        handle: HANDLE,
+        pid: u32,
+    }
+
+    impl ContainerProcess {
+        pub fn id(&self) -> u32 {
+            self.pid
+        }
+
+        /// Exit code if the process has already exited.
+        pub fn try_wait(&mut self) -> Result<Option<i32>, String> {
+            if unsafe { WaitForSingleObject(self.handle, 0) } != WAIT_OBJECT_0 {
+                return Ok(None);
+            }
+            self.wait().map(Some)
+        }
+
+        pub fn kill(&mut self) -> Result<(), String> {
+            if unsafe { TerminateProcess(self.handle, 1) } == 0 {
+                return Err(format!(
+                    "Failed to stop application: {}",
+                    io::Error::last_os_error()
+                ));
+            }
+            Ok(())
+        }
+
+        /// Block until the process exits and return its exit code.
+        pub fn wait(&mut self) -> Result<i32, String> {
+            let mut code = 0u32;
+            let ok = unsafe {
+                WaitForSingleObject(self.handle, INFINITE);
+                GetExitCodeProcess(self.handle, &mut code)
+            };
+            if ok == 0 {
+                return Err(format!(
+                    "Failed to wait for application: {}",
+                    io::Error::last_os_error()
+                ));
+            }
+            Ok(code as i32)
+        }
+    }
+
+    impl Drop for ContainerProcess {
+        fn drop(&mut self) {
+            unsafe { CloseHandle(self.handle) };
+        }
+    }
+
+    /// Start `command` (program, args, env, cwd) inside the AppContainer.
+    pub fn spawn_in_app_container(
+        command: &Command,
+        creation_flags: u32,
+        log_path: &Option<PathBuf>,
+    ) -> Result<ContainerProcess, String> {
+        spawn(command, creation_flags, log_path).map_err(|detail| {
+            log_event(log_path, &format!("AppContainer setup failed: {}", detail));
+            setup_error_message(&detail)
+        })
+    }
+
+    fn spawn(
+        command: &Command,
+        creation_flags: u32,
+        log_path: &Option<PathBuf>,
+    ) -> Result<ContainerProcess, String> {
+        let mut sid_buffers = capability_sid_buffers()?;
+        let sids: Vec<*mut c_void> = sid_buffers
+            .iter_mut()
+            .map(|buffer| buffer.as_mut_ptr() as *mut c_void)
+            .collect();
+        let mut entries = capability_entries(&sids);
+        let sid = container_sid(&mut entries)?;
+        log_event(
+            log_path,
+            &format!(
+                "AppContainer {} (SID {})",
+                APP_CONTAINER_NAME,
+                sid_string(sid.0).unwrap_or_else(|| "unknown".to_string())
+            ),
+        );
+
+        let mut capabilities = security_capabilities(sid.0 as *mut c_void, &mut entries);
+        let mut attributes = Win32AttributeList {
+            buffer: Vec::new(),
+            initialized: false,
+        };
+        build_attribute_list(&mut attributes, &mut capabilities)?;
+
+        let mut startup: STARTUPINFOEXW = unsafe { zeroed() };
+        startup.StartupInfo.cb = size_of::<STARTUPINFOEXW>() as u32;
+        startup.lpAttributeList = attributes.as_ptr();
+
+        let mut command_line = command_line(command);
+        let mut environment = environment_block(command);
+        let current_dir = command.get_current_dir().map(|dir| wide(dir.as_os_str()));
+        let mut info: PROCESS_INFORMATION = unsafe { zeroed() };
+
+        let ok = unsafe {
+            CreateProcessW(
+                null(),
+                command_line.as_mut_ptr(),
+                null_mut(),
+                null_mut(),
+                0,
+                creation_flags | EXTENDED_STARTUPINFO_PRESENT | CREATE_UNICODE_ENVIRONMENT,
+                environment.as_mut_ptr() as *mut _,
+                current_dir.as_ref().map_or(null(), |dir| dir.as_ptr()),
+                &mut startup.StartupInfo,
+                &mut info,
+            )
+        };
+        if ok == 0 {
+            return Err(format!(
+                "CreateProcessW failed: {}",
+                io::Error::last_os_error()
+            ));
+        }
+
+        unsafe { CloseHandle(info.hThread) };
+        Ok(ContainerProcess {
+            handle: info.hProcess,
+            pid: info.dwProcessId,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingList {
+        initialized_with: Option<u32>,
+        updates: Vec<(usize, usize)>,
+        capability_count: Option<u32>,
+    }
+
+    impl AttributeList for RecordingList {
+        fn initialize(&mut self, attribute_count: u32) -> Result<(), String> {
+            self.initialized_with = Some(attribute_count);
+            Ok(())
+        }
+
+        fn update(
+            &mut self,
+            attribute: usize,
+            value: *mut c_void,
+            size: usize,
+        ) -> Result<(), String> {
+            assert!(self.initialized_with.is_some(), "update before initialize");
+            let capabilities = unsafe { &*(value as *const SecurityCapabilities) };
+            self.capability_count = Some(capabilities.capability_count);
+            self.updates.push((attribute, size));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn attribute_list_carries_security_capabilities() {
+        let mut container_sid = [0u8; 16];
+        let mut capability_sids = [[0u8; 16]; 2];
+        let sids: Vec<*mut c_void> = capability_sids
+            .iter_mut()
+            .map(|sid| sid.as_mut_ptr() as *mut c_void)
+            .collect();
+        let mut entries = capability_entries(&sids);
+        let mut capabilities =
+            security_capabilities(container_sid.as_mut_ptr() as *mut c_void, &mut entries);
+
+        let mut list = RecordingList::default();
+        build_attribute_list(&mut list, &mut capabilities).unwrap();
+
+        assert_eq!(list.initialized_with, Some(1));
+        assert_eq!(
+            list.updates,
+            vec![(
+                PROC_THREAD_ATTRIBUTE_SECURITY_CAPABILITIES,
+                size_of::<SecurityCapabilities>()
+            )]
+        );
+        assert_eq!(list.capability_count, Some(2));
+        assert!(entries.iter().all(|e| e.attributes == SE_GROUP_ENABLED));
+        assert_eq!(entries[1].sid, sids[1]);
+    }
+
+    #[test]
+    fn attribute_list_errors_propagate() {
+        struct FailingList;
+        impl AttributeList for FailingList {
+            fn initialize(&mut self, _attribute_count: u32) -> Result<(), String> {
+                Err("no memory".to_string())
+            }
+            fn update(&mut self, _: usize, _: *mut c_void, _: usize) -> Result<(), String> {
+                unreachable!()
+            }
+        }
+        let mut capabilities = security_capabilities(std::ptr::null_mut(), &mut []);
+        assert_eq!(capabilities.capability_count, 0);
+        let err = build_attribute_list(&mut FailingList, &mut capabilities).unwrap_err();
+        assert!(setup_error_message(&err).contains("no memory"));
+    }
+}
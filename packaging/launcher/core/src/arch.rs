@@ -0,0 +1,169 @@
+//! Architecture marker in the state dir.
+//!
+//! A state dir written by a different-architecture build (e.g. after moving
+//! from a 32-bit to a 64-bit install) can hold caches the app cannot load.
+//! The launcher records its architecture and warns when it changes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::log::log_event;
+
+pub const ARCH_MARKER_FILE: &str = "launcher_arch";
+/// Caches under the state dir that the app regenerates on demand.
+pub const ARCH_SPECIFIC_CACHES: [&str; 1] = ["pycache"];
+
+/// Architecture of this launcher build (`x86_64`, `x86`, `aarch64`, ...).
+pub fn launcher_arch() -> &'static str {
+    std::env::consts::ARCH
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArchCheck {
+    /// No marker yet: first launch with this state dir.
+    FirstRun,
+    Match,
+    /// The state dir was last used by a `previous`-architecture build.
+    Mismatch {
+        previous: String,
+    },
+}
+
+pub fn compare_arch(marker: Option<&str>, current: &str) -> ArchCheck {
+    match marker.map(str::trim).filter(|value| !value.is_empty()) {
+        None => ArchCheck::FirstRun,
+        Some(previous) if previous == current => ArchCheck::Match,
+        Some(previous) => ArchCheck::Mismatch {
+            previous: previous.to_string(),
+        },
+    }
+}
+
+/// Compare `state_dir`'s marker with `current` and log a mismatch. With
+/// `clear_caches` the arch-specific caches are removed and the marker is
+/// updated; otherwise the old marker stays, so the warning repeats on every
+/// launch until the caches are dealt with.
+pub fn check_state_arch(
+    state_dir: &Path,
+    current: &str,
+    clear_caches: bool,
+    log_path: &Option<PathBuf>,
+) -> ArchCheck {
+    let marker_path = state_dir.join(ARCH_MARKER_FILE);
+    let marker = fs::read_to_string(&marker_path).ok();
+    let check = compare_arch(marker.as_deref(), current);
+    match &check {
+        ArchCheck::Match => return check,
+        ArchCheck::FirstRun => {}
+        ArchCheck::Mismatch { previous } => {
+            log_event(
+                log_path,
+                &format!(
+                    "WARNING: architecture mismatch - {:?} was last used by a {} build, this launcher is {}; cached files may be incompatible",
+                    state_dir, previous, current
+                ),
+            );
+            if !clear_caches {
+                log_event(
+                    log_path,
+                    "Arch-specific caches kept (set clear_arch_caches = true to remove them)",
+                );
+                return check;
+            }
+            for cache in ARCH_SPECIFIC_CACHES {
+                let path = state_dir.join(cache);
+                if !path.exists() {
+                    continue;
+                }
+                match fs::remove_dir_all(&path) {
+                    Ok(()) => log_event(log_path, &format!("Cleared cache {:?}", path)),
+                    Err(err) => log_event(
+                        log_path,
+                        &format!("Failed to clear cache {:?}: {}", path, err),
+                    ),
+                }
+            }
+        }
+    }
+    let _ = fs::create_dir_all(state_dir);
+    if let Err(err) = fs::write(&marker_path, format!("{}\n", current)) {
+        log_event(log_path, &format!("Failed to write arch marker: {}", err));
+    }
+    check
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn compares_markers() {
+        assert_eq!(compare_arch(None, "x86_64"), ArchCheck::FirstRun);
+        assert_eq!(compare_arch(Some(""), "x86_64"), ArchCheck::FirstRun);
+        assert_eq!(compare_arch(Some("x86_64\n"), "x86_64"), ArchCheck::Match);
+        assert_eq!(
+            compare_arch(Some("x86"), "x86_64"),
+            ArchCheck::Mismatch {
+                previous: "x86".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn first_run_writes_marker_and_match_keeps_caches() {
+        let dir = temp_dir("arch-match");
+        fs::create_dir_all(dir.join("pycache")).unwrap();
+        assert_eq!(
+            check_state_arch(&dir, "x86_64", true, &None),
+            ArchCheck::FirstRun
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join(ARCH_MARKER_FILE)).unwrap(),
+            "x86_64\n"
+        );
+        assert_eq!(
+            check_state_arch(&dir, "x86_64", true, &None),
+            ArchCheck::Match
+        );
+        assert!(dir.join("pycache").is_dir());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn mismatch_clears_caches_when_configured() {
+        let dir = temp_dir("arch-clear");
+        fs::create_dir_all(dir.join("pycache").join("app")).unwrap();
+        fs::write(dir.join("history.db"), "").unwrap();
+        fs::write(dir.join(ARCH_MARKER_FILE), "x86\n").unwrap();
+        assert!(matches!(
+            check_state_arch(&dir, "x86_64", true, &None),
+            ArchCheck::Mismatch { .. }
+        ));
+        assert!(!dir.join("pycache").exists());
+        assert!(
+            dir.join("history.db").exists(),
+            "user data is never cleared"
+        );
+        assert_eq!(
+            check_state_arch(&dir, "x86_64", true, &None),
+            ArchCheck::Match
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn mismatch_without_clearing_keeps_warning() {
+        let dir = temp_dir("arch-keep");
+        fs::create_dir_all(dir.join("pycache")).unwrap();
+        fs::write(dir.join(ARCH_MARKER_FILE), "x86\n").unwrap();
+        for _ in 0..2 {
+            assert!(matches!(
+                check_state_arch(&dir, "x86_64", false, &None),
+                ArchCheck::Mismatch { .. }
+            ));
+        }
+        assert!(dir.join("pycache").is_dir());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
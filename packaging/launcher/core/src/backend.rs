@@ -0,0 +1,695 @@
+//! `--check-backend`: can this machine reach the translation backend?
+//!
+//! Checks DNS, TCP reachability and, for `https`, the TLS handshake with
+//! certificate validation, going through the proxy from `HTTPS_PROXY` /
+//! `HTTP_PROXY` (honouring `NO_PROXY`) like the app would. Nothing is
+//! launched. The JSON report goes to stdout; the exit code is non-zero when
+//! a check failed.
+//!
+//! The URL comes from `--check-backend=<url>`, `YAKULINGO_BACKEND_URL`,
+//! `backend_url` in `launcher.toml`, else the bundled local AI server at
+//! the address in the app settings.
+
+use std::env;
+use std::fs;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::load_config;
+use crate::paths::launcher_dir;
+use crate::tail::attach_console;
+use crate::verify::{print_report, CheckReport};
+
+/// App settings defaults, relative to the install dir. The app drops
+/// `local_ai_*` keys from `user_settings.json`, so only these count.
+const APP_SETTINGS_TEMPLATE: &str = "config/settings.template.json";
+/// The only `local_ai_host` the app accepts; it replaces any other value.
+const LOCAL_AI_HOST: &str = "127.0.0.1";
+/// The app's fallback when `local_ai_port_base` is missing or out of range.
+const DEFAULT_LOCAL_AI_PORT: u16 = 4891;
+const BACKEND_TIMEOUT_MS: u64 = 5000;
+
+/// WinHTTP error codes, named here so messages can be tested everywhere.
+const ERROR_WINHTTP_TIMEOUT: u32 = 12002;
+const ERROR_WINHTTP_NAME_NOT_RESOLVED: u32 = 12007;
+const ERROR_WINHTTP_CANNOT_CONNECT: u32 = 12029;
+const ERROR_WINHTTP_CONNECTION_ERROR: u32 = 12030;
+const ERROR_WINHTTP_SECURE_CERT_DATE_INVALID: u32 = 12037;
+const ERROR_WINHTTP_SECURE_CERT_CN_INVALID: u32 = 12038;
+const ERROR_WINHTTP_SECURE_INVALID_CA: u32 = 12045;
+const ERROR_WINHTTP_SECURE_CERT_REV_FAILED: u32 = 12057;
+const ERROR_WINHTTP_SECURE_CHANNEL_ERROR: u32 = 12157;
+const ERROR_WINHTTP_SECURE_INVALID_CERT: u32 = 12169;
+const ERROR_WINHTTP_SECURE_CERT_REVOKED: u32 = 12170;
+const ERROR_WINHTTP_SECURE_FAILURE: u32 = 12175;
+const ERROR_WINHTTP_INVALID_SERVER_RESPONSE: u32 = 12152;
+/// Status of a proxy that wants credentials (those in `HTTPS_PROXY` are
+/// not passed on, see [`proxy_for`]).
+const HTTP_STATUS_PROXY_AUTH_REQUIRED: u32 = 407;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackendTarget {
+    pub https: bool,
+    pub host: String,
+    pub port: u16,
+    /// Path and query, at least `/`.
+    pub path: String,
+}
+
+/// Parse an `http(s)://host[:port][/path]` URL.
+pub fn parse_backend_url(url: &str) -> Result<BackendTarget, String> {
+    let url = url.trim();
+    let (https, rest) = if let Some(rest) = url.strip_prefix("https://") {
+        (true, rest)
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        (false, rest)
+    } else {
+        return Err(format!("{:?} is not an http:// or https:// URL", url));
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(idx) => (&rest[..idx], &rest[idx..]),
+        None => (rest, "/"),
+    };
+    if authority.contains('@') {
+        return Err("credentials in the backend URL are not supported".to_string());
+    }
+    let (host, port) =
+        split_host_port(authority).ok_or_else(|| format!("invalid host in {:?}", url))?;
+    Ok(BackendTarget {
+        https,
+        host,
+        port: port.unwrap_or(if https { 443 } else { 80 }),
+        path: path.to_string(),
+    })
+}
+
+/// `host`, `host:port`, `[v6]` or `[v6]:port`.
+fn split_host_port(authority: &str) -> Option<(String, Option<u16>)> {
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']')?;
+        (host, rest.strip_prefix(':'))
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+    if host.is_empty() {
+        return None;
+    }
+    let port = match port {
+        Some(port) => Some(port.parse().ok().filter(|&port: &u16| port != 0)?),
+        None => None,
+    };
+    Some((host.to_string(), port))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyEndpoint {
+    pub host: String,
+    pub port: u16,
+    /// The env var it came from, for the report.
+    pub source: &'static str,
+}
+
+/// The proxy the app would use for `target`, from the usual env vars.
+pub fn proxy_for(
+    target: &BackendTarget,
+    var: impl Fn(&str) -> Option<String>,
+) -> Option<ProxyEndpoint> {
+    let no_proxy = var("NO_PROXY")
+        .or_else(|| var("no_proxy"))
+        .unwrap_or_default();
+    let host = target.host.to_ascii_lowercase();
+    let bypassed = no_proxy
+        .split(',')
+        .map(|entry| entry.trim().trim_start_matches('.').to_ascii_lowercase())
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| entry == "*" || host == entry || host.ends_with(&format!(".{}", entry)));
+    if bypassed {
+        return None;
+    }
+    let names: &[&'static str] = if target.https {
+        &["HTTPS_PROXY", "https_proxy"]
+    } else {
+        &["HTTP_PROXY", "http_proxy"]
+    };
+    names.iter().find_map(|&name| {
+        let value = var(name).filter(|value| !value.trim().is_empty())?;
+        let value = value.trim();
+        let authority = value
+            .strip_prefix("http://")
+            .or_else(|| value.strip_prefix("https://"))
+            .unwrap_or(value)
+            .trim_end_matches('/');
+        let authority = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        let (host, port) = split_host_port(authority)?;
+        Some(ProxyEndpoint {
+            host,
+            port: port.unwrap_or(80),
+            source: name,
+        })
+    })
+}
+
+/// The network operations, behind a seam so failures can be simulated.
+pub trait NetworkProbe {
+    fn resolve(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>, String>;
+    fn connect(&self, addr: SocketAddr) -> Result<(), String>;
+    /// TLS handshake with certificate validation; `None` where this build
+    /// cannot check it.
+    fn tls_handshake(
+        &self,
+        target: &BackendTarget,
+        proxy: Option<&ProxyEndpoint>,
+    ) -> Option<Result<String, String>>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BackendSubject {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+}
+
+pub type BackendReport = CheckReport<BackendSubject>;
+
+/// The bundled local AI server: `local_ai_host` / `local_ai_port_base` from
+/// the app settings in `install_dir`, validated the way the app does.
+pub fn default_backend_url(install_dir: &Path) -> String {
+    let settings = fs::read_to_string(install_dir.join(APP_SETTINGS_TEMPLATE))
+        .ok()
+        .and_then(|content| {
+            serde_json::from_str::<Value>(content.trim_start_matches('\u{feff}')).ok()
+        })
+        .unwrap_or(Value::Null);
+    let port = settings["local_ai_port_base"]
+        .as_u64()
+        .and_then(|port| u16::try_from(port).ok())
+        .filter(|&port| port >= 1024)
+        .unwrap_or(DEFAULT_LOCAL_AI_PORT);
+    format!("http://{}:{}/", LOCAL_AI_HOST, port)
+}
+
+/// Run the checks for `url` in order, stopping at the first failure.
+pub fn check_backend<N: NetworkProbe>(
+    url: &str,
+    var: impl Fn(&str) -> Option<String>,
+    network: &N,
+) -> BackendReport {
+    let mut report = CheckReport::for_subject(BackendSubject {
+        url: url.to_string(),
+        proxy: None,
+    });
+    let target = match parse_backend_url(url) {
+        Ok(target) => target,
+        Err(err) => {
+            report.record("url", Err(err));
+            return report;
+        }
+    };
+    let proxy = proxy_for(&target, var);
+    report.subject.proxy = proxy
+        .as_ref()
+        .map(|proxy| format!("{}:{} ({})", proxy.host, proxy.port, proxy.source));
+    let (host, port) = match &proxy {
+        Some(proxy) => (proxy.host.as_str(), proxy.port),
+        None => (target.host.as_str(), target.port),
+    };
+
+    let addrs = match network.resolve(host, port) {
+        Ok(addrs) if !addrs.is_empty() => addrs,
+        Ok(_) => {
+            report.record("dns", Err(format!("{} resolved to no addresses", host)));
+            return report;
+        }
+        Err(err) => {
+            report.record("dns", Err(format!("{} did not resolve: {}", host, err)));
+            return report;
+        }
+    };
+    let listed: Vec<String> = addrs.iter().map(|addr| addr.ip().to_string()).collect();
+    report.record("dns", Ok(format!("{} -> {}", host, listed.join(", "))));
+
+    let mut errors = Vec::new();
+    let connected = addrs.iter().find(|&&addr| match network.connect(addr) {
+        Ok(()) => true,
+        Err(err) => {
+            errors.push(format!("{}: {}", addr, err));
+            false
+        }
+    });
+    let reachable = match connected {
+        Some(addr) => Ok(format!("connected to {}", addr)),
+        None => Err(format!("unreachable ({})", errors.join("; "))),
+    };
+    if !report.record("connect", reachable) || !target.https {
+        return report;
+    }
+
+    if let Some(result) = network.tls_handshake(&target, proxy.as_ref()) {
+        report.record("tls", result);
+    }
+    report
+}
+
+/// What a failed WinHTTP request means for the report.
+pub fn winhttp_error_message(code: u32) -> String {
+    let what = match code {
+        ERROR_WINHTTP_SECURE_CERT_DATE_INVALID => "certificate expired or not yet valid",
+        ERROR_WINHTTP_SECURE_CERT_CN_INVALID => "certificate does not match the host name",
+        ERROR_WINHTTP_SECURE_INVALID_CA => "certificate issuer is not trusted",
+        ERROR_WINHTTP_SECURE_CERT_REV_FAILED => "certificate revocation check failed",
+        ERROR_WINHTTP_SECURE_CERT_REVOKED => "certificate is revoked",
+        ERROR_WINHTTP_SECURE_INVALID_CERT => "certificate is invalid",
+        ERROR_WINHTTP_SECURE_CHANNEL_ERROR | ERROR_WINHTTP_SECURE_FAILURE => {
+            "TLS handshake or certificate validation failed"
+        }
+        ERROR_WINHTTP_NAME_NOT_RESOLVED => "host name did not resolve",
+        ERROR_WINHTTP_CANNOT_CONNECT | ERROR_WINHTTP_CONNECTION_ERROR => "connection failed",
+        ERROR_WINHTTP_TIMEOUT => "timed out",
+        ERROR_WINHTTP_INVALID_SERVER_RESPONSE => "invalid response from the server or proxy",
+        _ => "request failed",
+    };
+    format!("{} (WinHTTP error {})", what, code)
+}
+
+/// What a WinHTTP `HEAD` that got an HTTP `status` means for the `tls`
+/// check. Without a server certificate (`handshake` false) the answer came
+/// from the proxy refusing the tunnel, not from the backend.
+pub fn winhttp_response_result(status: u32, handshake: bool) -> Result<String, String> {
+    if status == HTTP_STATUS_PROXY_AUTH_REQUIRED {
+        return Err(format!(
+            "proxy authentication required (HTTP {}); credentials in the proxy URL are not used",
+            status
+        ));
+    }
+    if !handshake {
+        return Err(format!("proxy refused the connection (HTTP {})", status));
+    }
+    Ok(format!(
+        "handshake completed, certificate valid (HTTP {})",
+        status
+    ))
+}
+
+/// DNS and TCP from std; TLS through WinHTTP (Windows certificate store).
+pub struct SystemNetwork;
+
+impl NetworkProbe for SystemNetwork {
+    fn resolve(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>, String> {
+        (host, port)
+            .to_socket_addrs()
+            .map(Iterator::collect)
+            .map_err(|e| e.to_string())
+    }
+
+    fn connect(&self, addr: SocketAddr) -> Result<(), String> {
+        TcpStream::connect_timeout(&addr, Duration::from_millis(BACKEND_TIMEOUT_MS))
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    #[cfg(windows)]
+    fn tls_handshake(
+        &self,
+        target: &BackendTarget,
+        proxy: Option<&ProxyEndpoint>,
+    ) -> Option<Result<String, String>> {
+        Some(winhttp_head(target, proxy))
+    }
+
+    #[cfg(not(windows))]
+    fn tls_handshake(
+        &self,
+        _target: &BackendTarget,
+        _proxy: Option<&ProxyEndpoint>,
+    ) -> Option<Result<String, String>> {
+        None
+    }
+}
+
+/// `HEAD` the URL over WinHTTP; success means the handshake and the
+/// certificate chain were accepted (see [`winhttp_response_result`]).
+#[cfg(windows)]
+fn winhttp_head(target: &BackendTarget, proxy: Option<&ProxyEndpoint>) -> Result<String, String> {
+    use std::ffi::OsStr;
+    use std::iter::once;
+    use std::mem::size_of;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::{null, null_mut};
+    use winapi::shared::minwindef::DWORD;
+    use winapi::um::errhandlingapi::GetLastError;
+    use winapi::um::wincrypt::{CertFreeCertificateContext, PCCERT_CONTEXT};
+    use winapi::um::winhttp::{
+        WinHttpCloseHandle, WinHttpConnect, WinHttpOpen, WinHttpOpenRequest, WinHttpQueryHeaders,
+        WinHttpQueryOption, WinHttpReceiveResponse, WinHttpSendRequest, WinHttpSetTimeouts,
+        HINTERNET, WINHTTP_ACCESS_TYPE_AUTOMATIC_PROXY, WINHTTP_ACCESS_TYPE_DEFAULT_PROXY,
+        WINHTTP_ACCESS_TYPE_NAMED_PROXY, WINHTTP_FLAG_SECURE, WINHTTP_OPTION_SERVER_CERT_CONTEXT,
+        WINHTTP_QUERY_FLAG_NUMBER, WINHTTP_QUERY_STATUS_CODE,
+    };
+
+    fn wide(value: &str) -> Vec<u16> {
+        OsStr::new(value).encode_wide().chain(once(0)).collect()
+    }
+
+    /// Closes the handle when dropped.
+    struct Handle(HINTERNET);
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            unsafe { WinHttpCloseHandle(self.0) };
+        }
+    }
+
+    fn check(handle: HINTERNET) -> Result<Handle, String> {
+        if handle.is_null() {
+            Err(winhttp_error_message(unsafe { GetLastError() }))
+        } else {
+            Ok(Handle(handle))
+        }
+    }
+
+    let agent = wide("YakuLingo-Launcher");
+    let proxy_name = proxy.map(|proxy| wide(&format!("{}:{}", proxy.host, proxy.port)));
+    let session = unsafe {
+        match &proxy_name {
+            Some(name) => WinHttpOpen(
+                agent.as_ptr(),
+                WINHTTP_ACCESS_TYPE_NAMED_PROXY,
+                name.as_ptr(),
+                null(),
+                0,
+            ),
+            None => {
+                // Automatic proxy needs Windows 8.1; fall back to the WinHTTP default.
+                let session = WinHttpOpen(
+                    agent.as_ptr(),
+                    WINHTTP_ACCESS_TYPE_AUTOMATIC_PROXY,
+                    null(),
+                    null(),
+                    0,
+                );
+                if session.is_null() {
+                    WinHttpOpen(
+                        agent.as_ptr(),
+                        WINHTTP_ACCESS_TYPE_DEFAULT_PROXY,
+                        null(),
+                        null(),
+                        0,
+                    )
+                } else {
+                    session
+                }
+            }
+        }
+    };
+    let session = check(session)?;
+    let timeout = BACKEND_TIMEOUT_MS as i32;
+    unsafe { WinHttpSetTimeouts(session.0, timeout, timeout, timeout, timeout) };
+
+    let host = wide(&target.host);
+    let connection = check(unsafe { WinHttpConnect(session.0, host.as_ptr(), target.port, 0) })?;
+    let verb = wide("HEAD");
+    let path = wide(&target.path);
+    let request = check(unsafe {
+        WinHttpOpenRequest(
+            connection.0,
+            verb.as_ptr(),
+            path.as_ptr(),
+            null(),
+            null(),
+            null_mut(),
+            WINHTTP_FLAG_SECURE,
+        )
+    })?;
+    let sent = unsafe {
+        WinHttpSendRequest(request.0, null(), 0, null_mut(), 0, 0, 0) != 0
+            && WinHttpReceiveResponse(request.0, null_mut()) != 0
+    };
+    if !sent {
+        return Err(winhttp_error_message(unsafe { GetLastError() }));
+    }
+
+    let mut status: DWORD = 0;
+    let mut status_len = size_of::<DWORD>() as DWORD;
+    let queried = unsafe {
+        WinHttpQueryHeaders(
+            request.0,
+            WINHTTP_QUERY_STATUS_CODE | WINHTTP_QUERY_FLAG_NUMBER,
+            null(),
+            &mut status as *mut DWORD as *mut _,
+            &mut status_len,
+            null_mut(),
+        ) != 0
+    };
+    if !queried {
+        return Err(winhttp_error_message(unsafe { GetLastError() }));
+    }
+    // Only a completed handshake leaves the backend's certificate behind.
+    let mut cert: PCCERT_CONTEXT = null();
+    let mut cert_len = size_of::<PCCERT_CONTEXT>() as DWORD;
+    let handshake = unsafe {
+        WinHttpQueryOption(
+            request.0,
+            WINHTTP_OPTION_SERVER_CERT_CONTEXT,
+            &mut cert as *mut PCCERT_CONTEXT as *mut _,
+            &mut cert_len,
+        ) != 0
+            && !cert.is_null()
+    };
+    if !cert.is_null() {
+        unsafe { CertFreeCertificateContext(cert) };
+    }
+    winhttp_response_result(status, handshake)
+}
+
+/// `--check-backend[=<url>]`: returns the process exit code.
+pub fn run_check_backend(url: Option<&str>) -> i32 {
+    attach_console();
+    // `load_config` applies `YAKULINGO_BACKEND_URL` over the files.
+    let base_dir = launcher_dir().ok();
+    let url = url
+        .map(str::to_string)
+        .or_else(|| {
+            base_dir
+                .as_ref()
+                .and_then(|dir| load_config(dir, false, &None).backend_url)
+        })
+        .or_else(|| base_dir.as_deref().map(default_backend_url))
+        .unwrap_or_else(|| format!("http://{}:{}/", LOCAL_AI_HOST, DEFAULT_LOCAL_AI_PORT));
+    let report = check_backend(&url, |name| env::var(name).ok(), &SystemNetwork);
+    print_report(&report, 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Controllable network: DNS answer, which addresses accept, TLS outcome.
+    struct StubNetwork {
+        dns: Result<Vec<SocketAddr>, String>,
+        reachable: Vec<SocketAddr>,
+        tls: Option<Result<String, String>>,
+        tls_calls: Cell<u32>,
+    }
+
+    impl StubNetwork {
+        fn reachable() -> Self {
+            let addr: SocketAddr = "203.0.113.10:443".parse().unwrap();
+            StubNetwork {
+                dns: Ok(vec![addr]),
+                reachable: vec![addr],
+                tls: Some(Ok("handshake completed, certificate valid".to_string())),
+                tls_calls: Cell::new(0),
+            }
+        }
+    }
+
+    impl NetworkProbe for StubNetwork {
+        fn resolve(&self, _host: &str, _port: u16) -> Result<Vec<SocketAddr>, String> {
+            self.dns.clone()
+        }
+
+        fn connect(&self, addr: SocketAddr) -> Result<(), String> {
+            if self.reachable.contains(&addr) {
+                Ok(())
+            } else {
+                Err("connection refused".to_string())
+            }
+        }
+
+        fn tls_handshake(
+            &self,
+            _target: &BackendTarget,
+            _proxy: Option<&ProxyEndpoint>,
+        ) -> Option<Result<String, String>> {
+            self.tls_calls.set(self.tls_calls.get() + 1);
+            self.tls.clone()
+        }
+    }
+
+    fn names(report: &BackendReport) -> Vec<(&str, bool)> {
+        report
+            .checks
+            .iter()
+            .map(|check| (check.name.as_str(), check.ok))
+            .collect()
+    }
+
+    const URL: &str = "https://translate.example.com/v1";
+
+    #[test]
+    fn reachable_backend_passes_every_check() {
+        let report = check_backend(URL, |_| None, &StubNetwork::reachable());
+        assert!(report.ok);
+        assert_eq!(
+            names(&report),
+            vec![("dns", true), ("connect", true), ("tls", true)]
+        );
+        assert_eq!(report.subject.proxy, None);
+    }
+
+    #[test]
+    fn dns_failure_stops_before_connecting() {
+        let network = StubNetwork {
+            dns: Err("no such host".to_string()),
+            ..StubNetwork::reachable()
+        };
+        let report = check_backend(URL, |_| None, &network);
+        assert!(!report.ok);
+        assert_eq!(names(&report), vec![("dns", false)]);
+        assert!(report.checks[0].detail.contains("translate.example.com"));
+        assert_eq!(network.tls_calls.get(), 0);
+    }
+
+    #[test]
+    fn tls_failure_is_reported_after_a_good_connect() {
+        let network = StubNetwork {
+            tls: Some(Err(winhttp_error_message(ERROR_WINHTTP_SECURE_INVALID_CA))),
+            ..StubNetwork::reachable()
+        };
+        let report = check_backend(URL, |_| None, &network);
+        assert!(!report.ok);
+        assert_eq!(
+            names(&report),
+            vec![("dns", true), ("connect", true), ("tls", false)]
+        );
+        assert!(report.checks[2].detail.contains("issuer is not trusted"));
+    }
+
+    #[test]
+    fn proxy_answers_fail_the_tls_check() {
+        let network = StubNetwork {
+            tls: Some(winhttp_response_result(407, false)),
+            ..StubNetwork::reachable()
+        };
+        let report = check_backend(URL, |_| None, &network);
+        assert!(!report.ok);
+        assert_eq!(names(&report)[2], ("tls", false));
+        assert!(report.checks[2]
+            .detail
+            .contains("proxy authentication required"));
+
+        assert!(winhttp_response_result(502, false)
+            .unwrap_err()
+            .contains("proxy refused the connection (HTTP 502)"));
+        // Any status the backend sends after the handshake proves TLS works.
+        assert_eq!(
+            winhttp_response_result(404, true),
+            Ok("handshake completed, certificate valid (HTTP 404)".to_string())
+        );
+        assert!(winhttp_error_message(ERROR_WINHTTP_INVALID_SERVER_RESPONSE)
+            .contains("server or proxy"));
+    }
+
+    #[test]
+    fn unreachable_or_plain_http_skips_tls() {
+        let network = StubNetwork {
+            reachable: Vec::new(),
+            ..StubNetwork::reachable()
+        };
+        let report = check_backend(URL, |_| None, &network);
+        assert_eq!(names(&report), vec![("dns", true), ("connect", false)]);
+
+        let network = StubNetwork::reachable();
+        let report = check_backend("http://127.0.0.1:4891/", |_| None, &network);
+        assert!(report.ok);
+        assert_eq!(network.tls_calls.get(), 0);
+
+        let report = check_backend("ftp://example.com", |_| None, &network);
+        assert_eq!(names(&report), vec![("url", false)]);
+    }
+
+    #[test]
+    fn proxy_settings_are_applied() {
+        let target = parse_backend_url(URL).unwrap();
+        let env = |no_proxy: &'static str| {
+            move |name: &str| match name {
+                "HTTPS_PROXY" => Some("http://user:pw@proxy.corp:3128/".to_string()),
+                "NO_PROXY" => Some(no_proxy.to_string()),
+                _ => None,
+            }
+        };
+        assert_eq!(
+            proxy_for(&target, env("localhost")),
+            Some(ProxyEndpoint {
+                host: "proxy.corp".to_string(),
+                port: 3128,
+                source: "HTTPS_PROXY",
+            })
+        );
+        assert_eq!(proxy_for(&target, env("localhost, .example.com")), None);
+        assert_eq!(proxy_for(&target, env("*")), None);
+
+        let report = check_backend(URL, env(""), &StubNetwork::reachable());
+        assert_eq!(
+            report.subject.proxy.as_deref(),
+            Some("proxy.corp:3128 (HTTPS_PROXY)")
+        );
+    }
+
+    #[test]
+    fn default_url_follows_the_app_settings() {
+        let dir = crate::test_util::temp_dir("backend-default");
+        assert_eq!(default_backend_url(&dir), "http://127.0.0.1:4891/");
+
+        fs::create_dir_all(dir.join("config")).unwrap();
+        let template = dir.join(APP_SETTINGS_TEMPLATE);
+        fs::write(
+            &template,
+            "\u{feff}{\"local_ai_host\": \"127.0.0.1\", \"local_ai_port_base\": 5100}",
+        )
+        .unwrap();
+        assert_eq!(default_backend_url(&dir), "http://127.0.0.1:5100/");
+
+        // Like the app: other hosts become localhost, bad ports the default.
+        fs::write(
+            &template,
+            "{\"local_ai_host\": \"0.0.0.0\", \"local_ai_port_base\": 80}",
+        )
+        .unwrap();
+        assert_eq!(default_backend_url(&dir), "http://127.0.0.1:4891/");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn parses_backend_urls() {
+        let target = parse_backend_url("https://[::1]:8443/api?x=1").unwrap();
+        assert_eq!(
+            (target.host.as_str(), target.port, target.path.as_str()),
+            ("::1", 8443, "/api?x=1")
+        );
+        let target = parse_backend_url("http://example.com").unwrap();
+        assert_eq!((target.port, target.path.as_str()), (80, "/"));
+        assert!(parse_backend_url("https://user:pw@example.com").is_err());
+        assert!(parse_backend_url("https://example.com:0").is_err());
+    }
+}
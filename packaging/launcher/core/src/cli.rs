@@ -0,0 +1,281 @@
+//! Command-line options for the launcher.
+
+use std::path::PathBuf;
+
+use crate::LaunchMode;
+
+const MAX_INSTANCE_NAME_LEN: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchOptions {
+    pub mode: LaunchMode,
+    /// `--instance <name>`: run a separately named instance (own port,
+    /// profile dir, window title and single-instance mutex).
+    pub instance_name: Option<String>,
+    /// `--foreground`: stream `launcher.log` to a console while running.
+    pub foreground: bool,
+    /// `--kiosk`: start from the kiosk config profile.
+    pub kiosk: bool,
+    /// `--no-restart`: supervise one run of the app, never restart it.
+    pub no_restart: bool,
+    /// `--verbose`: also echo log lines to a console.
+    pub verbose: bool,
+    /// `--port <N>`: app port for this run, over the configured one.
+    pub port: Option<u16>,
+    /// `--check`: validate this install's environment and exit.
+    pub check: bool,
+    /// `--verify-install <dir>`: check an install dir instead of launching.
+    pub verify_install: Option<PathBuf>,
+    /// `--headless`: with `--verify-install`, also start and stop the app
+    /// without a window.
+    pub headless: bool,
+    /// `--export-config <path>`: write the effective config and exit.
+    pub export_config: Option<PathBuf>,
+    /// `--import-config <path>`: validate and install a config, then exit.
+    pub import_config: Option<PathBuf>,
+    /// `--machine`: with `--import-config`, replace the install dir's
+    /// `launcher.toml` instead of the per-user one.
+    pub machine: bool,
+    /// `--check-backend[=<url>]`: check the backend's reachability and exit.
+    pub check_backend: bool,
+    /// The `<url>` of `--check-backend=<url>`.
+    pub backend_url: Option<String>,
+    /// Arguments that are not launcher flags (typically document paths),
+    /// handed to an already-running instance or passed to a new app.
+    pub forward_args: Vec<String>,
+}
+
+impl Default for LaunchOptions {
+    fn default() -> Self {
+        LaunchOptions {
+            mode: LaunchMode::Watchdog,
+            instance_name: None,
+            foreground: false,
+            kiosk: false,
+            no_restart: false,
+            verbose: false,
+            port: None,
+            check: false,
+            verify_install: None,
+            headless: false,
+            export_config: None,
+            import_config: None,
+            machine: false,
+            check_backend: false,
+            backend_url: None,
+            forward_args: Vec::new(),
+        }
+    }
+}
+
+/// Parse launcher flags (program name already stripped). Unrecognized
+/// arguments are kept in `forward_args`.
+pub fn parse_args<I>(args: I) -> Result<LaunchOptions, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut options = LaunchOptions::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--detach" {
+            options.mode = LaunchMode::Detach;
+        } else if arg == "--foreground" {
+            options.foreground = true;
+        } else if arg == "--kiosk" {
+            options.kiosk = true;
+        } else if arg == "--no-restart" {
+            options.no_restart = true;
+        } else if arg == "--verbose" {
+            options.verbose = true;
+        } else if arg == "--port" {
+            let value = args.next().ok_or("--port requires a port number")?;
+            options.port = Some(parse_port(&value)?);
+        } else if let Some(value) = arg.strip_prefix("--port=") {
+            options.port = Some(parse_port(value)?);
+        } else if arg == "--check" {
+            options.check = true;
+        } else if arg == "--instance" {
+            let value = args
+                .next()
+                .ok_or("--instance requires a name (e.g. --instance work)")?;
+            options.instance_name = Some(validate_instance_name(&value)?);
+        } else if let Some(value) = arg.strip_prefix("--instance=") {
+            options.instance_name = Some(validate_instance_name(value)?);
+        } else if arg == "--verify-install" {
+            let value = args
+                .next()
+                .ok_or("--verify-install requires an install directory")?;
+            options.verify_install = Some(PathBuf::from(value));
+        } else if let Some(value) = arg.strip_prefix("--verify-install=") {
+            options.verify_install = Some(PathBuf::from(value));
+        } else if arg == "--headless" {
+            options.headless = true;
+        } else if arg == "--export-config" {
+            let value = args.next().ok_or("--export-config requires a file path")?;
+            options.export_config = Some(PathBuf::from(value));
+        } else if let Some(value) = arg.strip_prefix("--export-config=") {
+            options.export_config = Some(PathBuf::from(value));
+        } else if arg == "--import-config" {
+            let value = args.next().ok_or("--import-config requires a file path")?;
+            options.import_config = Some(PathBuf::from(value));
+        } else if let Some(value) = arg.strip_prefix("--import-config=") {
+            options.import_config = Some(PathBuf::from(value));
+        } else if arg == "--machine" {
+            options.machine = true;
+        } else if arg == "--check-backend" {
+            options.check_backend = true;
+        } else if let Some(value) = arg.strip_prefix("--check-backend=") {
+            options.check_backend = true;
+            options.backend_url = Some(value.to_string());
+        } else {
+            options.forward_args.push(arg);
+        }
+    }
+    Ok(options)
+}
+
+fn parse_port(value: &str) -> Result<u16, String> {
+    match value.trim().parse::<u16>() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(format!(
+            "Invalid port {:?}.\n\nUse a number from 1 to 65535.",
+            value
+        )),
+    }
+}
+
+/// Instance names end up in mutex names, file names and env vars, so keep
+/// them to a short, case-insensitive `[a-z0-9_-]` token.
+pub fn validate_instance_name(name: &str) -> Result<String, String> {
+    let name = name.trim().to_ascii_lowercase();
+    let valid = !name.is_empty()
+        && name.len() <= MAX_INSTANCE_NAME_LEN
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+    if valid {
+        Ok(name)
+    } else {
+        Err(format!(
+            "Invalid instance name {:?}.\n\nUse up to {} letters, digits, '-' or '_'.",
+            name, MAX_INSTANCE_NAME_LEN
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn no_args_means_watchdog_default_instance() {
+        assert_eq!(parse_args(args(&[])).unwrap(), LaunchOptions::default());
+    }
+
+    #[test]
+    fn parses_detach_and_instance_forms() {
+        let options = parse_args(args(&["--detach", "--instance", "Work"])).unwrap();
+        assert_eq!(options.mode, LaunchMode::Detach);
+        assert_eq!(options.instance_name.as_deref(), Some("work"));
+
+        let options = parse_args(args(&["--instance=personal"])).unwrap();
+        assert_eq!(options.instance_name.as_deref(), Some("personal"));
+        assert!(!options.foreground);
+
+        let options = parse_args(args(&["--foreground", "--kiosk"])).unwrap();
+        assert!(options.foreground);
+        assert!(options.kiosk);
+        assert_eq!(options.mode, LaunchMode::Watchdog);
+    }
+
+    #[test]
+    fn parses_debugging_flags() {
+        let options = parse_args(args(&["--no-restart", "--verbose", "--port", "9000"])).unwrap();
+        assert!(options.no_restart);
+        assert!(options.verbose);
+        assert_eq!(options.port, Some(9000));
+        assert_eq!(options.mode, LaunchMode::Watchdog);
+        let options = parse_args(args(&["--port=8766", "--debug-ui"])).unwrap();
+        assert_eq!(options.port, Some(8766));
+        assert_eq!(options.forward_args, args(&["--debug-ui"]));
+        assert!(parse_args(args(&["--port"])).is_err());
+        assert!(parse_args(args(&["--port", "0"])).is_err());
+        assert!(parse_args(args(&["--port=70000"])).is_err());
+    }
+
+    #[test]
+    fn unrecognized_args_are_kept_for_forwarding() {
+        let options = parse_args(args(&[
+            "--detach",
+            "C:\\Docs\\report.docx",
+            "--lang=en",
+            "notes.txt",
+        ]))
+        .unwrap();
+        assert_eq!(options.mode, LaunchMode::Detach);
+        assert_eq!(
+            options.forward_args,
+            args(&["C:\\Docs\\report.docx", "--lang=en", "notes.txt"])
+        );
+    }
+
+    #[test]
+    fn parses_verify_install() {
+        let options =
+            parse_args(args(&["--verify-install", "dist/YakuLingo", "--headless"])).unwrap();
+        assert_eq!(
+            options.verify_install,
+            Some(PathBuf::from("dist/YakuLingo"))
+        );
+        assert!(options.headless);
+        let options = parse_args(args(&["--verify-install=out"])).unwrap();
+        assert_eq!(options.verify_install, Some(PathBuf::from("out")));
+        assert!(!options.headless);
+        assert!(parse_args(args(&["--verify-install"])).is_err());
+    }
+
+    #[test]
+    fn parses_config_transfer() {
+        let options = parse_args(args(&["--export-config", "known-good.toml"])).unwrap();
+        assert_eq!(
+            options.export_config,
+            Some(PathBuf::from("known-good.toml"))
+        );
+        let options = parse_args(args(&["--import-config=known-good.toml", "--machine"])).unwrap();
+        assert_eq!(
+            options.import_config,
+            Some(PathBuf::from("known-good.toml"))
+        );
+        assert!(options.machine);
+        assert!(parse_args(args(&["--import-config"])).is_err());
+    }
+
+    #[test]
+    fn parses_check() {
+        let options = parse_args(args(&["--check", "--port", "9000"])).unwrap();
+        assert!(options.check);
+        assert_eq!(options.port, Some(9000));
+        assert!(!parse_args(args(&["--check-backend"])).unwrap().check);
+    }
+
+    #[test]
+    fn parses_check_backend() {
+        let options = parse_args(args(&["--check-backend"])).unwrap();
+        assert!(options.check_backend);
+        assert_eq!(options.backend_url, None);
+        let options = parse_args(args(&["--check-backend=https://example.com"])).unwrap();
+        assert_eq!(options.backend_url.as_deref(), Some("https://example.com"));
+    }
+
+    #[test]
+    fn rejects_missing_or_unsafe_instance_names() {
+        assert!(parse_args(args(&["--instance"])).is_err());
+        assert!(parse_args(args(&["--instance", "../evil"])).is_err());
+        assert!(parse_args(args(&["--instance", ""])).is_err());
+        assert!(validate_instance_name(&"x".repeat(MAX_INSTANCE_NAME_LEN + 1)).is_err());
+    }
+}
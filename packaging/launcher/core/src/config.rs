@@ -0,0 +1,569 @@
+//! Launcher configuration.
+//!
+//! Precedence, lowest first: defaults, the `--kiosk` profile, the machine
+//! `launcher.toml` next to the exe, the per-user `launcher.toml` (see [`user_config_path`]), then
+//! `YAKULINGO_*` environment overrides. Files are layered key by key, so a
+//! user file may set only the keys it cares about. A missing file is
+//! skipped; a malformed one is logged and ignored so the launch still
+//! proceeds. CLI flags are applied on top by the caller.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use toml::Table;
+
+use crate::log::log_event;
+use crate::paths::get_home_dir;
+use crate::registry::PublishLocations;
+use crate::snapshot::SnapshotPolicy;
+use crate::supervisor::HelperSpec;
+
+pub const CONFIG_FILE_NAME: &str = "launcher.toml";
+
+/// Which launches count as "the same instance".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SingletonScope {
+    /// One instance per machine: `Global\` mutex and the shared port.
+    #[default]
+    PerMachine,
+    /// One instance per logon session (terminal servers / RDP): `Local\`
+    /// mutex and a port derived from the session ID.
+    PerUser,
+}
+
+impl SingletonScope {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "per-machine" | "machine" => Some(SingletonScope::PerMachine),
+            "per-user" | "user" => Some(SingletonScope::PerUser),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LauncherConfig {
+    pub singleton_scope: SingletonScope,
+    /// Start python.exe inside the AppContainer sandbox.
+    pub app_container: bool,
+    /// Restart the app when its heartbeat file is older than this; 0 disables.
+    pub heartbeat_timeout_sec: u64,
+    /// Registry hives that receive the resolved state/log directories.
+    pub publish_locations: PublishLocations,
+    /// Script to run instead of `app.py`/`app.pyc`, relative to the install dir.
+    pub entrypoint: Option<String>,
+    /// Module to run with `python -m`; takes precedence over scripts.
+    pub entry_module: Option<String>,
+    /// Bundled CPython to use (`3.11` or `3.11.9`) when `.uv-python` holds
+    /// several and pyvenv.cfg does not name one; default the newest.
+    pub python_version: Option<String>,
+    /// Daily local-time window (`"02:00-03:00"`) with no automatic restarts.
+    pub maintenance_window: Option<String>,
+    /// Log a pseudonymous machine fingerprint once per session (off for privacy).
+    pub machine_fingerprint: bool,
+    /// Restart the app when `launcher.toml` or `app_config_file` changes.
+    pub watch_config: bool,
+    /// App config file watched with `watch_config`, relative to the install dir.
+    pub app_config_file: Option<String>,
+    /// Remove arch-specific caches when the state dir was used by a
+    /// different-architecture build.
+    pub clear_arch_caches: bool,
+    /// Keep restarting after unexpected exits instead of giving up after
+    /// the restart limit.
+    pub restart_always: bool,
+    /// Base port the app listens on; default `APP_PORT`. The resolved port
+    /// is handed to the app as `YAKULINGO_PORT`.
+    pub app_port: Option<u16>,
+    /// When another application holds the port, start on a free one
+    /// instead of refusing to launch.
+    pub port_fallback: bool,
+    /// Unexpected exits restarted before giving up; default `MAX_RESTARTS`.
+    pub max_restarts: Option<u32>,
+    /// First restart delay in seconds, doubled per attempt; default 1.
+    pub restart_backoff_base_sec: Option<u64>,
+    /// Ceiling on a single restart delay in seconds; default 30.
+    pub restart_backoff_max_sec: Option<u64>,
+    /// A run lasting this many seconds resets the restart count; default 60.
+    pub restart_reset_after_sec: Option<u64>,
+    /// Stop restarting after this many seconds of back-to-back restarts,
+    /// even with `restart_always`; 0 (the default) never does.
+    pub restart_give_up_after_sec: u64,
+    /// Seconds to wait for the app window after launch; default 30.
+    pub window_wait_sec: Option<u64>,
+    /// Seconds to wait after the restart limit before trying again with a
+    /// fresh budget; 0 gives up as before.
+    pub restart_cooldown_sec: u64,
+    /// Write dialog messages to the log instead of showing message boxes.
+    pub quiet_dialogs: bool,
+    /// Updater run for `deferred_update.json`: program relative to the
+    /// install dir, then its arguments.
+    pub updater: Vec<String>,
+    /// `[[helpers]]`: processes supervised alongside the app in watchdog mode.
+    pub helpers: Vec<HelperSpec>,
+    /// Gzip rotated logs (`launcher.log.1.gz`); the active log stays plain.
+    pub compress_rotated_logs: bool,
+    /// Rotate `launcher.log` past this many bytes; default `LOG_ROTATE_BYTES`.
+    pub log_rotate_bytes: Option<u64>,
+    /// Rotated logs kept; default `LOG_KEEP_ROTATED`.
+    pub log_keep_rotated: Option<u32>,
+    /// When non-empty, the app inherits only these environment variables
+    /// (plus the required ones); a trailing `*` matches a prefix.
+    pub env_allowlist: Vec<String>,
+    /// Inherited environment variables withheld from the app.
+    pub env_denylist: Vec<String>,
+    /// Keep the last lines of the app's stderr for crash reports (watchdog only).
+    pub capture_stderr_tail: bool,
+    /// Keep the app's recent stdout/stderr and save it to `app.log` on crash
+    /// exits (watchdog only); on unless set to `false`.
+    pub capture_app_output: Option<bool>,
+    /// Translation backend checked by `--check-backend`.
+    pub backend_url: Option<String>,
+    /// Diagnostic snapshot next to the log on crash exits (`"off"` disables).
+    pub crash_snapshots: SnapshotPolicy,
+    /// Show a splash window until the app window appears (Windows only).
+    pub splash: bool,
+    /// Splash image relative to the install dir; default `splash.png` /
+    /// `splash.bmp` beside the exe, else the built-in icon.
+    pub splash_image: Option<String>,
+    /// Splash caption; default `DEFAULT_SPLASH_CAPTION`.
+    pub splash_caption: Option<String>,
+}
+
+/// Defaults applied by `--kiosk`, below every config file and env override.
+pub fn kiosk_profile() -> Table {
+    let mut profile = Table::new();
+    profile.insert("restart_always".into(), true.into());
+    profile.insert("quiet_dialogs".into(), true.into());
+    profile.insert("heartbeat_timeout_sec".into(), 60.into());
+    profile
+}
+
+impl LauncherConfig {
+    /// Apply `YAKULINGO_*` overrides (looked up through `var`) on top of file values.
+    fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>, log_path: &Option<PathBuf>) {
+        if let Some(value) = var("YAKULINGO_SINGLETON_SCOPE") {
+            match SingletonScope::parse(&value) {
+                Some(scope) => self.singleton_scope = scope,
+                None => log_event(
+                    log_path,
+                    &format!("Ignoring invalid YAKULINGO_SINGLETON_SCOPE: {:?}", value),
+                ),
+            }
+        }
+        if let Some(value) = var("YAKULINGO_APP_CONTAINER") {
+            self.app_container = value == "1";
+        }
+        if let Some(value) = var("YAKULINGO_ENTRY_MODULE") {
+            self.entry_module = Some(value);
+        }
+        if let Some(value) = var("YAKULINGO_BACKEND_URL") {
+            self.backend_url = Some(value);
+        }
+        if let Some(value) = var("YAKULINGO_PUBLISH_LOCATIONS") {
+            match PublishLocations::parse(&value) {
+                Some(locations) => self.publish_locations = locations,
+                None => log_event(
+                    log_path,
+                    &format!("Ignoring invalid YAKULINGO_PUBLISH_LOCATIONS: {:?}", value),
+                ),
+            }
+        }
+        if let Some(value) = var("YAKULINGO_HEARTBEAT_TIMEOUT_SEC") {
+            match value.trim().parse() {
+                Ok(secs) => self.heartbeat_timeout_sec = secs,
+                Err(_) => log_event(
+                    log_path,
+                    &format!(
+                        "Ignoring invalid YAKULINGO_HEARTBEAT_TIMEOUT_SEC: {:?}",
+                        value
+                    ),
+                ),
+            }
+        }
+    }
+}
+
+/// Per-user overrides: `%APPDATA%\YakuLingo\launcher.toml` on Windows,
+/// `$XDG_CONFIG_HOME/yakulingo/launcher.toml` (default `~/.config`) elsewhere.
+pub fn user_config_path() -> Option<PathBuf> {
+    let dir = if cfg!(windows) {
+        PathBuf::from(env::var_os("APPDATA")?).join("YakuLingo")
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| get_home_dir().map(|home| home.join(".config")))?
+            .join("yakulingo")
+    };
+    Some(dir.join(CONFIG_FILE_NAME))
+}
+
+/// Load the machine `launcher.toml` from `base_dir`, layer the per-user file
+/// over it and apply environment overrides. `kiosk` starts from
+/// [`kiosk_profile`] instead of the plain defaults.
+pub fn load_config(base_dir: &Path, kiosk: bool, log_path: &Option<PathBuf>) -> LauncherConfig {
+    let profile = if kiosk { kiosk_profile() } else { Table::new() };
+    load_layered(
+        profile,
+        &base_dir.join(CONFIG_FILE_NAME),
+        user_config_path().as_deref(),
+        |name| env::var(name).ok(),
+        log_path,
+    )
+}
+
+/// Only the machine `launcher.toml` in `base_dir`, with no user file or
+/// environment overrides: what an install ships with.
+pub fn load_machine_config(base_dir: &Path) -> LauncherConfig {
+    load_layered(
+        Table::new(),
+        &base_dir.join(CONFIG_FILE_NAME),
+        None,
+        |_| None,
+        &None,
+    )
+}
+
+/// Whether the config file at `path` is valid; a missing file is.
+pub fn check_config_file(path: &Path) -> Result<(), String> {
+    match fs::read_to_string(path) {
+        Ok(content) => parse_config_layer(&content).map(|_| ()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn load_layered(
+    profile: Table,
+    machine_path: &Path,
+    user_path: Option<&Path>,
+    var: impl Fn(&str) -> Option<String>,
+    log_path: &Option<PathBuf>,
+) -> LauncherConfig {
+    let mut table = profile;
+    for path in std::iter::once(machine_path).chain(user_path) {
+        if let Some(layer) = read_config_layer(path, log_path) {
+            table.extend(layer);
+        }
+    }
+    // Every layer deserialized on its own, so the merge cannot fail.
+    let mut config: LauncherConfig = table.try_into().unwrap_or_default();
+    config.apply_env(var, log_path);
+    config
+}
+
+/// Keys set by one config file, or `None` when it is missing or malformed.
+fn read_config_layer(path: &Path, log_path: &Option<PathBuf>) -> Option<Table> {
+    let content = fs::read_to_string(path).ok()?;
+    match parse_config_layer(&content) {
+        Ok(table) => {
+            log_event(log_path, &format!("Loaded config: {:?}", path));
+            Some(table)
+        }
+        Err(err) => {
+            log_event(log_path, &format!("Invalid {:?} - ignoring: {}", path, err));
+            None
+        }
+    }
+}
+
+pub(crate) fn parse_config_layer(content: &str) -> Result<Table, String> {
+    toml::from_str::<Table>(content)
+        .and_then(|table| table.clone().try_into::<LauncherConfig>().map(|_| table))
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::supervisor::RestartPolicy;
+    use crate::test_util::temp_dir;
+
+    fn read_config_file(path: &Path, log_path: &Option<PathBuf>) -> LauncherConfig {
+        load_layered(Table::new(), path, None, |_| None, log_path)
+    }
+
+    fn write_layers(name: &str, machine: Option<&str>, user: Option<&str>) -> (PathBuf, PathBuf) {
+        let dir = temp_dir(name);
+        fs::create_dir_all(dir.join("user")).unwrap();
+        let machine_path = dir.join(CONFIG_FILE_NAME);
+        let user_path = dir.join("user").join(CONFIG_FILE_NAME);
+        if let Some(content) = machine {
+            fs::write(&machine_path, content).unwrap();
+        }
+        if let Some(content) = user {
+            fs::write(&user_path, content).unwrap();
+        }
+        (dir, user_path)
+    }
+
+    #[test]
+    fn missing_file_yields_defaults() {
+        let dir = temp_dir("config-missing");
+        let config = read_config_file(&dir.join(CONFIG_FILE_NAME), &None);
+        assert_eq!(config, LauncherConfig::default());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn file_sets_singleton_scope() {
+        let dir = temp_dir("config-scope");
+        let path = dir.join(CONFIG_FILE_NAME);
+        fs::write(&path, "singleton_scope = \"per-user\"\n").unwrap();
+        let config = read_config_file(&path, &None);
+        assert_eq!(config.singleton_scope, SingletonScope::PerUser);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn file_enables_app_container() {
+        let dir = temp_dir("config-app-container");
+        let path = dir.join(CONFIG_FILE_NAME);
+        fs::write(&path, "app_container = true\n").unwrap();
+        let config = read_config_file(&path, &None);
+        assert!(config.app_container);
+        assert_eq!(config.singleton_scope, SingletonScope::PerMachine);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn file_overrides_port_and_restart_limits() {
+        let dir = temp_dir("config-limits");
+        let path = dir.join(CONFIG_FILE_NAME);
+        fs::write(
+            &path,
+            "app_port = 9765\nmax_restarts = 5\nrestart_reset_after_sec = 600\nwindow_wait_sec = 90\n",
+        )
+        .unwrap();
+        let config = read_config_file(&path, &None);
+        assert_eq!(config.app_port, Some(9765));
+        assert_eq!(config.max_restarts, Some(5));
+        assert_eq!(config.restart_backoff_base_sec, None);
+        assert_eq!(config.restart_reset_after_sec, Some(600));
+        assert_eq!(config.window_wait_sec, Some(90));
+
+        fs::write(&path, "app_port = 70000\n").unwrap();
+        assert_eq!(read_config_file(&path, &None), LauncherConfig::default());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn heartbeat_is_disabled_by_default() {
+        assert_eq!(LauncherConfig::default().heartbeat_timeout_sec, 0);
+        let dir = temp_dir("config-heartbeat");
+        let path = dir.join(CONFIG_FILE_NAME);
+        fs::write(&path, "heartbeat_timeout_sec = 45\n").unwrap();
+        assert_eq!(read_config_file(&path, &None).heartbeat_timeout_sec, 45);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn app_output_capture_is_unset_until_turned_off() {
+        assert_eq!(LauncherConfig::default().capture_app_output, None);
+        let dir = temp_dir("config-capture");
+        let path = dir.join(CONFIG_FILE_NAME);
+        fs::write(&path, "capture_app_output = false\n").unwrap();
+        assert_eq!(
+            read_config_file(&path, &None).capture_app_output,
+            Some(false)
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn splash_is_off_until_enabled() {
+        assert!(!LauncherConfig::default().splash);
+        let dir = temp_dir("config-splash");
+        let path = dir.join(CONFIG_FILE_NAME);
+        fs::write(
+            &path,
+            "splash = true\nsplash_image = \"brand/acme.png\"\nsplash_caption = \"Starting Acme\"\n",
+        )
+        .unwrap();
+        let config = read_config_file(&path, &None);
+        assert!(config.splash);
+        assert_eq!(config.splash_image.as_deref(), Some("brand/acme.png"));
+        assert_eq!(config.splash_caption.as_deref(), Some("Starting Acme"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn helpers_default_to_none_and_parse_from_tables() {
+        assert!(LauncherConfig::default().helpers.is_empty());
+        let dir = temp_dir("config-helpers");
+        let path = dir.join(CONFIG_FILE_NAME);
+        fs::write(
+            &path,
+            "[[helpers]]\nname = \"model-server\"\ncommand = [\"llm\\\\server.exe\", \"--port\", \"8766\"]\nrestart = \"always\"\nready_port = 8766\n",
+        )
+        .unwrap();
+        let helpers = read_config_file(&path, &None).helpers;
+        assert_eq!(
+            helpers,
+            [HelperSpec {
+                name: "model-server".to_string(),
+                command: vec![
+                    "llm\\server.exe".to_string(),
+                    "--port".to_string(),
+                    "8766".to_string()
+                ],
+                restart: RestartPolicy::Always,
+                ready_port: Some(8766),
+            }]
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn malformed_file_falls_back_to_defaults() {
+        let dir = temp_dir("config-malformed");
+        let path = dir.join(CONFIG_FILE_NAME);
+        fs::write(&path, "singleton_scope = \"everyone\"\n").unwrap();
+        assert_eq!(read_config_file(&path, &None), LauncherConfig::default());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn precedence_is_defaults_machine_user_env() {
+        let (dir, user_path) = write_layers(
+            "config-precedence",
+            Some("singleton_scope = \"per-user\"\nheartbeat_timeout_sec = 30\napp_container = true\n"),
+            Some("heartbeat_timeout_sec = 60\nentry_module = \"user.main\"\n"),
+        );
+        let env = |name: &str| (name == "YAKULINGO_ENTRY_MODULE").then(|| "env.main".to_string());
+        let config = load_layered(
+            Table::new(),
+            &dir.join(CONFIG_FILE_NAME),
+            Some(&user_path),
+            env,
+            &None,
+        );
+        // Machine only.
+        assert_eq!(config.singleton_scope, SingletonScope::PerUser);
+        assert!(config.app_container);
+        // User over machine.
+        assert_eq!(config.heartbeat_timeout_sec, 60);
+        // Env over user.
+        assert_eq!(config.entry_module.as_deref(), Some("env.main"));
+        // Set nowhere.
+        assert_eq!(config.publish_locations, PublishLocations::default());
+        assert_eq!(config.maintenance_window, None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn partial_user_file_keeps_other_machine_keys() {
+        let (dir, user_path) = write_layers(
+            "config-partial",
+            Some("maintenance_window = \"02:00-03:00\"\nwatch_config = true\n"),
+            Some("watch_config = false\n"),
+        );
+        let config = load_layered(
+            Table::new(),
+            &dir.join(CONFIG_FILE_NAME),
+            Some(&user_path),
+            |_| None,
+            &None,
+        );
+        assert!(!config.watch_config);
+        assert_eq!(config.maintenance_window.as_deref(), Some("02:00-03:00"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn user_file_applies_without_machine_file() {
+        let (dir, user_path) =
+            write_layers("config-user-only", None, Some("app_container = true\n"));
+        let config = load_layered(
+            Table::new(),
+            &dir.join(CONFIG_FILE_NAME),
+            Some(&user_path),
+            |_| None,
+            &None,
+        );
+        assert!(config.app_container);
+        assert_eq!(config.singleton_scope, SingletonScope::PerMachine);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn malformed_user_file_is_skipped_not_the_machine_file() {
+        let (dir, user_path) = write_layers(
+            "config-user-malformed",
+            Some("heartbeat_timeout_sec = 30\n"),
+            Some("heartbeat_timeout_sec = \"soon\"\napp_container = true\n"),
+        );
+        let config = load_layered(
+            Table::new(),
+            &dir.join(CONFIG_FILE_NAME),
+            Some(&user_path),
+            |_| None,
+            &None,
+        );
+        assert_eq!(config.heartbeat_timeout_sec, 30);
+        assert!(!config.app_container, "no keys from a rejected layer");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn invalid_env_value_keeps_file_value() {
+        let (dir, _) = write_layers(
+            "config-env-invalid",
+            Some("singleton_scope = \"per-user\"\n"),
+            None,
+        );
+        let env =
+            |name: &str| (name == "YAKULINGO_SINGLETON_SCOPE").then(|| "everyone".to_string());
+        let config = load_layered(Table::new(), &dir.join(CONFIG_FILE_NAME), None, env, &None);
+        assert_eq!(config.singleton_scope, SingletonScope::PerUser);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn kiosk_profile_matches_documented_settings() {
+        let dir = temp_dir("config-kiosk");
+        let config = load_layered(
+            kiosk_profile(),
+            &dir.join(CONFIG_FILE_NAME),
+            None,
+            |_| None,
+            &None,
+        );
+        assert_eq!(
+            config,
+            LauncherConfig {
+                restart_always: true,
+                quiet_dialogs: true,
+                heartbeat_timeout_sec: 60,
+                ..LauncherConfig::default()
+            }
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn config_and_env_override_kiosk_profile() {
+        let (dir, user_path) = write_layers(
+            "config-kiosk-override",
+            Some("quiet_dialogs = false\n"),
+            Some("heartbeat_timeout_sec = 0\n"),
+        );
+        let env = |name: &str| (name == "YAKULINGO_APP_CONTAINER").then(|| "1".to_string());
+        let config = load_layered(
+            kiosk_profile(),
+            &dir.join(CONFIG_FILE_NAME),
+            Some(&user_path),
+            env,
+            &None,
+        );
+        assert!(config.restart_always);
+        assert!(!config.quiet_dialogs);
+        assert_eq!(config.heartbeat_timeout_sec, 0);
+        assert!(config.app_container);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
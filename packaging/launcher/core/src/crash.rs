@@ -0,0 +1,281 @@
+//! Crash diagnostics: the app's own `startup_error.json` and a short crash history.
+//!
+//! `startup_error.json` (written by the app in the state dir before exiting):
+//!
+//! ```json
+//! {"reason": "ImportError: No module named 'nicegui'", "traceback": "...", "ts": 1700000000.0}
+//! ```
+//!
+//! `reason` is required; `traceback` is an optional summary. Files older than
+//! `STARTUP_ERROR_TTL_SEC` belong to an earlier run and are ignored.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::log::log_event;
+use crate::paths::get_state_dir;
+use crate::{INSTANCE_ALREADY_RUNNING_CODE, UPDATE_IN_PROGRESS_CODE, USER_EXIT_CODE};
+
+const STARTUP_ERROR_TTL_SEC: u64 = 300;
+const MAX_CRASH_HISTORY: usize = 20;
+/// Keep the dialog readable; the tail of a traceback holds the actual error.
+const MAX_DIALOG_TRACEBACK_CHARS: usize = 1500;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StartupError {
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub traceback: Option<String>,
+    pub ts: f64,
+}
+
+impl StartupError {
+    pub fn dialog_message(&self) -> String {
+        let mut message = format!("YakuLingo failed to start.\n\n{}", self.reason);
+        if let Some(traceback) = self.traceback.as_deref().map(str::trim) {
+            if !traceback.is_empty() {
+                message.push_str("\n\n");
+                message.push_str(tail_chars(traceback, MAX_DIALOG_TRACEBACK_CHARS));
+            }
+        }
+        message
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrashRecord {
+    pub ts: u64,
+    pub exit_code: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub startup_error: Option<StartupError>,
+    /// Last lines of the app's stderr, with `capture_stderr_tail`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stderr_tail: Option<String>,
+}
+
+/// Dialog shown when the watchdog gives up: the app's startup error if it
+/// left one, else (or in place of a missing traceback) its stderr tail.
+pub fn crash_dialog_message(
+    exit_code: i32,
+    startup_error: Option<&StartupError>,
+    stderr_tail: Option<&str>,
+) -> Option<String> {
+    let tail = stderr_tail
+        .map(str::trim)
+        .filter(|tail| !tail.is_empty())
+        .map(|tail| tail_chars(tail, MAX_DIALOG_TRACEBACK_CHARS));
+    match (startup_error, tail) {
+        (Some(error), Some(tail)) if error.traceback.is_none() => {
+            Some(format!("{}\n\n{}", error.dialog_message(), tail))
+        }
+        (Some(error), _) => Some(error.dialog_message()),
+        (None, Some(tail)) => Some(format!(
+            "YakuLingo stopped unexpectedly (exit code {}).\n\n{}",
+            exit_code, tail
+        )),
+        (None, None) => None,
+    }
+}
+
+/// Exit codes the app uses on purpose (and 0) are not crashes.
+pub fn is_crash_exit(exit_code: i32) -> bool {
+    !matches!(
+        exit_code,
+        0 | USER_EXIT_CODE | INSTANCE_ALREADY_RUNNING_CODE | UPDATE_IN_PROGRESS_CODE
+    )
+}
+
+pub fn get_startup_error_path(base_dir: &Path) -> PathBuf {
+    get_state_dir(base_dir).join("startup_error.json")
+}
+
+pub fn get_crash_history_path(base_dir: &Path) -> PathBuf {
+    get_state_dir(base_dir).join("crash_history.jsonl")
+}
+
+pub fn parse_startup_error(content: &str, now_secs: u64) -> Result<StartupError, String> {
+    let error: StartupError =
+        serde_json::from_str(content).map_err(|e| format!("invalid JSON: {}", e))?;
+    if error.reason.trim().is_empty() {
+        return Err("missing reason".to_string());
+    }
+    if !error.ts.is_finite() || error.ts < 0.0 {
+        return Err("invalid timestamp".to_string());
+    }
+    let ts_secs = error.ts.floor() as u64;
+    if now_secs < ts_secs || now_secs - ts_secs > STARTUP_ERROR_TTL_SEC {
+        return Err("stale".to_string());
+    }
+    Ok(error)
+}
+
+/// Read and remove `startup_error.json`; the file is consumed either way.
+pub fn take_startup_error(path: &Path, log_path: &Option<PathBuf>) -> Option<StartupError> {
+    let content = fs::read_to_string(path).ok()?;
+    let _ = fs::remove_file(path);
+    match parse_startup_error(&content, now_secs()) {
+        Ok(error) => Some(error),
+        Err(reason) => {
+            log_event(
+                log_path,
+                &format!("Ignoring startup error file ({})", reason),
+            );
+            None
+        }
+    }
+}
+
+/// Append to the crash history, keeping only the newest `MAX_CRASH_HISTORY` entries.
+pub fn record_crash(path: &Path, record: &CrashRecord, log_path: &Option<PathBuf>) {
+    let line = match serde_json::to_string(record) {
+        Ok(value) => value,
+        Err(_) => return,
+    };
+    let existing = fs::read_to_string(path).unwrap_or_default();
+    let mut lines: Vec<&str> = existing.lines().filter(|l| !l.trim().is_empty()).collect();
+    lines.push(&line);
+    let start = lines.len().saturating_sub(MAX_CRASH_HISTORY);
+    let mut content = lines[start..].join("\n");
+    content.push('\n');
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Err(err) = fs::write(path, content) {
+        log_event(log_path, &format!("Failed to write crash history: {}", err));
+    }
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_else(|_| Duration::from_secs(0))
+        .as_secs()
+}
+
+fn tail_chars(text: &str, max_chars: usize) -> &str {
+    let count = text.chars().count();
+    if count <= max_chars {
+        return text;
+    }
+    let skip = count - max_chars;
+    let (idx, _) = text.char_indices().nth(skip).unwrap_or((0, ' '));
+    &text[idx..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn crash_exit_excludes_intentional_codes() {
+        assert!(!is_crash_exit(0));
+        assert!(!is_crash_exit(USER_EXIT_CODE));
+        assert!(!is_crash_exit(INSTANCE_ALREADY_RUNNING_CODE));
+        assert!(!is_crash_exit(UPDATE_IN_PROGRESS_CODE));
+        assert!(is_crash_exit(1));
+        assert!(is_crash_exit(-1));
+    }
+
+    #[test]
+    fn present_startup_error_is_surfaced_and_consumed() {
+        let dir = temp_dir("startup-error");
+        let path = dir.join("startup_error.json");
+        fs::write(
+            &path,
+            format!(
+                "{{\"reason\": \"ImportError: nicegui\", \"traceback\": \"Traceback...\\nImportError: nicegui\", \"ts\": {}}}",
+                now_secs()
+            ),
+        )
+        .unwrap();
+
+        let error = take_startup_error(&path, &None).unwrap();
+        assert_eq!(error.reason, "ImportError: nicegui");
+        assert!(error.dialog_message().contains("ImportError: nicegui"));
+        assert!(!path.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn stale_or_invalid_startup_error_is_ignored() {
+        let now = 1_700_000_000;
+        let stale = format!(
+            "{{\"reason\": \"old\", \"ts\": {}}}",
+            now - STARTUP_ERROR_TTL_SEC - 1
+        );
+        assert!(parse_startup_error(&stale, now).is_err());
+        assert!(parse_startup_error("{\"reason\": \"\", \"ts\": 1700000000}", now).is_err());
+        assert!(parse_startup_error("not json", now).is_err());
+        assert!(parse_startup_error("{\"reason\": \"x\", \"ts\": 1700000000}", now).is_ok());
+    }
+
+    #[test]
+    fn crash_history_keeps_newest_entries() {
+        let dir = temp_dir("crash-history");
+        let path = dir.join("crash_history.jsonl");
+        for code in 0..(MAX_CRASH_HISTORY as i32 + 5) {
+            let record = CrashRecord {
+                ts: 1,
+                exit_code: code,
+                startup_error: None,
+                stderr_tail: None,
+            };
+            record_crash(&path, &record, &None);
+        }
+
+        let content = fs::read_to_string(&path).unwrap();
+        let records: Vec<CrashRecord> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), MAX_CRASH_HISTORY);
+        assert_eq!(
+            records.last().unwrap().exit_code,
+            MAX_CRASH_HISTORY as i32 + 4
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn stderr_tail_fills_in_the_crash_dialog() {
+        let tail = "Traceback (most recent call last):\nImportError: nicegui";
+        let message = crash_dialog_message(1, None, Some(tail)).unwrap();
+        assert!(message.contains("exit code 1"));
+        assert!(message.ends_with("ImportError: nicegui"));
+
+        let error = StartupError {
+            reason: "boom".to_string(),
+            traceback: None,
+            ts: 0.0,
+        };
+        let message = crash_dialog_message(1, Some(&error), Some(tail)).unwrap();
+        assert!(message.contains("boom") && message.ends_with("ImportError: nicegui"));
+        assert_eq!(crash_dialog_message(1, None, Some(" ")), None);
+
+        let record = CrashRecord {
+            ts: 1,
+            exit_code: 1,
+            startup_error: None,
+            stderr_tail: Some(tail.to_string()),
+        };
+        let line = serde_json::to_string(&record).unwrap();
+        assert!(line.contains("\"stderr_tail\""));
+    }
+
+    #[test]
+    fn dialog_message_keeps_traceback_tail() {
+        let error = StartupError {
+            reason: "boom".to_string(),
+            traceback: Some(format!("{}END", "x".repeat(5000))),
+            ts: 0.0,
+        };
+        let message = error.dialog_message();
+        assert!(message.ends_with("END"));
+        assert!(message.len() < 2000);
+    }
+}
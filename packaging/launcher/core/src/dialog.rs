@@ -0,0 +1,138 @@
+//! User-facing message boxes.
+//!
+//! With `quiet_dialogs` (kiosks, where nobody is there to press OK) messages
+//! go to the log instead and questions are declined.
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::log::log_event;
+
+static QUIET_LOG: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// Route every later dialog to `log_path` instead of showing it.
+pub fn set_quiet(log_path: &Option<PathBuf>) {
+    let _ = QUIET_LOG.set(log_path.clone());
+}
+
+/// Log `message` and return true when dialogs are quiet.
+fn suppressed(kind: &str, message: &str) -> bool {
+    match QUIET_LOG.get() {
+        Some(log_path) => {
+            log_event(
+                log_path,
+                &format!("Dialog suppressed ({}): {}", kind, message),
+            );
+            true
+        }
+        None => false,
+    }
+}
+
+/// Show error message box (Windows) or print to stderr
+#[cfg(windows)]
+pub fn show_error(message: &str) {
+    if suppressed("error", message) {
+        return;
+    }
+    use std::ffi::OsStr;
+    use std::iter::once;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::null_mut;
+
+    let wide_message: Vec<u16> = OsStr::new(message).encode_wide().chain(once(0)).collect();
+    let wide_title: Vec<u16> = OsStr::new("YakuLingo - Error")
+        .encode_wide()
+        .chain(once(0))
+        .collect();
+
+    unsafe {
+        winapi::um::winuser::MessageBoxW(
+            null_mut(),
+            wide_message.as_ptr(),
+            wide_title.as_ptr(),
+            winapi::um::winuser::MB_ICONERROR | winapi::um::winuser::MB_OK,
+        );
+    }
+}
+
+#[cfg(not(windows))]
+pub fn show_error(message: &str) {
+    if suppressed("error", message) {
+        return;
+    }
+    eprintln!("Error: {}", message);
+}
+
+/// Show info message box (Windows) or print to stdout
+#[cfg(windows)]
+pub fn show_info(message: &str) {
+    if suppressed("info", message) {
+        return;
+    }
+    use std::ffi::OsStr;
+    use std::iter::once;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::null_mut;
+
+    let wide_message: Vec<u16> = OsStr::new(message).encode_wide().chain(once(0)).collect();
+    let wide_title: Vec<u16> = OsStr::new("YakuLingo")
+        .encode_wide()
+        .chain(once(0))
+        .collect();
+
+    unsafe {
+        winapi::um::winuser::MessageBoxW(
+            null_mut(),
+            wide_message.as_ptr(),
+            wide_title.as_ptr(),
+            winapi::um::winuser::MB_ICONINFORMATION | winapi::um::winuser::MB_OK,
+        );
+    }
+}
+
+#[cfg(not(windows))]
+pub fn show_info(message: &str) {
+    if suppressed("info", message) {
+        return;
+    }
+    println!("{}", message);
+}
+
+/// Ask a Yes/No question (Windows). Without a dialog there is nobody to
+/// answer, so non-Windows platforms always decline.
+#[cfg(windows)]
+pub fn ask_yes_no(message: &str) -> bool {
+    if suppressed("question, declined", message) {
+        return false;
+    }
+    use std::ffi::OsStr;
+    use std::iter::once;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::null_mut;
+    use winapi::um::winuser::{IDYES, MB_ICONWARNING, MB_YESNO};
+
+    let wide_message: Vec<u16> = OsStr::new(message).encode_wide().chain(once(0)).collect();
+    let wide_title: Vec<u16> = OsStr::new("YakuLingo")
+        .encode_wide()
+        .chain(once(0))
+        .collect();
+
+    unsafe {
+        winapi::um::winuser::MessageBoxW(
+            null_mut(),
+            wide_message.as_ptr(),
+            wide_title.as_ptr(),
+            MB_ICONWARNING | MB_YESNO,
+        ) == IDYES
+    }
+}
+
+#[cfg(not(windows))]
+pub fn ask_yes_no(message: &str) -> bool {
+    if suppressed("question, declined", message) {
+        return false;
+    }
+    println!("{}", message);
+    false
+}
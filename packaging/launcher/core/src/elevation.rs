@@ -0,0 +1,94 @@
+//! Relaunching the launcher with administrator rights.
+
+/// Quote one argument for a Windows command line (`CommandLineToArgvW` rules).
+pub fn quote_windows_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0usize;
+    for ch in arg.chars() {
+        match ch {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                quoted.push(ch);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// Start a new elevated copy of this launcher (UAC prompt) with the same arguments.
+#[cfg(windows)]
+pub fn relaunch_elevated() -> Result<(), String> {
+    use std::env;
+    use std::ffi::OsStr;
+    use std::iter::once;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::null_mut;
+    use winapi::um::shellapi::ShellExecuteW;
+    use winapi::um::winuser::SW_SHOWNORMAL;
+
+    let exe = crate::paths::launcher_exe()?;
+    let params = env::args()
+        .skip(1)
+        .map(|arg| quote_windows_arg(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let wide = |value: &OsStr| -> Vec<u16> { value.encode_wide().chain(once(0)).collect() };
+    let verb = wide(OsStr::new("runas"));
+    let file = wide(exe.as_os_str());
+    let params = wide(OsStr::new(&params));
+
+    // ShellExecuteW reports success with a value greater than 32.
+    let result = unsafe {
+        ShellExecuteW(
+            null_mut(),
+            verb.as_ptr(),
+            file.as_ptr(),
+            params.as_ptr(),
+            null_mut(),
+            SW_SHOWNORMAL,
+        )
+    } as isize;
+    if result > 32 {
+        Ok(())
+    } else {
+        Err(format!("Elevated relaunch failed (code {})", result))
+    }
+}
+
+#[cfg(not(windows))]
+pub fn relaunch_elevated() -> Result<(), String> {
+    Err("Elevated relaunch is only supported on Windows".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_windows_arg_handles_spaces_quotes_and_backslashes() {
+        assert_eq!(quote_windows_arg("--detach"), "--detach");
+        assert_eq!(quote_windows_arg(""), "\"\"");
+        assert_eq!(
+            quote_windows_arg("C:\\My Docs\\a.txt"),
+            "\"C:\\My Docs\\a.txt\""
+        );
+        assert_eq!(quote_windows_arg("say \"hi\""), "\"say \\\"hi\\\"\"");
+        assert_eq!(
+            quote_windows_arg("C:\\dir with space\\"),
+            "\"C:\\dir with space\\\\\""
+        );
+    }
+}
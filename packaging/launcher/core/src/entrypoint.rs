@@ -0,0 +1,127 @@
+//! Choosing what python.exe runs.
+//!
+//! Source installs ship `app.py`; frozen distributions may ship only
+//! `app.pyc`, a configured loader, or an importable module.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Script names tried, in order, when nothing else is configured.
+pub const DEFAULT_ENTRYPOINTS: [&str; 2] = ["app.py", "app.pyc"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entrypoint {
+    /// A `.py`/`.pyc` file passed to python as the script.
+    Script(PathBuf),
+    /// A module run with `python -m`.
+    Module(String),
+}
+
+impl Entrypoint {
+    /// Arguments placed before any app arguments on the python command line.
+    pub fn python_args(&self) -> Vec<OsString> {
+        match self {
+            Entrypoint::Script(path) => vec![path.clone().into_os_string()],
+            Entrypoint::Module(name) => vec!["-m".into(), name.into()],
+        }
+    }
+}
+
+/// Resolve the entrypoint: an entry module wins, then the configured
+/// script (relative to `base_dir`), then `app.py`, then `app.pyc`.
+pub fn resolve_entrypoint(
+    base_dir: &Path,
+    configured: Option<&str>,
+    entry_module: Option<&str>,
+) -> Result<Entrypoint, String> {
+    if let Some(module) = entry_module.map(str::trim).filter(|name| !name.is_empty()) {
+        return Ok(Entrypoint::Module(module.to_string()));
+    }
+
+    let configured = configured.map(str::trim).filter(|path| !path.is_empty());
+    let candidates = configured.into_iter().chain(DEFAULT_ENTRYPOINTS);
+    for candidate in candidates {
+        let path = base_dir.join(candidate);
+        if path.is_file() {
+            return Ok(Entrypoint::Script(path));
+        }
+    }
+
+    Err(match configured {
+        Some(path) => format!(
+            "Entrypoint not found ({}, app.py or app.pyc).\n\nPlease reinstall the application.",
+            path
+        ),
+        None => "app.py not found.\n\nPlease reinstall the application.".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::fs;
+
+    #[test]
+    fn prefers_app_py() {
+        let dir = temp_dir("entry-py");
+        fs::write(dir.join("app.py"), "").unwrap();
+        fs::write(dir.join("app.pyc"), "").unwrap();
+        assert_eq!(
+            resolve_entrypoint(&dir, None, None).unwrap(),
+            Entrypoint::Script(dir.join("app.py"))
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn falls_back_to_app_pyc() {
+        let dir = temp_dir("entry-pyc");
+        fs::write(dir.join("app.pyc"), "").unwrap();
+        assert_eq!(
+            resolve_entrypoint(&dir, None, None).unwrap(),
+            Entrypoint::Script(dir.join("app.pyc"))
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn configured_entrypoint_wins_when_present() {
+        let dir = temp_dir("entry-configured");
+        fs::write(dir.join("app.py"), "").unwrap();
+        fs::create_dir_all(dir.join("bin")).unwrap();
+        fs::write(dir.join("bin").join("loader.pyc"), "").unwrap();
+        assert_eq!(
+            resolve_entrypoint(&dir, Some("bin/loader.pyc"), None).unwrap(),
+            Entrypoint::Script(dir.join("bin/loader.pyc"))
+        );
+        // A missing configured file falls through to the defaults.
+        assert_eq!(
+            resolve_entrypoint(&dir, Some("missing.pyc"), None).unwrap(),
+            Entrypoint::Script(dir.join("app.py"))
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn entry_module_needs_no_file() {
+        let dir = temp_dir("entry-module");
+        let entry = resolve_entrypoint(&dir, Some("loader.pyc"), Some("yakulingo.main")).unwrap();
+        assert_eq!(entry, Entrypoint::Module("yakulingo.main".to_string()));
+        assert_eq!(
+            entry.python_args(),
+            vec![OsString::from("-m"), "yakulingo.main".into()]
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_entrypoint_is_an_error() {
+        let dir = temp_dir("entry-missing");
+        let err = resolve_entrypoint(&dir, None, None).unwrap_err();
+        assert!(err.starts_with("app.py not found"));
+        let err = resolve_entrypoint(&dir, Some("loader.pyc"), None).unwrap_err();
+        assert!(err.contains("loader.pyc"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,653 @@
+//! Process environment handed down to the Python app.
+
+use std::collections::HashSet;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::instance::InstanceScope;
+use crate::log::log_event;
+use crate::paths::{get_instance_profile_dir, get_state_dir};
+
+/// Overrides where WebView2 keeps its user data (must be absolute).
+pub const WEBVIEW2_DATA_DIR_ENV: &str = "YAKULINGO_WEBVIEW2_DATA_DIR";
+/// Set once the venv, pyvenv.cfg and environment are prepared; launchers
+/// started from the app inherit it and skip the same work. An elevated
+/// relaunch does not: `ShellExecuteW("runas")` starts it with a fresh
+/// environment, which has to be prepared again anyway.
+pub const ENV_PREPARED_ENV: &str = "YAKULINGO_ENV_PREPARED";
+/// Install + session the marker was set for, so an inherited marker from
+/// another install or logon session is not trusted.
+pub const ENV_TOKEN_ENV: &str = "YAKULINGO_ENV_TOKEN";
+
+/// Who started this launcher. The launcher sets it to `launcher` for the
+/// app, so a launcher re-exec'd by the app sees `launcher`; installers set
+/// `installer` for their post-install launch.
+pub const LAUNCH_SOURCE_ENV: &str = "YAKULINGO_LAUNCH_SOURCE";
+
+/// Optional `KEY=VALUE` file in the install dir applied before the app starts.
+pub const ENV_FILE_NAME: &str = ".env";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchSource {
+    /// Unset or unrecognized: a user (shortcut, double-click).
+    User,
+    /// Started from an app the launcher spawned.
+    Launcher,
+    /// An installer's post-install step: the environment may still be
+    /// settling (PATH not refreshed, pending reboot), so caches are
+    /// bypassed, probes retried and the update check skipped.
+    Installer,
+}
+
+impl LaunchSource {
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> Self {
+        match var(LAUNCH_SOURCE_ENV)
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "launcher" => LaunchSource::Launcher,
+            "installer" => LaunchSource::Installer,
+            _ => LaunchSource::User,
+        }
+    }
+
+    /// Whether results of an earlier discovery may be reused.
+    pub fn uses_caches(self) -> bool {
+        self != LaunchSource::Installer
+    }
+
+    /// Loopback self-test attempts before falling back to lock-only checks.
+    pub fn probe_attempts(self) -> u32 {
+        match self {
+            LaunchSource::Installer => 3,
+            _ => 1,
+        }
+    }
+
+    pub fn checks_updates(self) -> bool {
+        self != LaunchSource::Installer
+    }
+}
+
+/// Token binding the prepared marker to `base_dir` and `session_id`
+/// (FNV-1a, hex).
+pub fn env_token(base_dir: &Path, session_id: u32) -> String {
+    let key = format!("{}|{}", session_id, base_dir.display());
+    let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Whether a parent launcher already prepared this environment. Installer
+/// launches always prepare it again.
+pub fn environment_prepared(var: impl Fn(&str) -> Option<String>, token: &str) -> bool {
+    LaunchSource::from_env(&var).uses_caches()
+        && var(ENV_PREPARED_ENV).as_deref() == Some("1")
+        && var(ENV_TOKEN_ENV).as_deref() == Some(token)
+}
+
+/// Record that the environment is prepared, for launchers re-exec'd from here.
+pub fn mark_environment_prepared(token: &str) {
+    env::set_var(ENV_PREPARED_ENV, "1");
+    env::set_var(ENV_TOKEN_ENV, token);
+}
+
+/// Variables the app always receives, whatever `env_allowlist` /
+/// `env_denylist` say: the ones the launcher sets, `YAKULINGO_*`, and what
+/// Python and WebView2 need to start on Windows.
+pub const REQUIRED_CHILD_ENV: [&str; 16] = [
+    "YAKULINGO_*",
+    "VIRTUAL_ENV",
+    "PYWEBVIEW_GUI",
+    "NO_PROXY",
+    "PYTHONUNBUFFERED",
+    "PATH",
+    "WEBVIEW2_USER_DATA_FOLDER",
+    "SYSTEMROOT",
+    "WINDIR",
+    "SYSTEMDRIVE",
+    "TEMP",
+    "TMP",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "HOME",
+];
+
+/// Config-driven filter for the environment the app inherits. Patterns are
+/// case-insensitive names; a trailing `*` matches a prefix.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnvFilter {
+    /// When non-empty, only these (plus the required vars) are inherited.
+    pub allow: Vec<String>,
+    /// Inherited vars removed; required vars are never removed.
+    pub deny: Vec<String>,
+}
+
+fn env_pattern_matches(pattern: &str, name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    let pattern = pattern.trim().to_ascii_uppercase();
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => name == pattern,
+    }
+}
+
+impl EnvFilter {
+    pub fn is_active(&self) -> bool {
+        !self.allow.is_empty() || !self.deny.is_empty()
+    }
+
+    pub fn keeps(&self, name: &str) -> bool {
+        let matches = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| env_pattern_matches(pattern, name))
+        };
+        if REQUIRED_CHILD_ENV
+            .iter()
+            .any(|pattern| env_pattern_matches(pattern, name))
+        {
+            return true;
+        }
+        (self.allow.is_empty() || matches(&self.allow)) && !matches(&self.deny)
+    }
+}
+
+/// Setup environment variables
+pub fn setup_environment(
+    base_dir: &Path,
+    venv_dir: &Path,
+    python_dir: &Path,
+    log_path: &Option<PathBuf>,
+) {
+    // Site-specific variables first; the defaults below yield to them and
+    // to anything the launcher inherited.
+    let from_file = load_env_file(&base_dir.join(ENV_FILE_NAME), log_path);
+    let set_default = |name: &str, value: &str| set_default_var(name, value, &from_file);
+
+    // VIRTUAL_ENV
+    env::set_var("VIRTUAL_ENV", venv_dir);
+
+    // pywebview web engine (avoid runtime installation dialog)
+    set_default("PYWEBVIEW_GUI", "edgechromium");
+
+    // Proxy bypass for localhost (avoids corporate proxy delays)
+    set_default("NO_PROXY", "localhost,127.0.0.1");
+
+    // Disable Python output buffering (slightly faster startup)
+    set_default("PYTHONUNBUFFERED", "1");
+
+    // WebView2 defaults its user data next to the exe; under a read-only
+    // per-machine install that is a blank window.
+    setup_webview2_data_dir(
+        env::var_os(WEBVIEW2_DATA_DIR_ENV),
+        &get_state_dir(base_dir),
+        log_path,
+    );
+
+    // PATH - prepend venv and python directories
+    let venv_scripts = venv_dir.join("Scripts");
+    let python_scripts = python_dir.join("Scripts");
+
+    let old_path = env::var_os("PATH").unwrap_or_default();
+    let prefixes = [venv_scripts, python_dir.to_path_buf(), python_scripts];
+    env::set_var("PATH", compose_path(&prefixes, &old_path));
+}
+
+/// One line of a `.env` file: `None` for blanks and comments.
+fn parse_env_line(line: &str) -> Result<Option<(String, String)>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    let (name, raw) = line.split_once('=').ok_or("expected KEY=VALUE")?;
+    let name = name.trim();
+    let valid_name = name
+        .chars()
+        .next()
+        .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+    if !valid_name {
+        return Err(format!("invalid variable name {:?}", name));
+    }
+    let raw = raw.trim();
+    let value = if let Some(rest) = raw.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.chars();
+        loop {
+            match chars.next() {
+                Some('"') => break,
+                Some('\\') => match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some(escaped @ ('"' | '\\')) => value.push(escaped),
+                    // Windows paths: other backslashes are kept.
+                    Some(other) => {
+                        value.push('\\');
+                        value.push(other);
+                    }
+                    None => return Err("unterminated double quote".to_string()),
+                },
+                Some(ch) => value.push(ch),
+                None => return Err("unterminated double quote".to_string()),
+            }
+        }
+        check_trailing(chars.as_str())?;
+        value
+    } else if let Some(rest) = raw.strip_prefix('\'') {
+        let (value, trailing) = rest.split_once('\'').ok_or("unterminated single quote")?;
+        check_trailing(trailing)?;
+        value.to_string()
+    } else {
+        // Unquoted: ` #` starts a comment.
+        raw.split(" #")
+            .next()
+            .unwrap_or_default()
+            .trim_end()
+            .to_string()
+    };
+    Ok(Some((name.to_string(), value)))
+}
+
+/// Only a comment may follow a closing quote.
+fn check_trailing(rest: &str) -> Result<(), String> {
+    let rest = rest.trim();
+    if rest.is_empty() || rest.starts_with('#') {
+        Ok(())
+    } else {
+        Err(format!("unexpected text after quoted value: {:?}", rest))
+    }
+}
+
+/// Parse a `.env` file: the variables in order, and one message per line
+/// that could not be parsed.
+pub fn parse_env_file(content: &str) -> (Vec<(String, String)>, Vec<String>) {
+    let mut vars = Vec::new();
+    let mut errors = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        match parse_env_line(line) {
+            Ok(Some(var)) => vars.push(var),
+            Ok(None) => {}
+            Err(err) => errors.push(format!("line {}: {}", index + 1, err)),
+        }
+    }
+    (vars, errors)
+}
+
+/// Set `name` to `value` unless `.env` (`from_file`) or the inherited
+/// environment already gave it one.
+pub fn set_default_var(name: &str, value: &str, from_file: &HashSet<String>) {
+    if !from_file.contains(name) && env::var_os(name).is_none() {
+        env::set_var(name, value);
+    }
+}
+
+/// Apply the variables in `path` (if it exists) to this process, bad lines
+/// logged and skipped. Values are never logged. Returns the names set.
+pub fn load_env_file(path: &Path, log_path: &Option<PathBuf>) -> HashSet<String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return HashSet::new(),
+    };
+    let (vars, errors) = parse_env_file(&content);
+    for error in &errors {
+        log_event(
+            log_path,
+            &format!(
+                "Skipping {:?} {}",
+                path.file_name().unwrap_or_default(),
+                error
+            ),
+        );
+    }
+    let mut names: Vec<&str> = Vec::new();
+    for (name, value) in &vars {
+        env::set_var(name, value);
+        names.push(name);
+    }
+    log_event(
+        log_path,
+        &format!(
+            "Loaded {} variable(s) from {:?}: {}",
+            vars.len(),
+            path,
+            names.join(", ")
+        ),
+    );
+    vars.into_iter().map(|(name, _)| name).collect()
+}
+
+/// Point `WEBVIEW2_USER_DATA_FOLDER` at `override_dir`, or `<state dir>/webview2`,
+/// creating it. An unusable override falls back to the default.
+pub fn setup_webview2_data_dir(
+    override_dir: Option<OsString>,
+    state_dir: &Path,
+    log_path: &Option<PathBuf>,
+) -> Option<PathBuf> {
+    let default_dir = state_dir.join("webview2");
+    let dir = match override_dir
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+    {
+        Some(dir) if dir.is_absolute() => dir,
+        Some(dir) => {
+            log_event(
+                log_path,
+                &format!(
+                    "Ignoring {} {:?} (not an absolute path)",
+                    WEBVIEW2_DATA_DIR_ENV, dir
+                ),
+            );
+            default_dir
+        }
+        None => default_dir,
+    };
+    if let Err(err) = fs::create_dir_all(&dir) {
+        log_event(
+            log_path,
+            &format!("Cannot create WebView2 data folder {:?}: {}", dir, err),
+        );
+        return None;
+    }
+    env::set_var("WEBVIEW2_USER_DATA_FOLDER", &dir);
+    log_event(log_path, &format!("WebView2 data folder: {:?}", dir));
+    Some(dir)
+}
+
+/// Prepend `prefixes` to `old_path`, dropping empty and duplicate entries.
+/// Re-exec'd launchers inherit an already-prefixed PATH, so without this the
+/// venv entries pile up on every relaunch and slow down process creation.
+pub fn compose_path(prefixes: &[PathBuf], old_path: &OsStr) -> OsString {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut entries: Vec<PathBuf> = Vec::new();
+    for entry in prefixes.iter().cloned().chain(env::split_paths(old_path)) {
+        if entry.as_os_str().is_empty() {
+            continue;
+        }
+        if seen.insert(path_key(&entry)) {
+            entries.push(entry);
+        }
+    }
+    // Entries came from split_paths, so they cannot contain the separator;
+    // fall back to the old value rather than dropping PATH entirely.
+    env::join_paths(entries).unwrap_or_else(|_| old_path.to_os_string())
+}
+
+/// Comparison key for PATH entries: Windows paths are case-insensitive and
+/// `C:\dir` and `C:\dir\` name the same directory.
+fn path_key(path: &Path) -> String {
+    let text = path.to_string_lossy();
+    let trimmed = text.trim_end_matches(['\\', '/']);
+    let trimmed = if trimmed.is_empty() { &text } else { trimmed };
+    if cfg!(windows) {
+        trimmed.to_lowercase()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Tell the app which single-instance identity the launcher resolved, so the
+/// mutex it creates and the port it binds match what the launcher probes.
+pub fn setup_instance_environment(base_dir: &Path, instance: &InstanceScope) {
+    env::set_var("YAKULINGO_PORT", instance.port.to_string());
+    env::set_var("YAKULINGO_INSTANCE_MUTEX", &instance.mutex_name);
+    env::set_var("YAKULINGO_WINDOW_TITLE", &instance.window_title);
+
+    // Named instances keep separate profiles (settings, history, caches)
+    if let Some(name) = &instance.name {
+        let profile_dir = get_instance_profile_dir(base_dir, name);
+        let _ = fs::create_dir_all(&profile_dir);
+        env::set_var("YAKULINGO_INSTANCE_NAME", name);
+        env::set_var("YAKULINGO_PROFILE_DIR", &profile_dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn join(entries: &[&str]) -> OsString {
+        env::join_paths(entries).unwrap()
+    }
+
+    #[test]
+    fn defaults_yield_to_inherited_and_env_file_values() {
+        let inherited = "YAKULINGO_TEST_DEFAULT_INHERITED";
+        let from_env_file = "YAKULINGO_TEST_DEFAULT_FROM_FILE";
+        let unset = "YAKULINGO_TEST_DEFAULT_UNSET";
+        env::set_var(inherited, "corp-proxy");
+        env::remove_var(from_env_file);
+        env::remove_var(unset);
+        let from_file: HashSet<String> = [from_env_file.to_string()].into_iter().collect();
+
+        for name in [inherited, from_env_file, unset] {
+            set_default_var(name, "default", &from_file);
+        }
+        assert_eq!(env::var(inherited).as_deref(), Ok("corp-proxy"));
+        assert_eq!(env::var_os(from_env_file), None);
+        assert_eq!(env::var(unset).as_deref(), Ok("default"));
+
+        for name in [inherited, unset] {
+            env::remove_var(name);
+        }
+    }
+
+    #[test]
+    fn env_file_lines_are_parsed_and_bad_ones_reported() {
+        let content = "# site settings\n\
+                       \n\
+                       NO_PROXY=localhost,127.0.0.1,.corp.example\n\
+                       export HTTPS_PROXY = http://proxy:8080 # via the gateway\n\
+                       MESSAGE=\"say \\\"hi\\\"\\nbye\" # quoted\n\
+                       PLAYWRIGHT_BROWSERS_PATH='C:\\Browsers #1'\n\
+                       EMPTY=\n\
+                       1BAD=x\n\
+                       no equals sign\n\
+                       OPEN=\"never closed\n\
+                       TRAILING='x' y\n\
+                       DATA_DIR=\"D:\\YakuLingo\\data\"\n";
+        let (vars, errors) = parse_env_file(content);
+        let pairs: Vec<(&str, &str)> = vars
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("NO_PROXY", "localhost,127.0.0.1,.corp.example"),
+                ("HTTPS_PROXY", "http://proxy:8080"),
+                ("MESSAGE", "say \"hi\"\nbye"),
+                ("PLAYWRIGHT_BROWSERS_PATH", "C:\\Browsers #1"),
+                ("EMPTY", ""),
+                ("DATA_DIR", "D:\\YakuLingo\\data"),
+            ]
+        );
+        assert_eq!(errors.len(), 4);
+        assert!(errors[0].starts_with("line 8: invalid variable name"));
+        assert_eq!(errors[1], "line 9: expected KEY=VALUE");
+        assert_eq!(errors[2], "line 10: unterminated double quote");
+        assert!(errors[3].starts_with("line 11: unexpected text"));
+    }
+
+    #[test]
+    fn prefixes_are_not_duplicated_when_already_present() {
+        let prefixes = [
+            PathBuf::from("/opt/yl/.venv/Scripts"),
+            PathBuf::from("/opt/py"),
+        ];
+        let once = compose_path(&prefixes, &join(&["/usr/bin", "/bin"]));
+        let twice = compose_path(&prefixes, &once);
+
+        assert_eq!(
+            once,
+            join(&["/opt/yl/.venv/Scripts", "/opt/py", "/usr/bin", "/bin"])
+        );
+        assert_eq!(twice, once);
+    }
+
+    #[test]
+    fn inherited_duplicates_and_empty_entries_are_removed() {
+        let prefixes = [PathBuf::from("/opt/py")];
+        let old = join(&["/usr/bin", "", "/opt/py/", "/usr/bin", "/bin"]);
+        assert_eq!(
+            compose_path(&prefixes, &old),
+            join(&["/opt/py", "/usr/bin", "/bin"])
+        );
+    }
+
+    #[test]
+    fn webview2_data_folder_is_set_to_a_writable_dir() {
+        use crate::test_util::temp_dir;
+
+        let dir = temp_dir("env-webview2");
+        let state_dir = dir.join("state");
+        let custom = dir.join("custom").join("WebView2");
+
+        // The only test that touches WEBVIEW2_USER_DATA_FOLDER.
+        let chosen = setup_webview2_data_dir(None, &state_dir, &None).unwrap();
+        assert_eq!(chosen, state_dir.join("webview2"));
+        assert_eq!(
+            env::var_os("WEBVIEW2_USER_DATA_FOLDER"),
+            Some(chosen.clone().into())
+        );
+        assert!(chosen.is_dir());
+
+        let chosen =
+            setup_webview2_data_dir(Some(custom.clone().into()), &state_dir, &None).unwrap();
+        assert_eq!(chosen, custom);
+        assert_eq!(
+            env::var_os("WEBVIEW2_USER_DATA_FOLDER"),
+            Some(custom.clone().into())
+        );
+        assert!(custom.is_dir());
+
+        let chosen =
+            setup_webview2_data_dir(Some("relative/dir".into()), &state_dir, &None).unwrap();
+        assert_eq!(chosen, state_dir.join("webview2"));
+
+        env::remove_var("WEBVIEW2_USER_DATA_FOLDER");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn prepared_marker_short_circuits_only_for_the_same_install() {
+        let token = env_token(Path::new("C:/YakuLingo"), 2);
+        let vars = |marker: &'static str, token: String| {
+            move |name: &str| match name {
+                ENV_PREPARED_ENV => Some(marker.to_string()),
+                ENV_TOKEN_ENV => Some(token.clone()),
+                _ => None,
+            }
+        };
+
+        assert!(environment_prepared(vars("1", token.clone()), &token));
+        assert!(!environment_prepared(|_| None, &token), "first launch");
+        assert!(!environment_prepared(vars("0", token.clone()), &token));
+        let other_install = env_token(Path::new("D:/YakuLingo"), 2);
+        assert!(!environment_prepared(vars("1", other_install), &token));
+        let other_session = env_token(Path::new("C:/YakuLingo"), 3);
+        assert!(!environment_prepared(vars("1", other_session), &token));
+    }
+
+    fn filtered(filter: &EnvFilter) -> Vec<String> {
+        let names = [
+            "PATH",
+            "SystemRoot",
+            "YAKULINGO_PORT",
+            "AWS_SECRET_ACCESS_KEY",
+            "AWS_REGION",
+            "HTTPS_PROXY",
+            "GIT_TOKEN",
+        ];
+        names
+            .into_iter()
+            .filter(|name| filter.keeps(name))
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn env_allowlist_keeps_only_listed_and_required_vars() {
+        let filter = EnvFilter {
+            allow: vec!["https_proxy".to_string()],
+            deny: Vec::new(),
+        };
+        assert!(filter.is_active());
+        assert_eq!(
+            filtered(&filter),
+            ["PATH", "SystemRoot", "YAKULINGO_PORT", "HTTPS_PROXY"]
+        );
+    }
+
+    #[test]
+    fn env_denylist_removes_inherited_but_not_required_vars() {
+        let filter = EnvFilter {
+            allow: Vec::new(),
+            deny: vec!["AWS_*".to_string(), "PATH".to_string()],
+        };
+        assert_eq!(
+            filtered(&filter),
+            [
+                "PATH",
+                "SystemRoot",
+                "YAKULINGO_PORT",
+                "HTTPS_PROXY",
+                "GIT_TOKEN"
+            ]
+        );
+        assert!(!EnvFilter::default().is_active());
+        assert_eq!(filtered(&EnvFilter::default()).len(), 7);
+    }
+
+    #[test]
+    fn env_allowlist_and_denylist_combine() {
+        let filter = EnvFilter {
+            allow: vec!["AWS_*".to_string(), "HTTPS_PROXY".to_string()],
+            deny: vec!["AWS_SECRET_*".to_string()],
+        };
+        assert_eq!(
+            filtered(&filter),
+            [
+                "PATH",
+                "SystemRoot",
+                "YAKULINGO_PORT",
+                "AWS_REGION",
+                "HTTPS_PROXY"
+            ]
+        );
+    }
+
+    #[test]
+    fn installer_launches_bypass_caches() {
+        let token = env_token(Path::new("C:/YakuLingo"), 2);
+        let vars = |source: &'static str| {
+            let token = token.clone();
+            move |name: &str| match name {
+                LAUNCH_SOURCE_ENV => Some(source.to_string()),
+                ENV_PREPARED_ENV => Some("1".to_string()),
+                ENV_TOKEN_ENV => Some(token.clone()),
+                _ => None,
+            }
+        };
+
+        assert_eq!(
+            LaunchSource::from_env(vars(" Installer ")),
+            LaunchSource::Installer
+        );
+        assert!(!environment_prepared(vars("installer"), &token));
+        assert!(environment_prepared(vars("launcher"), &token));
+        assert_eq!(LaunchSource::from_env(|_| None), LaunchSource::User);
+        assert_eq!(LaunchSource::from_env(vars("shortcut")), LaunchSource::User);
+        assert!(LaunchSource::User.uses_caches() && LaunchSource::User.checks_updates());
+        assert!(!LaunchSource::Installer.checks_updates());
+        assert!(LaunchSource::Installer.probe_attempts() > LaunchSource::User.probe_attempts());
+    }
+}
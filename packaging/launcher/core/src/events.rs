@@ -0,0 +1,277 @@
+//! Named events for coordinating with the installer (Windows).
+//!
+//! While the watchdog runs, `Global\YakuLingo_Stopped` is reset; it is set
+//! once the watchdog loop has ended and the app process is gone, so an
+//! installer can `WaitForSingleObject` on it before replacing files.
+//! Setting `Global\YakuLingo_StopRequest` asks the launcher to shut the app
+//! down cleanly (as a user exit) and stop supervising it.
+
+use std::path::PathBuf;
+
+pub const STOPPED_EVENT_NAME: &str = "Global\\YakuLingo_Stopped";
+pub const STOP_REQUEST_EVENT_NAME: &str = "Global\\YakuLingo_StopRequest";
+
+/// A manual-reset event, behind a seam so the handshake can be tested
+/// without Win32.
+pub trait NamedEvent {
+    fn set(&self);
+    fn reset(&self);
+    fn is_set(&self) -> bool;
+}
+
+/// Event names for an instance; named instances get a `-<name>` suffix
+/// like their mutex.
+pub fn event_name(base: &str, instance_name: Option<&str>) -> String {
+    match instance_name {
+        Some(name) => format!("{}-{}", base, name),
+        None => base.to_string(),
+    }
+}
+
+/// The launcher's side of the installer handshake. Either event may be
+/// missing (non-Windows, or creation failed); the handshake then does nothing.
+pub struct InstallerEvents {
+    stopped: Option<Box<dyn NamedEvent>>,
+    stop_request: Option<Box<dyn NamedEvent>>,
+}
+
+impl InstallerEvents {
+    pub fn new(
+        stopped: Option<Box<dyn NamedEvent>>,
+        stop_request: Option<Box<dyn NamedEvent>>,
+    ) -> Self {
+        InstallerEvents {
+            stopped,
+            stop_request,
+        }
+    }
+
+    /// Open (or create) the real events for `instance_name`.
+    pub fn open(instance_name: Option<&str>, log_path: &Option<PathBuf>) -> Self {
+        InstallerEvents::new(
+            open_event(&event_name(STOPPED_EVENT_NAME, instance_name), log_path),
+            open_event(
+                &event_name(STOP_REQUEST_EVENT_NAME, instance_name),
+                log_path,
+            ),
+        )
+    }
+
+    /// Whether a stop request can arrive, i.e. the watchdog has to poll.
+    pub fn is_active(&self) -> bool {
+        self.stop_request.is_some()
+    }
+
+    /// Mark the app as running. A stop request left signaled by an earlier
+    /// install is cleared so it does not stop this launch.
+    pub fn begin(&self) {
+        if let Some(event) = &self.stopped {
+            event.reset();
+        }
+        if let Some(event) = &self.stop_request {
+            event.reset();
+        }
+    }
+
+    pub fn stop_requested(&self) -> bool {
+        self.stop_request
+            .as_ref()
+            .is_some_and(|event| event.is_set())
+    }
+
+    /// Tell waiters the watchdog has ended and the app is gone.
+    pub fn signal_stopped(&self) {
+        if let Some(event) = &self.stopped {
+            event.set();
+        }
+    }
+}
+
+/// Signals `Stopped` when dropped, so every way out of the watchdog
+/// (including errors) releases a waiting installer.
+pub struct StoppedGuard<'a>(pub &'a InstallerEvents);
+
+impl Drop for StoppedGuard<'_> {
+    fn drop(&mut self) {
+        self.0.signal_stopped();
+    }
+}
+
+#[cfg(windows)]
+mod win32 {
+    use std::ffi::OsStr;
+    use std::iter::once;
+    use std::mem::size_of;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::null_mut;
+
+    use winapi::shared::minwindef::{FALSE, TRUE};
+    use winapi::shared::sddl::{
+        ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+    };
+    use winapi::um::errhandlingapi::GetLastError;
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::minwinbase::SECURITY_ATTRIBUTES;
+    use winapi::um::synchapi::{CreateEventW, ResetEvent, SetEvent, WaitForSingleObject};
+    use winapi::um::winbase::{LocalFree, WAIT_OBJECT_0};
+    use winapi::um::winnt::HANDLE;
+
+    use super::NamedEvent;
+
+    /// SYSTEM, administrators and the creator get full access; other
+    /// signed-in users may only wait, so they cannot stop someone else's app.
+    const EVENT_SDDL: &str = "D:(A;;GA;;;SY)(A;;GA;;;BA)(A;;GA;;;OW)(A;;0x00100000;;;AU)";
+
+    pub struct Win32Event(HANDLE);
+
+    impl Win32Event {
+        pub fn create(name: &str) -> Result<Self, String> {
+            let sddl: Vec<u16> = OsStr::new(EVENT_SDDL)
+                .encode_wide()
+                .chain(once(0))
+                .collect();
+            let wide_name: Vec<u16> = OsStr::new(name).encode_wide().chain(once(0)).collect();
+            unsafe {
+                let mut descriptor = null_mut();
+                if ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                    sddl.as_ptr(),
+                    SDDL_REVISION_1 as u32,
+                    &mut descriptor,
+                    null_mut(),
+                ) == 0
+                {
+                    return Err(format!("security descriptor failed ({})", GetLastError()));
+                }
+                let mut attributes = SECURITY_ATTRIBUTES {
+                    nLength: size_of::<SECURITY_ATTRIBUTES>() as u32,
+                    lpSecurityDescriptor: descriptor,
+                    bInheritHandle: FALSE,
+                };
+                // Opens the existing event when the installer created it first.
+                let handle = CreateEventW(&mut attributes, TRUE, FALSE, wide_name.as_ptr());
+                let error = GetLastError();
+                LocalFree(descriptor);
+                if handle.is_null() {
+                    return Err(format!("CreateEventW failed ({})", error));
+                }
+                Ok(Win32Event(handle))
+            }
+        }
+    }
+
+    impl NamedEvent for Win32Event {
+        fn set(&self) {
+            unsafe { SetEvent(self.0) };
+        }
+
+        fn reset(&self) {
+            unsafe { ResetEvent(self.0) };
+        }
+
+        fn is_set(&self) -> bool {
+            unsafe { WaitForSingleObject(self.0, 0) == WAIT_OBJECT_0 }
+        }
+    }
+
+    impl Drop for Win32Event {
+        fn drop(&mut self) {
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+}
+
+#[cfg(windows)]
+fn open_event(name: &str, log_path: &Option<PathBuf>) -> Option<Box<dyn NamedEvent>> {
+    match win32::Win32Event::create(name) {
+        Ok(event) => Some(Box::new(event)),
+        Err(err) => {
+            crate::log::log_event(
+                log_path,
+                &format!("Installer event {} unavailable: {}", name, err),
+            );
+            None
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn open_event(_name: &str, _log_path: &Option<PathBuf>) -> Option<Box<dyn NamedEvent>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[derive(Clone, Default)]
+    struct FakeEvent(Rc<Cell<bool>>);
+
+    impl NamedEvent for FakeEvent {
+        fn set(&self) {
+            self.0.set(true);
+        }
+
+        fn reset(&self) {
+            self.0.set(false);
+        }
+
+        fn is_set(&self) -> bool {
+            self.0.get()
+        }
+    }
+
+    fn events() -> (InstallerEvents, FakeEvent, FakeEvent) {
+        let stopped = FakeEvent::default();
+        let stop_request = FakeEvent::default();
+        let events = InstallerEvents::new(
+            Some(Box::new(stopped.clone())),
+            Some(Box::new(stop_request.clone())),
+        );
+        (events, stopped, stop_request)
+    }
+
+    #[test]
+    fn stopped_is_signaled_only_after_watchdog_ends() {
+        let (events, stopped, _) = events();
+        stopped.set();
+        {
+            let _guard = StoppedGuard(&events);
+            events.begin();
+            assert!(!stopped.is_set(), "running app must hold the installer");
+        }
+        assert!(stopped.is_set());
+    }
+
+    #[test]
+    fn stop_request_is_observed_and_stale_requests_cleared() {
+        let (events, _, stop_request) = events();
+        stop_request.set();
+        events.begin();
+        assert!(!events.stop_requested(), "request from a previous install");
+        stop_request.set();
+        assert!(events.stop_requested());
+    }
+
+    #[test]
+    fn missing_events_are_inert() {
+        let events = InstallerEvents::new(None, None);
+        assert!(!events.is_active());
+        events.begin();
+        assert!(!events.stop_requested());
+        events.signal_stopped();
+    }
+
+    #[test]
+    fn named_instances_get_their_own_events() {
+        assert_eq!(
+            event_name(STOPPED_EVENT_NAME, None),
+            "Global\\YakuLingo_Stopped"
+        );
+        assert_eq!(
+            event_name(STOP_REQUEST_EVENT_NAME, Some("work")),
+            "Global\\YakuLingo_StopRequest-work"
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Pseudonymous machine fingerprint for correlating logs across a fleet.
+//!
+//! The machine ID (`MachineGuid` on Windows, `/etc/machine-id` elsewhere) is
+//! only ever emitted as a salted SHA-256 prefix, never raw.
+
+use std::env;
+use std::fmt;
+
+use sha2::{Digest, Sha256};
+
+/// Domain separator so the hash cannot be matched against other products
+/// that hash the same machine ID.
+const FINGERPRINT_SALT: &str = "yakulingo-machine-fingerprint-v1:";
+/// Hex digits of the digest kept (64 bits).
+const FINGERPRINT_HEX_LEN: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachineFingerprint {
+    pub hostname: String,
+    /// Truncated hex SHA-256 of the salted machine ID.
+    pub machine_hash: String,
+}
+
+impl fmt::Display for MachineFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "host={} machine={}", self.hostname, self.machine_hash)
+    }
+}
+
+pub fn hash_machine_id(machine_id: &str) -> String {
+    let digest = Sha256::digest(format!("{}{}", FINGERPRINT_SALT, machine_id.trim()).as_bytes());
+    digest
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>()[..FINGERPRINT_HEX_LEN]
+        .to_string()
+}
+
+/// Build the fingerprint from raw values; `None` without a machine ID.
+pub fn fingerprint_from(
+    hostname: Option<&str>,
+    machine_id: Option<&str>,
+) -> Option<MachineFingerprint> {
+    let machine_id = machine_id.map(str::trim).filter(|id| !id.is_empty())?;
+    Some(MachineFingerprint {
+        hostname: hostname
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .unwrap_or("unknown")
+            .to_string(),
+        machine_hash: hash_machine_id(machine_id),
+    })
+}
+
+fn hostname() -> Option<String> {
+    if let Ok(name) = env::var("COMPUTERNAME") {
+        return Some(name);
+    }
+    if let Ok(name) = env::var("HOSTNAME") {
+        return Some(name);
+    }
+    std::fs::read_to_string("/etc/hostname").ok()
+}
+
+#[cfg(windows)]
+fn machine_id() -> Option<String> {
+    use std::ffi::OsStr;
+    use std::iter::once;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::null_mut;
+    use winapi::um::winreg::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ};
+
+    /// Read the 64-bit view even from a 32-bit build.
+    const RRF_SUBKEY_WOW6464KEY: u32 = 0x0001_0000;
+
+    fn wide(value: &str) -> Vec<u16> {
+        OsStr::new(value).encode_wide().chain(once(0)).collect()
+    }
+
+    let key = wide("SOFTWARE\\Microsoft\\Cryptography");
+    let name = wide("MachineGuid");
+    let mut buffer = [0u16; 64];
+    let mut size = (buffer.len() * 2) as u32;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            key.as_ptr(),
+            name.as_ptr(),
+            RRF_RT_REG_SZ | RRF_SUBKEY_WOW6464KEY,
+            null_mut(),
+            buffer.as_mut_ptr() as *mut _,
+            &mut size,
+        )
+    };
+    if status != 0 {
+        return None;
+    }
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    Some(String::from_utf16_lossy(&buffer[..len]))
+}
+
+#[cfg(not(windows))]
+fn machine_id() -> Option<String> {
+    std::fs::read_to_string("/etc/machine-id")
+        .or_else(|_| std::fs::read_to_string("/var/lib/dbus/machine-id"))
+        .ok()
+}
+
+/// Fingerprint of this machine, if a machine ID is available.
+pub fn machine_fingerprint() -> Option<MachineFingerprint> {
+    fingerprint_from(hostname().as_deref(), machine_id().as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUID: &str = "3f2504e0-4f89-11d3-9a0c-0305e82c3301";
+
+    #[test]
+    fn fingerprint_is_stable_and_hashed() {
+        let first = fingerprint_from(Some("PC-042"), Some(GUID)).unwrap();
+        let second = fingerprint_from(Some("PC-042"), Some(&format!("{}\n", GUID))).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.machine_hash.len(), FINGERPRINT_HEX_LEN);
+        assert!(first.machine_hash.chars().all(|c| c.is_ascii_hexdigit()));
+
+        let rendered = first.to_string();
+        assert!(rendered.contains("host=PC-042"));
+        assert!(!rendered.contains(GUID));
+        assert!(!rendered.contains("3f2504e0"));
+    }
+
+    #[test]
+    fn different_machines_hash_differently() {
+        let other = "3f2504e0-4f89-11d3-9a0c-0305e82c3302";
+        assert_ne!(hash_machine_id(GUID), hash_machine_id(other));
+    }
+
+    #[test]
+    fn machine_id_is_required() {
+        assert_eq!(fingerprint_from(Some("PC-042"), None), None);
+        assert_eq!(fingerprint_from(Some("PC-042"), Some("  ")), None);
+        assert_eq!(
+            fingerprint_from(None, Some(GUID)).unwrap().hostname,
+            "unknown"
+        );
+    }
+}
@@ -0,0 +1,98 @@
+//! Heartbeat file liveness check.
+//!
+//! When enabled, the app touches the file named by `YAKULINGO_HEARTBEAT_FILE`
+//! every `YAKULINGO_HEARTBEAT_INTERVAL_SEC`. The watchdog treats a live
+//! process whose heartbeat is older than the timeout as hung.
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// How often the watchdog polls the child and the heartbeat file.
+pub const HEARTBEAT_POLL_MS: u64 = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heartbeat {
+    pub path: PathBuf,
+    /// Maximum heartbeat age before the app counts as hung.
+    pub timeout: Duration,
+}
+
+impl Heartbeat {
+    /// Interval asked of the app: a third of the timeout, so one late write
+    /// is not enough to trigger a restart.
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs((self.timeout.as_secs() / 3).max(1))
+    }
+}
+
+/// Whether the app has gone `timeout` without a heartbeat. Before the first
+/// heartbeat, age is measured from `started` so startup gets the same grace.
+/// Heartbeats from before `started` (a previous run) are ignored.
+pub fn is_heartbeat_stale(
+    last_beat: Option<SystemTime>,
+    started: SystemTime,
+    now: SystemTime,
+    timeout: Duration,
+) -> bool {
+    let reference = match last_beat {
+        Some(beat) if beat > started => beat,
+        _ => started,
+    };
+    match now.duration_since(reference) {
+        Ok(age) => age > timeout,
+        // Clock moved backwards or mtime is in the future: not evidence of a hang.
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(30);
+
+    fn at(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + secs)
+    }
+
+    #[test]
+    fn fresh_heartbeat_is_not_stale() {
+        assert!(!is_heartbeat_stale(Some(at(100)), at(0), at(120), TIMEOUT));
+    }
+
+    #[test]
+    fn old_heartbeat_is_stale() {
+        assert!(is_heartbeat_stale(Some(at(100)), at(0), at(131), TIMEOUT));
+    }
+
+    #[test]
+    fn missing_heartbeat_gets_startup_grace() {
+        assert!(!is_heartbeat_stale(None, at(0), at(30), TIMEOUT));
+        assert!(is_heartbeat_stale(None, at(0), at(31), TIMEOUT));
+    }
+
+    #[test]
+    fn heartbeat_from_previous_run_is_ignored() {
+        assert!(!is_heartbeat_stale(Some(at(0)), at(50), at(60), TIMEOUT));
+        assert!(is_heartbeat_stale(Some(at(0)), at(50), at(81), TIMEOUT));
+    }
+
+    #[test]
+    fn future_mtime_is_not_stale() {
+        assert!(!is_heartbeat_stale(Some(at(500)), at(0), at(100), TIMEOUT));
+    }
+
+    #[test]
+    fn interval_is_a_third_of_timeout() {
+        let heartbeat = Heartbeat {
+            path: PathBuf::from("heartbeat"),
+            timeout: TIMEOUT,
+        };
+        assert_eq!(heartbeat.interval(), Duration::from_secs(10));
+        let short = Heartbeat {
+            timeout: Duration::from_secs(2),
+            ..heartbeat
+        };
+        assert_eq!(short.interval(), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,1267 @@
+//! Detection of (and handing off to) an already-running instance.
+
+use std::fs;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::config::SingletonScope;
+use crate::crash::now_secs;
+use crate::paths::write_file_atomic;
+
+const INSTANCE_MUTEX_BASE_NAME: &str = "YakuLingoSingleton";
+pub const APP_WINDOW_TITLE: &str = "YakuLingo";
+/// Per-user ports are spread over `base_port + 1 ..= base_port + PER_USER_PORT_SPAN`.
+const PER_USER_PORT_SPAN: u32 = 1000;
+/// Named instances use the next block of the same size, so they never share
+/// a port with the unnamed instance of the same scope.
+const NAMED_INSTANCE_PORT_SPAN: u32 = 1000;
+/// Loopback connects either succeed or are refused at once; the timeout only
+/// bounds a stalled stack, so keep it short for the relaunch-to-focus path.
+const PROBE_CONNECT_TIMEOUT_MS: u64 = 50;
+const PROBE_IO_TIMEOUT_MS: u64 = 200;
+/// The app may need a moment to accept forwarded arguments.
+const FORWARD_RESPONSE_TIMEOUT_MS: u64 = 2000;
+/// Reported ports older than this are ignored (guards against PID reuse);
+/// a running app refreshes its report hourly, well inside this window.
+pub const REPORTED_PORT_TTL_SEC: u64 = 24 * 60 * 60;
+/// Allowed clock skew for a report timestamp in the future.
+const REPORTED_PORT_SKEW_SEC: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppStatus {
+    NotRunning,
+    Running,
+    PortInUse,
+}
+
+/// What a launch should do given the single-instance probes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchDecision {
+    /// Our instance already runs: focus it instead of starting another.
+    FocusExisting,
+    /// The port answers, but not as YakuLingo.
+    PortConflict,
+    Launch,
+}
+
+pub fn decide_launch(mutex_present: bool, app_status: AppStatus) -> LaunchDecision {
+    if mutex_present || app_status == AppStatus::Running {
+        LaunchDecision::FocusExisting
+    } else if app_status == AppStatus::PortInUse {
+        LaunchDecision::PortConflict
+    } else {
+        LaunchDecision::Launch
+    }
+}
+
+/// How an existing instance is detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstanceCheck {
+    /// Mutex, then the HTTP probe on the app port.
+    PortProbe,
+    /// Loopback is blocked, so the port probe would always say "not
+    /// running": rely on the mutex and the PID file only.
+    LockOnly,
+}
+
+/// Whether a loopback connection to a fresh ephemeral listener succeeds.
+/// `connect` is the seam used to simulate blocking security software.
+pub fn loopback_works(connect: impl FnOnce(SocketAddr) -> io::Result<TcpStream>) -> bool {
+    let listener = match TcpListener::bind("127.0.0.1:0") {
+        Ok(value) => value,
+        Err(_) => return false,
+    };
+    let addr = match listener.local_addr() {
+        Ok(value) => value,
+        Err(_) => return false,
+    };
+    connect(addr).is_ok()
+}
+
+/// A loopback port free right now, for `port_fallback`.
+pub fn free_loopback_port() -> Option<u16> {
+    let listener = TcpListener::bind("127.0.0.1:0").ok()?;
+    Some(listener.local_addr().ok()?.port())
+}
+
+/// One-time startup check that picks the instance check for this launch.
+pub fn select_instance_check(loopback_ok: bool) -> InstanceCheck {
+    if loopback_ok {
+        InstanceCheck::PortProbe
+    } else {
+        InstanceCheck::LockOnly
+    }
+}
+
+/// Connect the way the port probe does, so the self-test fails when it would.
+pub fn probe_connect(addr: SocketAddr) -> io::Result<TcpStream> {
+    TcpStream::connect_timeout(&addr, Duration::from_millis(PROBE_CONNECT_TIMEOUT_MS))
+}
+
+/// The already-running probes and hand-off actions, behind a seam so the
+/// fast path can be tested without a real instance.
+pub trait InstanceProbe {
+    fn mutex_present(&self, mutex_name: &str) -> bool;
+    /// Lock-file check for `InstanceCheck::LockOnly`: the PID file names a
+    /// live process.
+    fn lock_held(&self) -> bool;
+    fn app_status(&self, port: u16) -> AppStatus;
+    fn focus_window(&self, window_title: &str) -> bool;
+    fn activate(&self, port: u16) -> bool;
+    /// Hand this launch's arguments to the instance on `port`; `None`
+    /// when there are none.
+    fn forward_args(&self, port: u16) -> Option<Result<(), String>>;
+    /// A port to start on when ours is taken by another application;
+    /// `None` unless `port_fallback` is enabled.
+    fn fallback_port(&self) -> Option<u16>;
+}
+
+/// Probes against the real mutex, port and windows.
+pub struct SystemProbe {
+    /// App PID from the PID file, used to reject same-titled foreign windows.
+    pub tracked_pid: Option<u32>,
+    /// Another launcher holds the launcher lock (it may still be starting
+    /// the app, before the app's own mutex exists).
+    pub launcher_running: bool,
+    pub port_fallback: bool,
+    /// Non-flag arguments (document paths) for a running instance.
+    pub forward_args: Vec<String>,
+}
+
+impl InstanceProbe for SystemProbe {
+    fn mutex_present(&self, mutex_name: &str) -> bool {
+        self.launcher_running || is_instance_mutex_present(mutex_name)
+    }
+
+    fn lock_held(&self) -> bool {
+        self.tracked_pid.is_some_and(process_alive)
+    }
+
+    fn app_status(&self, port: u16) -> AppStatus {
+        check_app_status(port)
+    }
+
+    fn focus_window(&self, window_title: &str) -> bool {
+        bring_window_to_front(window_title, self.tracked_pid)
+    }
+
+    fn activate(&self, port: u16) -> bool {
+        request_activate(port)
+    }
+
+    fn forward_args(&self, port: u16) -> Option<Result<(), String>> {
+        (!self.forward_args.is_empty())
+            .then(|| forward_args_to_running_instance(port, &self.forward_args))
+    }
+
+    fn fallback_port(&self) -> Option<u16> {
+        self.port_fallback.then(free_loopback_port).flatten()
+    }
+}
+
+/// Decide how to launch, skipping the port probe when the mutex already
+/// proves our instance is running (the focus fast path).
+pub fn probe_instance<P: InstanceProbe>(
+    probe: &P,
+    instance: &InstanceScope,
+    allow_multi_instance: bool,
+    check: InstanceCheck,
+) -> LaunchDecision {
+    if !allow_multi_instance && probe.mutex_present(&instance.mutex_name) {
+        return LaunchDecision::FocusExisting;
+    }
+    match check {
+        InstanceCheck::PortProbe => {
+            // The app fell back to another port: it answers there, not on ours.
+            if let Some(port) = instance.reported_port.filter(|&port| port != instance.port) {
+                if probe.app_status(port) == AppStatus::Running {
+                    return LaunchDecision::FocusExisting;
+                }
+            }
+            decide_launch(false, probe.app_status(instance.port))
+        }
+        InstanceCheck::LockOnly if !allow_multi_instance && probe.lock_held() => {
+            LaunchDecision::FocusExisting
+        }
+        InstanceCheck::LockOnly => LaunchDecision::Launch,
+    }
+}
+
+/// Whether `pid` is a running process.
+#[cfg(windows)]
+pub fn process_alive(pid: u32) -> bool {
+    use winapi::shared::minwindef::FALSE;
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::minwinbase::STILL_ACTIVE;
+    use winapi::um::processthreadsapi::{GetExitCodeProcess, OpenProcess};
+    use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, pid);
+        if handle.is_null() {
+            return false;
+        }
+        let mut code = 0u32;
+        let alive = GetExitCodeProcess(handle, &mut code) != 0 && code == STILL_ACTIVE;
+        CloseHandle(handle);
+        alive
+    }
+}
+
+/// Whether `pid` is a running process: `kill(pid, 0)` succeeds, or fails
+/// only because the process belongs to another user.
+#[cfg(unix)]
+pub fn process_alive(pid: u32) -> bool {
+    use crate::shutdown::signals;
+
+    // 0 and negative PIDs name process groups, not a process.
+    match i32::try_from(pid) {
+        Ok(pid) if pid > 0 => {
+            let signalled = unsafe { signals::kill(pid, 0) } == 0;
+            signalled || std::io::Error::last_os_error().raw_os_error() == Some(signals::EPERM)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(not(any(windows, unix)))]
+pub fn process_alive(_pid: u32) -> bool {
+    false
+}
+
+/// Single-instance identity (mutex name + port) resolved for this launch.
+/// Both values are handed to the app so it claims the same identity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceScope {
+    pub scope: SingletonScope,
+    /// `--instance` name, `None` for the default instance.
+    pub name: Option<String>,
+    pub port: u16,
+    pub mutex_name: String,
+    pub window_title: String,
+    /// Port the app reported binding instead of `port`, if any (see
+    /// [`read_reported_port`]).
+    pub reported_port: Option<u16>,
+}
+
+impl InstanceScope {
+    pub fn resolve(scope: SingletonScope, base_port: u16, name: Option<&str>) -> Self {
+        Self::for_session(scope, base_port, name, current_session_id())
+    }
+
+    pub fn for_session(
+        scope: SingletonScope,
+        base_port: u16,
+        name: Option<&str>,
+        session_id: u32,
+    ) -> Self {
+        let port = scoped_port(scope, base_port, session_id);
+        InstanceScope {
+            scope,
+            name: name.map(str::to_string),
+            port: name.map_or(port, |name| named_instance_port(port, name)),
+            mutex_name: instance_mutex_name(scope, name),
+            window_title: instance_window_title(name),
+            reported_port: None,
+        }
+    }
+
+    /// Where the app actually listens: the reported port, else `port`.
+    pub fn app_port(&self) -> u16 {
+        self.reported_port.unwrap_or(self.port)
+    }
+}
+
+/// One `app_ports` entry written to `launcher_context.json` when the app
+/// bound a different port than requested (by the app, or by the launcher
+/// after a `port_fallback`), keyed by the requested port:
+///
+/// ```json
+/// {"app_ports": {"8765": {"port": 49152, "pid": 4242, "ts": 1700000000.0}}}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ReportedPort {
+    port: u16,
+    pid: u32,
+    ts: f64,
+}
+
+/// The port reported for `requested`, if the report is for another port,
+/// recent (see `REPORTED_PORT_TTL_SEC`) and from a live process.
+pub fn parse_reported_port(
+    context: &str,
+    requested: u16,
+    now_secs: u64,
+    alive: impl Fn(u32) -> bool,
+) -> Option<u16> {
+    let context: Map<String, Value> = serde_json::from_str(context).ok()?;
+    let entry = context.get("app_ports")?.get(requested.to_string())?;
+    let report: ReportedPort = serde_json::from_value(entry.clone()).ok()?;
+    if report.port == 0 || report.port == requested {
+        return None;
+    }
+    if !report.ts.is_finite() || report.ts < 0.0 {
+        return None;
+    }
+    let ts = report.ts.floor() as u64;
+    if ts > now_secs + REPORTED_PORT_SKEW_SEC || now_secs.saturating_sub(ts) > REPORTED_PORT_TTL_SEC
+    {
+        return None;
+    }
+    alive(report.pid).then_some(report.port)
+}
+
+/// Record that `requested` is served on `port` by `pid`, keeping the rest
+/// of the context file.
+pub fn record_reported_port(
+    context_path: &Path,
+    requested: u16,
+    port: u16,
+    pid: u32,
+) -> Result<(), String> {
+    let mut context: Map<String, Value> = fs::read_to_string(context_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    let report = ReportedPort {
+        port,
+        pid,
+        ts: now_secs() as f64,
+    };
+    let mut ports = match context.remove("app_ports") {
+        Some(Value::Object(ports)) => ports,
+        _ => Map::new(),
+    };
+    ports.insert(
+        requested.to_string(),
+        serde_json::to_value(report).map_err(|e| e.to_string())?,
+    );
+    context.insert("app_ports".to_string(), Value::Object(ports));
+    let content = serde_json::to_string(&context).map_err(|e| e.to_string())?;
+    write_file_atomic(context_path, &content)
+}
+
+/// `parse_reported_port` over the context file at `context_path`.
+pub fn read_reported_port(context_path: &Path, requested: u16) -> Option<u16> {
+    let content = fs::read_to_string(context_path).ok()?;
+    parse_reported_port(&content, requested, now_secs(), process_alive)
+}
+
+pub fn instance_mutex_name(scope: SingletonScope, name: Option<&str>) -> String {
+    let namespace = match scope {
+        SingletonScope::PerMachine => "Global",
+        SingletonScope::PerUser => "Local",
+    };
+    match name {
+        Some(name) => format!("{}\\{}-{}", namespace, INSTANCE_MUTEX_BASE_NAME, name),
+        None => format!("{}\\{}", namespace, INSTANCE_MUTEX_BASE_NAME),
+    }
+}
+
+pub fn instance_window_title(name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("{} ({})", APP_WINDOW_TITLE, name),
+        None => APP_WINDOW_TITLE.to_string(),
+    }
+}
+
+/// Stable per-name port above the scope's port (FNV-1a of the name).
+pub fn named_instance_port(scope_port: u16, name: &str) -> u16 {
+    let hash = name.bytes().fold(0x811c9dc5u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x01000193)
+    });
+    let offset = PER_USER_PORT_SPAN + 1 + hash % NAMED_INSTANCE_PORT_SPAN;
+    u16::try_from(u32::from(scope_port) + offset).unwrap_or(scope_port)
+}
+
+/// Title match with a word boundary, so "YakuLingo.html" never matches.
+/// A named instance's "YakuLingo (work)" is not treated as the default
+/// "YakuLingo" window, but documents open in it ("YakuLingo - a.txt") are.
+pub fn window_title_matches(title: &str, expected: &str) -> bool {
+    if title.is_empty() || expected.is_empty() {
+        return false;
+    }
+    if title == expected {
+        return true;
+    }
+    match title.strip_prefix(expected) {
+        Some(rest) => {
+            rest.chars().next().is_some_and(char::is_whitespace) && !rest.starts_with(" (")
+        }
+        None => false,
+    }
+}
+
+/// Per-machine launches share `base_port`; per-user launches get a port
+/// offset by the session ID so concurrent sessions do not collide.
+pub fn scoped_port(scope: SingletonScope, base_port: u16, session_id: u32) -> u16 {
+    match scope {
+        SingletonScope::PerMachine => base_port,
+        SingletonScope::PerUser => {
+            let offset = 1 + session_id % PER_USER_PORT_SPAN;
+            u16::try_from(u32::from(base_port) + offset).unwrap_or(base_port)
+        }
+    }
+}
+
+/// Terminal Services session ID of this process.
+#[cfg(windows)]
+pub fn current_session_id() -> u32 {
+    use winapi::um::processthreadsapi::{GetCurrentProcessId, ProcessIdToSessionId};
+
+    let mut session_id: u32 = 0;
+    unsafe {
+        if ProcessIdToSessionId(GetCurrentProcessId(), &mut session_id) == 0 {
+            return 0;
+        }
+    }
+    session_id
+}
+
+/// On Unix the user ID stands in for the session (one instance per user).
+#[cfg(unix)]
+pub fn current_session_id() -> u32 {
+    use std::os::unix::fs::MetadataExt;
+
+    crate::paths::get_home_dir()
+        .and_then(|home| std::fs::metadata(home).ok())
+        .map(|meta| meta.uid())
+        .unwrap_or(0)
+}
+
+#[cfg(not(any(windows, unix)))]
+pub fn current_session_id() -> u32 {
+    0
+}
+
+/// How many parent links to follow from a window's process to the app PID.
+const MAX_PROCESS_ANCESTRY: usize = 4;
+
+/// Whether a window owned by `window_pid` belongs to the tracked app: the
+/// process itself or a descendant (NiceGUI opens the native window in a
+/// child process). Without a tracked PID every window passes, leaving the
+/// title match as the only filter.
+pub fn window_pid_matches(
+    window_pid: u32,
+    tracked_pid: Option<u32>,
+    parent_of: impl Fn(u32) -> Option<u32>,
+) -> bool {
+    let tracked_pid = match tracked_pid {
+        Some(value) => value,
+        None => return true,
+    };
+    let mut pid = window_pid;
+    for _ in 0..=MAX_PROCESS_ANCESTRY {
+        if pid == tracked_pid {
+            return true;
+        }
+        match parent_of(pid) {
+            Some(parent) if parent != 0 && parent != pid => pid = parent,
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// PID -> parent PID for all running processes.
+#[cfg(windows)]
+fn process_parents() -> std::collections::HashMap<u32, u32> {
+    use std::collections::HashMap;
+    use std::mem::{size_of, zeroed};
+    use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+    use winapi::um::tlhelp32::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
+
+    let mut parents = HashMap::new();
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+        if snapshot == INVALID_HANDLE_VALUE {
+            return parents;
+        }
+        let mut entry: PROCESSENTRY32W = zeroed();
+        entry.dwSize = size_of::<PROCESSENTRY32W>() as u32;
+        if Process32FirstW(snapshot, &mut entry) != 0 {
+            loop {
+                parents.insert(entry.th32ProcessID, entry.th32ParentProcessID);
+                if Process32NextW(snapshot, &mut entry) == 0 {
+                    break;
+                }
+            }
+        }
+        CloseHandle(snapshot);
+    }
+    parents
+}
+
+/// Attempt to bring existing YakuLingo window to the foreground when already running.
+/// With `tracked_pid`, only windows of that process (or its children) qualify.
+#[cfg(windows)]
+pub fn bring_window_to_front(window_title: &str, tracked_pid: Option<u32>) -> bool {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+    use winapi::shared::minwindef::{BOOL, LPARAM};
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{
+        EnumWindows, GetClassNameW, GetForegroundWindow, GetWindowTextLengthW, GetWindowTextW,
+        GetWindowThreadProcessId, IsIconic, SetForegroundWindow, ShowWindow, SW_RESTORE, SW_SHOW,
+    };
+
+    struct WindowSearch {
+        title: String,
+        tracked_pid: Option<u32>,
+        parents: std::collections::HashMap<u32, u32>,
+        handle: Option<HWND>,
+    }
+
+    unsafe extern "system" fn enum_proc(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let search = &mut *(lparam as *mut WindowSearch);
+
+        // Skip windows with no title
+        if GetWindowTextLengthW(hwnd) == 0 {
+            return 1; // TRUE to continue
+        }
+
+        let length = GetWindowTextLengthW(hwnd) as usize;
+        let mut buffer = vec![0u16; length + 1];
+        let read_len = GetWindowTextW(hwnd, buffer.as_mut_ptr(), buffer.len() as i32);
+        if read_len <= 0 {
+            return 1;
+        }
+
+        buffer.truncate(read_len as usize);
+        let title = OsString::from_wide(&buffer).to_string_lossy().to_string();
+
+        if title.starts_with("Setup - YakuLingo") {
+            return 1;
+        }
+
+        // Avoid matching File Explorer windows like "YakuLingo - エクスプローラー".
+        let mut class_buf = [0u16; 256];
+        let class_len = GetClassNameW(hwnd, class_buf.as_mut_ptr(), class_buf.len() as i32);
+        if class_len > 0 {
+            let class_name = OsString::from_wide(&class_buf[..class_len as usize])
+                .to_string_lossy()
+                .to_string();
+            if class_name == "CabinetWClass" || class_name == "ExploreWClass" {
+                return 1;
+            }
+        }
+
+        // Avoid matching unrelated windows like "YakuLingo.html ...".
+        if window_title_matches(&title, &search.title) {
+            // A browser tab or other app can carry the same title; require the app's process.
+            let mut window_pid = 0u32;
+            GetWindowThreadProcessId(hwnd, &mut window_pid);
+            if !window_pid_matches(window_pid, search.tracked_pid, |pid| {
+                search.parents.get(&pid).copied()
+            }) {
+                return 1;
+            }
+            search.handle = Some(hwnd);
+            return 0; // FALSE to stop enumeration
+        }
+
+        1
+    }
+
+    let parents = if tracked_pid.is_some() {
+        process_parents()
+    } else {
+        Default::default()
+    };
+    // A PID file left by a dead process proves nothing: fall back to the title.
+    let tracked_pid = tracked_pid.filter(|pid| parents.contains_key(pid));
+    let mut search = WindowSearch {
+        title: window_title.to_string(),
+        tracked_pid,
+        parents,
+        handle: None,
+    };
+    let search_ptr: *mut WindowSearch = &mut search;
+
+    unsafe {
+        EnumWindows(Some(enum_proc), search_ptr as LPARAM);
+
+        if let Some(hwnd) = search.handle {
+            if IsIconic(hwnd) != 0 {
+                ShowWindow(hwnd, SW_RESTORE);
+            } else {
+                ShowWindow(hwnd, SW_SHOW);
+            }
+
+            if GetForegroundWindow() != hwnd {
+                SetForegroundWindow(hwnd);
+            }
+
+            return true;
+        }
+    }
+
+    false
+}
+
+/// A top-level window reported by `wmctrl -lp` (Linux) or System Events
+/// (macOS). `id` is whatever the raise command needs: the X11 window ID,
+/// or the window's index within its process on macOS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListedWindow {
+    pub id: String,
+    pub pid: u32,
+    pub title: String,
+}
+
+/// Parse `wmctrl -lp`: `<id> <desktop> <pid> <host> <title...>`. Windows
+/// without a PID (`0`, set by some toolkits) are kept; they can only pass
+/// the title check.
+pub fn parse_wmctrl_list(output: &str) -> Vec<ListedWindow> {
+    fn next_field(rest: &str) -> Option<(&str, &str)> {
+        let rest = rest.trim_start();
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        (end > 0).then(|| (&rest[..end], &rest[end..]))
+    }
+
+    output
+        .lines()
+        .filter_map(|line| {
+            let (id, rest) = next_field(line)?;
+            let (_desktop, rest) = next_field(rest)?;
+            let (pid, rest) = next_field(rest)?;
+            let pid = pid.parse().ok()?;
+            let (_host, rest) = next_field(rest)?;
+            let title = rest.trim();
+            // Untitled windows are skipped, as on Windows.
+            (!title.is_empty()).then(|| ListedWindow {
+                id: id.to_string(),
+                pid,
+                title: title.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parse the `<pid>\t<index>\t<title>` lines printed by the macOS
+/// window listing script.
+pub fn parse_tab_window_list(output: &str) -> Vec<ListedWindow> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let pid = fields.next()?.trim().parse().ok()?;
+            let id = fields.next()?.trim().to_string();
+            let title = fields.next()?.trim().to_string();
+            Some(ListedWindow { id, pid, title })
+        })
+        .collect()
+}
+
+/// Parse `ps -A -o pid= -o ppid=` into PID -> parent PID.
+pub fn parse_ps_parents(output: &str) -> std::collections::HashMap<u32, u32> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let parent = fields.next()?.parse().ok()?;
+            Some((pid, parent))
+        })
+        .collect()
+}
+
+/// The window to raise, with the same rules as the Windows search: the
+/// setup wizard is skipped, the title must match, and with a live tracked
+/// PID the window must belong to the app's process tree.
+pub fn pick_window<'a>(
+    windows: &'a [ListedWindow],
+    window_title: &str,
+    tracked_pid: Option<u32>,
+    parents: &std::collections::HashMap<u32, u32>,
+) -> Option<&'a ListedWindow> {
+    // A PID file left by a dead process proves nothing: fall back to the title.
+    let tracked_pid = tracked_pid.filter(|pid| parents.contains_key(pid));
+    windows.iter().find(|window| {
+        !window.title.starts_with("Setup - YakuLingo")
+            && window_title_matches(&window.title, window_title)
+            && window_pid_matches(window.pid, tracked_pid, |pid| parents.get(&pid).copied())
+    })
+}
+
+/// Stdout of a helper command, `None` if it is missing or fails.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    use std::process::{Command, Stdio};
+
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn unix_process_parents(tracked_pid: Option<u32>) -> std::collections::HashMap<u32, u32> {
+    tracked_pid
+        .and_then(|_| command_stdout("ps", &["-A", "-o", "pid=", "-o", "ppid="]))
+        .map(|output| parse_ps_parents(&output))
+        .unwrap_or_default()
+}
+
+/// X11 (or XWayland) through `wmctrl`: list the windows, then activate the
+/// match, which also switches desktops and restores it if minimized.
+/// Without `wmctrl` nothing is raised.
+#[cfg(target_os = "linux")]
+pub fn bring_window_to_front(window_title: &str, tracked_pid: Option<u32>) -> bool {
+    let Some(listing) = command_stdout("wmctrl", &["-lp"]) else {
+        return false;
+    };
+    let windows = parse_wmctrl_list(&listing);
+    let parents = unix_process_parents(tracked_pid);
+    match pick_window(&windows, window_title, tracked_pid, &parents) {
+        Some(window) => command_stdout("wmctrl", &["-i", "-a", &window.id]).is_some(),
+        None => false,
+    }
+}
+
+/// Lists every app window through System Events, then makes the
+/// matching process frontmost and raises that window. Needs the
+/// Accessibility permission; without it the listing fails and nothing is
+/// raised.
+#[cfg(target_os = "macos")]
+pub fn bring_window_to_front(window_title: &str, tracked_pid: Option<u32>) -> bool {
+    const LIST_SCRIPT: &str = r#"set out to ""
+tell application "System Events"
+    repeat with p in (processes whose background only is false)
+        set pid to unix id of p
+        set i to 0
+        repeat with w in windows of p
+            set i to i + 1
+            try
+                set out to out & pid & tab & i & tab & (name of w) & linefeed
+            end try
+        end repeat
+    end repeat
+end tell
+return out"#;
+
+    let Some(listing) = command_stdout("osascript", &["-e", LIST_SCRIPT]) else {
+        return false;
+    };
+    let windows = parse_tab_window_list(&listing);
+    let parents = unix_process_parents(tracked_pid);
+    let Some(window) = pick_window(&windows, window_title, tracked_pid, &parents) else {
+        return false;
+    };
+    let raise_script = format!(
+        r#"tell application "System Events"
+    set p to first process whose unix id is {pid}
+    set frontmost of p to true
+    try
+        set value of attribute "AXMinimized" of window {index} of p to false
+    end try
+    perform action "AXRaise" of window {index} of p
+end tell"#,
+        pid = window.pid,
+        index = window.id
+    );
+    command_stdout("osascript", &["-e", &raise_script]).is_some()
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+pub fn bring_window_to_front(_window_title: &str, _tracked_pid: Option<u32>) -> bool {
+    false
+}
+
+#[cfg(windows)]
+pub fn is_instance_mutex_present(mutex_name: &str) -> bool {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::shared::minwindef::FALSE;
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::synchapi::OpenMutexW;
+    use winapi::um::winnt::SYNCHRONIZE;
+
+    let wide_name: Vec<u16> = OsStr::new(mutex_name)
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    unsafe {
+        let handle = OpenMutexW(SYNCHRONIZE, FALSE, wide_name.as_ptr());
+        if handle.is_null() {
+            return false;
+        }
+        CloseHandle(handle);
+    }
+    true
+}
+
+#[cfg(not(windows))]
+pub fn is_instance_mutex_present(_mutex_name: &str) -> bool {
+    false
+}
+
+pub fn request_activate(port: u16) -> bool {
+    let addr = format!("127.0.0.1:{}", port);
+    let mut stream =
+        match TcpStream::connect_timeout(&addr.parse().unwrap(), Duration::from_millis(200)) {
+            Ok(value) => value,
+            Err(_) => return false,
+        };
+    let request = b"POST /api/activate HTTP/1.1\r\nHost: 127.0.0.1\r\nX-YakuLingo-Activate: 1\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    stream.write_all(request).is_ok()
+}
+
+/// `POST /api/open-args` carrying a second launch's arguments and working
+/// directory (to resolve relative paths), e.g.
+/// `{"args": ["report.docx"], "cwd": "C:\\Users\\me\\Documents"}`.
+pub fn forward_args_request(args: &[String], cwd: &Path) -> String {
+    let body = serde_json::json!({ "args": args, "cwd": cwd }).to_string();
+    format!(
+        "POST /api/open-args HTTP/1.1\r\nHost: 127.0.0.1\r\nX-YakuLingo-Open: 1\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    )
+}
+
+/// The status code of an HTTP response's status line.
+pub fn response_status(response: &str) -> Option<u16> {
+    let status_line = response.lines().next()?;
+    let mut parts = status_line.split_whitespace();
+    parts
+        .next()
+        .filter(|version| version.starts_with("HTTP/"))?;
+    parts.next()?.parse().ok()
+}
+
+/// Hand `args` to the instance on `port`; an error (including an app
+/// without `/api/open-args`) means the caller should just focus it.
+pub fn forward_args_to_running_instance(port: u16, args: &[String]) -> Result<(), String> {
+    let cwd = std::env::current_dir().map_err(|e| format!("No working directory: {}", e))?;
+    let addr = format!("127.0.0.1:{}", port);
+    let mut stream = TcpStream::connect_timeout(&addr.parse().unwrap(), Duration::from_millis(200))
+        .map_err(|e| format!("Failed to connect to port {}: {}", port, e))?;
+    let _ = stream.set_read_timeout(Some(Duration::from_millis(FORWARD_RESPONSE_TIMEOUT_MS)));
+    stream
+        .write_all(forward_args_request(args, &cwd).as_bytes())
+        .map_err(|e| format!("Failed to send arguments: {}", e))?;
+    let mut response = [0u8; 256];
+    let read = stream
+        .read(&mut response)
+        .map_err(|e| format!("No response to forwarded arguments: {}", e))?;
+    match response_status(&String::from_utf8_lossy(&response[..read])) {
+        Some(200) => Ok(()),
+        Some(code) => Err(format!("Forwarded arguments rejected (HTTP {})", code)),
+        None => Err("Invalid response to forwarded arguments".to_string()),
+    }
+}
+
+/// Ask the app to shut down via `/api/shutdown`. With `restart` the restart
+/// header is sent, so no user-exit state is written and the watchdog brings
+/// the app back; without it the app records a user exit.
+pub fn request_shutdown(port: u16, reason: &str, restart: bool) -> bool {
+    let addr = format!("127.0.0.1:{}", port);
+    let mut stream =
+        match TcpStream::connect_timeout(&addr.parse().unwrap(), Duration::from_millis(200)) {
+            Ok(value) => value,
+            Err(_) => return false,
+        };
+    let body = format!("{{\"reason\": \"{}\"}}", reason);
+    let restart_header = if restart {
+        "X-YakuLingo-Restart: 1\r\n"
+    } else {
+        ""
+    };
+    let request = format!(
+        "POST /api/shutdown HTTP/1.1\r\nHost: 127.0.0.1\r\nX-YakuLingo-Exit: 1\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        restart_header,
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes()).is_ok()
+}
+
+/// Check if the application is already running by probing a local API endpoint.
+pub fn check_app_status(port: u16) -> AppStatus {
+    let addr = format!("127.0.0.1:{}", port);
+    let mut stream = match TcpStream::connect_timeout(
+        &addr.parse().unwrap(),
+        Duration::from_millis(PROBE_CONNECT_TIMEOUT_MS),
+    ) {
+        Ok(value) => value,
+        Err(_) => return AppStatus::NotRunning,
+    };
+
+    let _ = stream.set_read_timeout(Some(Duration::from_millis(PROBE_IO_TIMEOUT_MS)));
+    let _ = stream.set_write_timeout(Some(Duration::from_millis(PROBE_IO_TIMEOUT_MS)));
+
+    let request = b"GET /api/setup-status HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\n\r\n";
+    if stream.write_all(request).is_err() {
+        return AppStatus::PortInUse;
+    }
+
+    let mut response = String::new();
+    let mut buffer = [0u8; 512];
+    loop {
+        match stream.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => {
+                response.push_str(&String::from_utf8_lossy(&buffer[..read]));
+                if response.len() >= 4096 {
+                    break;
+                }
+            }
+            Err(err)
+                if err.kind() == ErrorKind::WouldBlock || err.kind() == ErrorKind::TimedOut =>
+            {
+                break;
+            }
+            Err(_) => break,
+        }
+    }
+
+    if response.is_empty() {
+        return AppStatus::PortInUse;
+    }
+    if is_yakulingo_setup_response(&response) {
+        AppStatus::Running
+    } else {
+        AppStatus::PortInUse
+    }
+}
+
+pub fn is_yakulingo_setup_response(response: &str) -> bool {
+    let status_line = response.lines().next().unwrap_or("");
+    if !status_line.contains(" 200 ") {
+        return false;
+    }
+
+    let body = if let Some((_, body)) = response.split_once("\r\n\r\n") {
+        body
+    } else if let Some((_, body)) = response.split_once("\n\n") {
+        body
+    } else {
+        ""
+    };
+
+    body.contains("\"ready\"") || body.contains("\"active\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forwarded_args_request_is_json_with_cwd() {
+        let args = vec!["report.docx".to_string(), "C:\\a \"b\".txt".to_string()];
+        let request = forward_args_request(&args, Path::new("C:\\Docs"));
+        let (head, body) = request.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("POST /api/open-args HTTP/1.1\r\n"));
+        assert!(head.contains("X-YakuLingo-Open: 1"));
+        assert!(head.contains(&format!("Content-Length: {}", body.len())));
+        let body: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(body["args"][1], "C:\\a \"b\".txt");
+        assert_eq!(body["cwd"], "C:\\Docs");
+
+        assert_eq!(response_status("HTTP/1.1 200 OK\r\n\r\n{}"), Some(200));
+        assert_eq!(response_status("HTTP/1.1 404 Not Found\r\n"), Some(404));
+        assert_eq!(response_status("SSH-2.0-OpenSSH"), None);
+    }
+
+    #[test]
+    fn args_are_forwarded_to_the_running_app() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let app = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut chunk = [0u8; 512];
+            while !request.ends_with(b"}") {
+                let read = stream.read(&mut chunk).unwrap();
+                assert!(read > 0, "request ended early");
+                request.extend_from_slice(&chunk[..read]);
+            }
+            stream.write_all(b"HTTP/1.1 200 OK\r\n\r\n").unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let args = vec!["report.docx".to_string()];
+        assert_eq!(forward_args_to_running_instance(port, &args), Ok(()));
+        assert!(app.join().unwrap().contains("\"args\":[\"report.docx\"]"));
+    }
+
+    #[test]
+    fn recorded_fallback_port_is_read_back() {
+        let dir = crate::test_util::temp_dir("reported-port-record");
+        let path = dir.join("launcher_context.json");
+        fs::write(&path, "{\"python\": {\"version\": \"3.11.9\"}}").unwrap();
+        record_reported_port(&path, 8765, 49152, std::process::id()).unwrap();
+        let context: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(context["python"]["version"], "3.11.9");
+        assert_eq!(
+            parse_reported_port(&context.to_string(), 8765, now_secs(), |_| true),
+            Some(49152)
+        );
+        assert_eq!(
+            parse_reported_port(&context.to_string(), 8766, now_secs(), |_| true),
+            None
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn reported_port_is_validated() {
+        let now = 1_700_000_000;
+        let context = |port: u16, ts: u64| {
+            format!(
+                "{{\"python\": {{}}, \"app_ports\": {{\"8765\": {{\"port\": {}, \"pid\": 4242, \"ts\": {}}}}}}}",
+                port, ts
+            )
+        };
+        let alive = |pid: u32| pid == 4242;
+        assert_eq!(
+            parse_reported_port(&context(49152, now - 10), 8765, now, alive),
+            Some(49152)
+        );
+        assert_eq!(
+            parse_reported_port(&context(49152, now - 10), 8766, now, alive),
+            None,
+            "reported for another instance"
+        );
+        assert_eq!(
+            parse_reported_port(&context(49152, now - 10), 8765, now, |_| false),
+            None,
+            "reporting process is gone"
+        );
+        let stale = now - REPORTED_PORT_TTL_SEC - 1;
+        assert_eq!(
+            parse_reported_port(&context(49152, stale), 8765, now, alive),
+            None
+        );
+        assert_eq!(
+            parse_reported_port(&context(0, now), 8765, now, alive),
+            None
+        );
+        assert_eq!(parse_reported_port("{}", 8765, now, alive), None);
+    }
+
+    #[test]
+    fn mutex_name_follows_scope_namespace() {
+        assert_eq!(
+            instance_mutex_name(SingletonScope::PerMachine, None),
+            "Global\\YakuLingoSingleton"
+        );
+        assert_eq!(
+            instance_mutex_name(SingletonScope::PerUser, None),
+            "Local\\YakuLingoSingleton"
+        );
+    }
+
+    #[test]
+    fn per_machine_scope_shares_base_port() {
+        let a = InstanceScope::for_session(SingletonScope::PerMachine, 8765, None, 1);
+        let b = InstanceScope::for_session(SingletonScope::PerMachine, 8765, None, 2);
+        assert_eq!(a.port, 8765);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn per_user_scope_derives_distinct_ports_per_session() {
+        assert_eq!(scoped_port(SingletonScope::PerUser, 8765, 0), 8766);
+        assert_eq!(scoped_port(SingletonScope::PerUser, 8765, 3), 8769);
+        assert_eq!(scoped_port(SingletonScope::PerUser, 8765, 1003), 8769);
+        // Never overflows past u16::MAX.
+        assert_eq!(scoped_port(SingletonScope::PerUser, 65535, 5), 65535);
+    }
+
+    #[test]
+    fn named_instance_derives_its_own_identity() {
+        let default = InstanceScope::for_session(SingletonScope::PerMachine, 8765, None, 1);
+        let work = InstanceScope::for_session(SingletonScope::PerMachine, 8765, Some("work"), 1);
+        let again = InstanceScope::for_session(SingletonScope::PerMachine, 8765, Some("work"), 1);
+        let personal =
+            InstanceScope::for_session(SingletonScope::PerMachine, 8765, Some("personal"), 1);
+
+        assert_eq!(work, again);
+        assert_eq!(work.mutex_name, "Global\\YakuLingoSingleton-work");
+        assert_eq!(work.window_title, "YakuLingo (work)");
+        assert!(work.port > 8765 + 1000 && work.port <= 8765 + 2000);
+        assert_ne!(work.port, default.port);
+        assert_ne!(work.port, personal.port);
+        assert_ne!(work.mutex_name, personal.mutex_name);
+    }
+
+    #[test]
+    fn same_name_focuses_and_new_name_launches() {
+        // Probing is keyed by the derived identity, so a running "work"
+        // instance only shows up when the same name is probed again.
+        assert_eq!(
+            decide_launch(true, AppStatus::NotRunning),
+            LaunchDecision::FocusExisting
+        );
+        assert_eq!(
+            decide_launch(false, AppStatus::Running),
+            LaunchDecision::FocusExisting
+        );
+        assert_eq!(
+            decide_launch(false, AppStatus::NotRunning),
+            LaunchDecision::Launch
+        );
+        assert_eq!(
+            decide_launch(false, AppStatus::PortInUse),
+            LaunchDecision::PortConflict
+        );
+    }
+
+    #[test]
+    fn window_title_match_respects_boundaries_and_instance_suffix() {
+        assert!(window_title_matches("YakuLingo", "YakuLingo"));
+        assert!(window_title_matches("YakuLingo - a.txt", "YakuLingo"));
+        assert!(!window_title_matches("YakuLingo.html - Edge", "YakuLingo"));
+        assert!(!window_title_matches("YakuLingo (work)", "YakuLingo"));
+        assert!(window_title_matches("YakuLingo (work)", "YakuLingo (work)"));
+        assert!(!window_title_matches(
+            "YakuLingo (personal)",
+            "YakuLingo (work)"
+        ));
+    }
+
+    #[test]
+    fn window_pid_filter_rejects_foreign_windows() {
+        use std::collections::HashMap;
+        // 100 = python.exe (tracked), 101 = its native-window child,
+        // 200 = a browser showing a "YakuLingo" tab, 1 = their common ancestor.
+        let parents: HashMap<u32, u32> = [(100, 1), (101, 100), (102, 101), (200, 1)].into();
+        let parent_of = |pid: u32| parents.get(&pid).copied();
+
+        assert!(window_pid_matches(100, Some(100), parent_of));
+        assert!(window_pid_matches(101, Some(100), parent_of));
+        assert!(window_pid_matches(102, Some(100), parent_of));
+        assert!(!window_pid_matches(200, Some(100), parent_of));
+        assert!(!window_pid_matches(1, Some(100), parent_of));
+        assert!(!window_pid_matches(999, Some(100), parent_of));
+    }
+
+    #[test]
+    fn window_pid_filter_falls_back_to_title_without_pid() {
+        assert!(window_pid_matches(200, None, |_| None));
+    }
+
+    #[test]
+    fn window_pid_filter_stops_on_parent_cycles() {
+        // PIDs are reused, so parent links can loop.
+        let parent_of = |pid: u32| Some(if pid == 300 { 301 } else { 300 });
+        assert!(!window_pid_matches(300, Some(100), parent_of));
+    }
+
+    #[test]
+    fn wmctrl_and_macos_listings_are_parsed() {
+        let wmctrl = "0x01e00003 -1 812    host Desktop\n\
+                      0x04000007  0 4321   host YakuLingo - a  b.docx\n\
+                      0x04200001  0 0      host\n\
+                      garbage\n";
+        assert_eq!(
+            parse_wmctrl_list(wmctrl),
+            vec![
+                ListedWindow {
+                    id: "0x01e00003".to_string(),
+                    pid: 812,
+                    title: "Desktop".to_string(),
+                },
+                ListedWindow {
+                    id: "0x04000007".to_string(),
+                    pid: 4321,
+                    title: "YakuLingo - a  b.docx".to_string(),
+                },
+            ]
+        );
+        let macos = parse_tab_window_list("4321\t2\tYakuLingo\nbad line\n");
+        assert_eq!(macos.len(), 1);
+        assert_eq!((macos[0].pid, macos[0].id.as_str()), (4321, "2"));
+        assert_eq!(macos[0].title, "YakuLingo");
+    }
+
+    #[test]
+    fn picked_window_follows_the_windows_rules() {
+        let window = |id: &str, pid: u32, title: &str| ListedWindow {
+            id: id.to_string(),
+            pid,
+            title: title.to_string(),
+        };
+        let windows = vec![
+            window("setup", 100, "Setup - YakuLingo"),
+            window("browser", 300, "YakuLingo - Firefox"),
+            window("html", 200, "YakuLingo.html"),
+            window("app", 200, "YakuLingo"),
+        ];
+        let parents = parse_ps_parents("  100     1\n  200   100\n  300     1\n");
+        assert_eq!(parents.get(&200), Some(&100));
+
+        let picked = pick_window(&windows, "YakuLingo", Some(100), &parents);
+        assert_eq!(picked.map(|w| w.id.as_str()), Some("app"));
+        // Without a (live) tracked PID the first title match wins.
+        let picked = pick_window(&windows, "YakuLingo", Some(999), &parents);
+        assert_eq!(picked.map(|w| w.id.as_str()), Some("browser"));
+        assert!(pick_window(&windows, "YakuLingo (work)", None, &parents).is_none());
+    }
+
+    #[test]
+    fn setup_response_requires_ok_status_and_known_field() {
+        let ok = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"ready\": true}";
+        assert!(is_yakulingo_setup_response(ok));
+
+        let lf_only = "HTTP/1.1 200 OK\n\n{\"active\": false}";
+        assert!(is_yakulingo_setup_response(lf_only));
+
+        let not_found = "HTTP/1.1 404 Not Found\r\n\r\n{\"ready\": true}";
+        assert!(!is_yakulingo_setup_response(not_found));
+
+        let foreign = "HTTP/1.1 200 OK\r\n\r\n<html>hello</html>";
+        assert!(!is_yakulingo_setup_response(foreign));
+    }
+
+    #[test]
+    fn loopback_self_test_selects_fallback_when_blocked() {
+        assert!(loopback_works(probe_connect));
+        assert_eq!(
+            select_instance_check(loopback_works(probe_connect)),
+            InstanceCheck::PortProbe
+        );
+
+        let blocked = |_addr: SocketAddr| -> io::Result<TcpStream> {
+            Err(io::Error::new(
+                ErrorKind::PermissionDenied,
+                "blocked by policy",
+            ))
+        };
+        assert!(!loopback_works(blocked));
+        assert_eq!(
+            select_instance_check(loopback_works(blocked)),
+            InstanceCheck::LockOnly
+        );
+    }
+
+    #[test]
+    fn current_process_is_alive() {
+        assert!(process_alive(std::process::id()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn exited_process_is_not_alive() {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        assert!(!process_alive(pid));
+        assert!(!process_alive(0));
+    }
+}
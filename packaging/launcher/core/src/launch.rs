@@ -0,0 +1,623 @@
+//! Spawning the Python app.
+
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStderr, ChildStdout, Command, Stdio};
+use std::time::Duration;
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+#[cfg(windows)]
+use crate::appcontainer::{spawn_in_app_container, ContainerProcess};
+use crate::dialog::ask_yes_no;
+use crate::entrypoint::Entrypoint;
+use crate::environment::{EnvFilter, LaunchSource, LAUNCH_SOURCE_ENV};
+use crate::heartbeat::Heartbeat;
+use crate::instance::{check_app_status, AppStatus};
+use crate::log::log_event;
+use crate::paths::write_pid_file;
+use crate::progress::{ProgressWatcher, FIRST_RUN_WINDOW_WAIT_SEC};
+use crate::splash::{show_splash, SplashAssets};
+
+/// Win32 `ERROR_ACCESS_DENIED`.
+const ERROR_ACCESS_DENIED: i32 = 5;
+/// Win32 `ERROR_ELEVATION_REQUIRED`: the target exe's manifest demands admin rights.
+const ERROR_ELEVATION_REQUIRED: i32 = 740;
+
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+const DETACHED_PROCESS: u32 = 0x00000008;
+#[cfg(windows)]
+const DETACH_WINDOW_WAIT_SEC: u64 = 10;
+/// Default wait for the app to become ready after launch (outside the
+/// first run).
+pub const WINDOW_WAIT_SEC: u64 = 30;
+const READY_POLL_MS: u64 = 100;
+
+/// Everything needed to (re)start the Python app.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppSpec {
+    pub python_exe: PathBuf,
+    pub entrypoint: Entrypoint,
+    pub working_dir: PathBuf,
+    /// Title of the window the app is expected to open.
+    pub window_title: String,
+    /// Run python.exe inside the AppContainer sandbox (Windows only).
+    pub app_container: bool,
+    /// Heartbeat file the watchdog monitors, when enabled.
+    pub heartbeat: Option<Heartbeat>,
+    /// Which inherited environment variables the app receives.
+    pub env_filter: EnvFilter,
+    /// First run: `setup_progress.json` to follow during a longer window wait.
+    pub setup_progress: Option<PathBuf>,
+    /// Pipe stderr so the watchdog can keep its tail (not in the AppContainer).
+    pub capture_stderr: bool,
+    /// Pipe stdout and stderr for `app.log` after a crash (not in the AppContainer).
+    pub capture_output: bool,
+    /// How long to wait for the app to become ready (first runs wait longer).
+    pub window_wait: Duration,
+    /// Launcher arguments it did not recognize, passed on to the app.
+    pub app_args: Vec<String>,
+    /// Splash shown while waiting for the app window: when enabled, and on
+    /// first runs to show the setup progress.
+    pub splash: Option<SplashAssets>,
+}
+
+/// A running app process, spawned normally or inside the AppContainer.
+#[derive(Debug)]
+pub enum AppProcess {
+    Child(Child),
+    #[cfg(windows)]
+    Container(ContainerProcess),
+}
+
+impl AppProcess {
+    pub fn id(&self) -> u32 {
+        match self {
+            AppProcess::Child(child) => child.id(),
+            #[cfg(windows)]
+            AppProcess::Container(process) => process.id(),
+        }
+    }
+
+    /// Exit code if the app has already exited (-1 if unknown).
+    pub fn try_wait(&mut self) -> Result<Option<i32>, String> {
+        match self {
+            AppProcess::Child(child) => child
+                .try_wait()
+                .map(|status| status.map(|status| status.code().unwrap_or(-1)))
+                .map_err(|e| format!("Failed to poll application: {}", e)),
+            #[cfg(windows)]
+            AppProcess::Container(process) => process.try_wait(),
+        }
+    }
+
+    pub fn kill(&mut self) -> Result<(), String> {
+        match self {
+            AppProcess::Child(child) => child
+                .kill()
+                .map_err(|e| format!("Failed to stop application: {}", e)),
+            #[cfg(windows)]
+            AppProcess::Container(process) => process.kill(),
+        }
+    }
+
+    /// The piped stderr, once; only for normally spawned apps.
+    pub fn take_stderr(&mut self) -> Option<ChildStderr> {
+        match self {
+            AppProcess::Child(child) => child.stderr.take(),
+            #[cfg(windows)]
+            AppProcess::Container(_) => None,
+        }
+    }
+
+    /// The piped stdout, once; only for normally spawned apps.
+    pub fn take_stdout(&mut self) -> Option<ChildStdout> {
+        match self {
+            AppProcess::Child(child) => child.stdout.take(),
+            #[cfg(windows)]
+            AppProcess::Container(_) => None,
+        }
+    }
+
+    /// Block until the app exits and return its exit code (-1 if unknown).
+    pub fn wait(&mut self) -> Result<i32, String> {
+        match self {
+            AppProcess::Child(child) => child
+                .wait()
+                .map(|status| status.code().unwrap_or(-1))
+                .map_err(|e| format!("Failed to wait for application: {}", e)),
+            #[cfg(windows)]
+            AppProcess::Container(process) => process.wait(),
+        }
+    }
+}
+
+/// Build the command that runs app.py with the launcher-provided environment.
+/// `watchdog` tells the app whether the launcher will supervise (and restart) it.
+pub fn build_app_command(app: &AppSpec, watchdog: bool) -> Command {
+    let mut command = Command::new(&app.python_exe);
+    command
+        .args(app.entrypoint.python_args())
+        .args(&app.app_args)
+        .current_dir(&app.working_dir);
+    // Removals rather than `env_clear`, so the AppContainer environment
+    // block (built from `get_envs`) is filtered the same way.
+    if app.env_filter.is_active() {
+        for (name, _) in env::vars_os() {
+            if !app.env_filter.keeps(&name.to_string_lossy()) {
+                command.env_remove(name);
+            }
+        }
+    }
+
+    if env::var("YAKULINGO_NO_AUTO_OPEN").is_err() {
+        command.env("YAKULINGO_NO_AUTO_OPEN", "1");
+    }
+    // The app must see `launcher` (it relaunches itself otherwise); an
+    // explicit source is kept except `installer`, which describes this
+    // launcher's own start.
+    let source = env::var(LAUNCH_SOURCE_ENV).ok();
+    if source.is_none() || LaunchSource::from_env(|_| source.clone()) == LaunchSource::Installer {
+        command.env(LAUNCH_SOURCE_ENV, "launcher");
+    }
+    if watchdog && env::var("YAKULINGO_WATCHDOG").is_err() {
+        command.env("YAKULINGO_WATCHDOG", "1");
+    }
+    if let Some(path) = &app.setup_progress {
+        command.env("YAKULINGO_SETUP_PROGRESS_FILE", path);
+    }
+    if let (true, Some(heartbeat)) = (watchdog, &app.heartbeat) {
+        command.env("YAKULINGO_HEARTBEAT_FILE", &heartbeat.path);
+        command.env(
+            "YAKULINGO_HEARTBEAT_INTERVAL_SEC",
+            heartbeat.interval().as_secs().to_string(),
+        );
+    }
+
+    command
+}
+
+/// The watchdog's command: `build_app_command`, with stderr (and stdout)
+/// piped when they are captured.
+fn supervised_app_command(app: &AppSpec) -> Command {
+    let mut command = build_app_command(app, true);
+    if app.app_container {
+        return command;
+    }
+    if app.capture_stderr || app.capture_output {
+        command.stderr(Stdio::piped());
+    }
+    if app.capture_output {
+        command.stdout(Stdio::piped());
+    }
+    command
+}
+
+/// Build a console-less command from a configured `[program, args...]`
+/// list; the program is relative to (and runs in) `base_dir`.
+pub fn install_dir_command(base_dir: &Path, command_line: &[String]) -> Option<Command> {
+    let (program, args) = command_line.split_first()?;
+    let mut command = Command::new(base_dir.join(program));
+    command.args(args).current_dir(base_dir);
+    #[cfg(windows)]
+    command.creation_flags(CREATE_NO_WINDOW);
+    Some(command)
+}
+
+/// Why `Command::spawn` failed, as far as the user-facing message cares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpawnFailure {
+    ElevationRequired,
+    AccessDenied,
+    Other,
+}
+
+pub fn classify_spawn_error(raw_os_error: Option<i32>, kind: io::ErrorKind) -> SpawnFailure {
+    match raw_os_error {
+        Some(ERROR_ELEVATION_REQUIRED) => SpawnFailure::ElevationRequired,
+        Some(ERROR_ACCESS_DENIED) if cfg!(windows) => SpawnFailure::AccessDenied,
+        _ if kind == io::ErrorKind::PermissionDenied => SpawnFailure::AccessDenied,
+        _ => SpawnFailure::Other,
+    }
+}
+
+pub fn spawn_error_message(failure: SpawnFailure, err: &io::Error) -> String {
+    match failure {
+        SpawnFailure::ElevationRequired => {
+            "Failed to start application: Windows requires administrator rights to run python.exe.\n\n\
+             Security software may have modified the Python runtime.\n\
+             Please run YakuLingo as administrator or reinstall the application."
+                .to_string()
+        }
+        SpawnFailure::AccessDenied => {
+            "Failed to start application: access to python.exe was denied.\n\n\
+             Security software may be blocking the Python runtime.\n\
+             Please contact your administrator."
+                .to_string()
+        }
+        SpawnFailure::Other => format!("Failed to start application: {}", err),
+    }
+}
+
+/// Why the app was not started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaunchError {
+    /// A user-facing message.
+    Failed(String),
+    /// The user agreed to restart as administrator. Callers unwind to `run`,
+    /// which starts the elevated launcher once this one's lock, PID file and
+    /// helpers are released.
+    RelaunchElevated,
+}
+
+impl From<String> for LaunchError {
+    fn from(message: String) -> Self {
+        LaunchError::Failed(message)
+    }
+}
+
+/// Spawn the app, turning OS errors into specific messages. When Windows
+/// demands elevation, offer to relaunch the launcher as administrator.
+fn spawn_app(
+    command: &mut Command,
+    app_container: bool,
+    creation_flags: u32,
+    log_path: &Option<PathBuf>,
+) -> Result<AppProcess, LaunchError> {
+    #[cfg(windows)]
+    {
+        if app_container {
+            return Ok(AppProcess::Container(spawn_in_app_container(
+                command,
+                creation_flags,
+                log_path,
+            )?));
+        }
+        command.creation_flags(creation_flags);
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = creation_flags;
+        if app_container {
+            log_event(
+                log_path,
+                "AppContainer sandbox is only available on Windows - ignoring",
+            );
+        }
+    }
+
+    let err = match command.spawn() {
+        Ok(child) => return Ok(AppProcess::Child(child)),
+        Err(err) => err,
+    };
+    let failure = classify_spawn_error(err.raw_os_error(), err.kind());
+    log_event(
+        log_path,
+        &format!("Failed to start application ({:?}): {}", failure, err),
+    );
+
+    if failure == SpawnFailure::ElevationRequired
+        && ask_yes_no(
+            "Windows requires administrator rights to start YakuLingo.\n\n\
+             Restart YakuLingo as administrator?",
+        )
+    {
+        log_event(
+            log_path,
+            "Relaunch as administrator accepted - shutting this launcher down first",
+        );
+        return Err(LaunchError::RelaunchElevated);
+    }
+
+    Err(spawn_error_message(failure, &err).into())
+}
+
+/// Launch the application; the watchdog then waits for it with
+/// [`wait_for_startup`].
+pub fn launch_app(app: &AppSpec, log_path: &Option<PathBuf>) -> Result<AppProcess, LaunchError> {
+    spawn_app(
+        &mut supervised_app_command(app),
+        app.app_container,
+        CREATE_NO_WINDOW,
+        log_path,
+    )
+}
+
+/// How the wait after a launch ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppReadiness {
+    /// `/api/setup-status` answered as YakuLingo.
+    Ready,
+    /// The window appeared before the backend answered (Windows only).
+    WindowOnly,
+    /// The app exited during the wait.
+    Exited,
+    TimedOut,
+}
+
+impl AppReadiness {
+    pub fn describe(self) -> &'static str {
+        match self {
+            AppReadiness::Ready => "app is ready",
+            AppReadiness::WindowOnly => "window appeared before the app answered",
+            AppReadiness::Exited => "app exited while starting",
+            AppReadiness::TimedOut => "timed out waiting for the app",
+        }
+    }
+}
+
+/// Call `check` every `interval` until it reports an outcome or `timeout`
+/// passes.
+pub fn poll_readiness(
+    timeout: Duration,
+    interval: Duration,
+    mut check: impl FnMut() -> Option<AppReadiness>,
+) -> AppReadiness {
+    let start = std::time::Instant::now();
+    loop {
+        if let Some(outcome) = check() {
+            return outcome;
+        }
+        if start.elapsed() >= timeout {
+            return AppReadiness::TimedOut;
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+/// Wait until the app answers the same probe used to detect a running
+/// instance. On Windows a window titled exactly `window_title` also ends
+/// the wait, so the busy cursor still clears for an app that does not
+/// serve the probe. `exited` stops the wait once the process is gone;
+/// `on_poll` runs between checks.
+pub fn wait_for_app_ready(
+    port: u16,
+    timeout: Duration,
+    window_title: &str,
+    mut exited: impl FnMut() -> bool,
+    mut on_poll: impl FnMut(),
+) -> AppReadiness {
+    poll_readiness(timeout, Duration::from_millis(READY_POLL_MS), || {
+        if check_app_status(port) == AppStatus::Running {
+            Some(AppReadiness::Ready)
+        } else if window_shown(window_title) {
+            Some(AppReadiness::WindowOnly)
+        } else if exited() {
+            Some(AppReadiness::Exited)
+        } else {
+            on_poll();
+            None
+        }
+    })
+}
+
+/// Wait for a freshly launched app. This keeps the launcher in its
+/// startup phase, which maintains the Windows busy cursor (loading
+/// circle) until the app is ready. A first run may download browsers
+/// first: it waits longer and follows the setup progress meanwhile.
+pub fn wait_for_startup(
+    app: &AppSpec,
+    port: u16,
+    child: &mut AppProcess,
+    log_path: &Option<PathBuf>,
+) -> AppReadiness {
+    let (timeout, mut progress) = match &app.setup_progress {
+        Some(path) => (
+            Duration::from_secs(FIRST_RUN_WINDOW_WAIT_SEC),
+            Some(ProgressWatcher::new(path.clone())),
+        ),
+        None => (app.window_wait, None),
+    };
+    // Closed when the wait ends: the window appeared, or the app is gone.
+    let splash = app
+        .splash
+        .as_ref()
+        .and_then(|assets| show_splash(assets, log_path));
+    wait_for_app_ready(
+        port,
+        timeout,
+        &app.window_title,
+        || !matches!(child.try_wait(), Ok(None)),
+        || {
+            if let Some(status) = progress.as_mut().and_then(ProgressWatcher::poll) {
+                let status = status.status_text();
+                log_event(log_path, &format!("Setup progress: {}", status));
+                if let Some(splash) = &splash {
+                    splash.set_caption(&status);
+                }
+            }
+        },
+    )
+}
+
+/// Whether a window with exactly this title exists.
+#[cfg(windows)]
+fn window_shown(title: &str) -> bool {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::um::winuser::FindWindowW;
+
+    let wide_title: Vec<u16> = OsStr::new(title)
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    unsafe { !FindWindowW(std::ptr::null(), wide_title.as_ptr()).is_null() }
+}
+
+#[cfg(not(windows))]
+fn window_shown(_title: &str) -> bool {
+    false
+}
+
+/// Launch the application without supervision (`--detach`).
+/// The console-less detached process outlives the launcher; only its PID is kept.
+pub fn launch_app_detached(
+    app: &AppSpec,
+    port: u16,
+    pid_path: &Option<PathBuf>,
+    log_path: &Option<PathBuf>,
+) -> Result<u32, LaunchError> {
+    let mut command = build_app_command(app, false);
+    let child = detach_app(&mut command, app.app_container, pid_path, log_path)?;
+    let pid = child.id();
+    log_event(
+        log_path,
+        &format!("Python process spawned detached (pid {})", pid),
+    );
+
+    // Keep the busy cursor briefly so the double-click still gets feedback,
+    // but do not block for the full watchdog timeout.
+    // An app that exits at once (crash, already running) ends the wait early.
+    #[cfg(windows)]
+    {
+        let mut child = child;
+        let _splash = app
+            .splash
+            .as_ref()
+            .and_then(|assets| show_splash(assets, log_path));
+        let readiness = wait_for_app_ready(
+            port,
+            Duration::from_secs(DETACH_WINDOW_WAIT_SEC),
+            &app.window_title,
+            || !matches!(child.try_wait(), Ok(None)),
+            || {},
+        );
+        log_event(
+            log_path,
+            &format!("Detached startup wait: {}", readiness.describe()),
+        );
+    }
+    #[cfg(not(windows))]
+    let _ = (port, child);
+
+    Ok(pid)
+}
+
+/// Spawn `command` and record its PID. Dropping the returned handle leaves
+/// the process running; it is only kept to notice an early exit.
+pub fn detach_app(
+    command: &mut Command,
+    app_container: bool,
+    pid_path: &Option<PathBuf>,
+    log_path: &Option<PathBuf>,
+) -> Result<AppProcess, LaunchError> {
+    let child = spawn_app(command, app_container, DETACHED_PROCESS, log_path)?;
+    write_pid_file(pid_path, child.id(), log_path);
+    Ok(child)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::fs;
+
+    #[cfg(windows)]
+    fn noop_command() -> Command {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "exit", "0"]);
+        command
+    }
+
+    #[cfg(not(windows))]
+    fn noop_command() -> Command {
+        Command::new("true")
+    }
+
+    #[test]
+    fn readiness_wait_ends_on_the_first_outcome() {
+        let mut calls = 0;
+        let outcome = poll_readiness(Duration::from_secs(5), Duration::from_millis(1), || {
+            calls += 1;
+            (calls == 3).then_some(AppReadiness::Ready)
+        });
+        assert_eq!((outcome, calls), (AppReadiness::Ready, 3));
+        let outcome = poll_readiness(Duration::from_millis(20), Duration::from_millis(5), || None);
+        assert_eq!(outcome, AppReadiness::TimedOut);
+
+        // Nothing listens on the port: only the exit check can end the wait.
+        let port = crate::instance::free_loopback_port().unwrap();
+        let polls = std::cell::Cell::new(0);
+        let outcome = wait_for_app_ready(
+            port,
+            Duration::from_secs(5),
+            "YakuLingo (readiness test)",
+            || polls.get() == 2,
+            || polls.set(polls.get() + 1),
+        );
+        assert_eq!((outcome, polls.get()), (AppReadiness::Exited, 2));
+    }
+
+    #[test]
+    fn elevation_required_maps_to_specific_message() {
+        let err = io::Error::from_raw_os_error(ERROR_ELEVATION_REQUIRED);
+        let failure = classify_spawn_error(err.raw_os_error(), err.kind());
+        assert_eq!(failure, SpawnFailure::ElevationRequired);
+        assert!(spawn_error_message(failure, &err).contains("administrator rights"));
+    }
+
+    #[test]
+    fn access_denied_is_distinct_from_elevation() {
+        let failure = classify_spawn_error(None, io::ErrorKind::PermissionDenied);
+        assert_eq!(failure, SpawnFailure::AccessDenied);
+        let message =
+            spawn_error_message(failure, &io::Error::from(io::ErrorKind::PermissionDenied));
+        assert!(message.contains("access to python.exe was denied"));
+        assert!(!message.contains("administrator rights"));
+    }
+
+    #[test]
+    fn other_errors_keep_generic_message() {
+        let err = io::Error::from(io::ErrorKind::NotFound);
+        let failure = classify_spawn_error(None, err.kind());
+        assert_eq!(failure, SpawnFailure::Other);
+        assert!(spawn_error_message(failure, &err).starts_with("Failed to start application: "));
+    }
+
+    #[test]
+    fn first_run_tells_the_app_where_to_report_progress() {
+        let mut app = AppSpec {
+            python_exe: PathBuf::from("python.exe"),
+            entrypoint: Entrypoint::Script(PathBuf::from("app.py")),
+            working_dir: PathBuf::from("."),
+            window_title: "YakuLingo".to_string(),
+            app_container: false,
+            heartbeat: None,
+            env_filter: EnvFilter::default(),
+            setup_progress: None,
+            capture_stderr: false,
+            capture_output: false,
+            window_wait: Duration::from_secs(30),
+            app_args: Vec::new(),
+            splash: None,
+        };
+        let progress_env = |app: &AppSpec| {
+            build_app_command(app, true)
+                .get_envs()
+                .find(|(name, _)| *name == "YAKULINGO_SETUP_PROGRESS_FILE")
+                .and_then(|(_, value)| value.map(PathBuf::from))
+        };
+        assert_eq!(progress_env(&app), None);
+        app.setup_progress = Some(PathBuf::from("state/setup_progress.json"));
+        assert_eq!(
+            progress_env(&app),
+            Some(PathBuf::from("state/setup_progress.json"))
+        );
+    }
+
+    #[test]
+    fn detach_writes_pid_file() {
+        let dir = temp_dir("detach");
+        let pid_path = Some(dir.join(".yakulingo").join("launcher.pid"));
+
+        let pid = detach_app(&mut noop_command(), false, &pid_path, &None)
+            .unwrap()
+            .id();
+
+        let content = fs::read_to_string(pid_path.as_ref().unwrap()).unwrap();
+        assert_eq!(content.trim(), pid.to_string());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,144 @@
+//! Launcher-level single-instance lock.
+//!
+//! The app's own mutex only exists once Python is running, and the port
+//! probe only answers once it serves, so two launchers started together
+//! could both decide to launch. The first launcher therefore takes this
+//! lock at once and holds it for its lifetime: a named mutex on Windows,
+//! an exclusive lock on `launcher[-<name>].lock` elsewhere. A launcher
+//! that finds it taken goes straight to the focus path.
+
+#[cfg(not(windows))]
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+
+use crate::instance::InstanceScope;
+use crate::paths::get_state_dir;
+
+const LAUNCHER_MUTEX_BASE_NAME: &str = "YakuLingo_Launcher";
+
+/// `Global\YakuLingo_Launcher[-<name>]`, in the instance mutex's namespace.
+pub fn launcher_mutex_name(instance: &InstanceScope) -> String {
+    let namespace = instance
+        .mutex_name
+        .split_once('\\')
+        .map_or("Global", |(namespace, _)| namespace);
+    match &instance.name {
+        Some(name) => format!("{}\\{}-{}", namespace, LAUNCHER_MUTEX_BASE_NAME, name),
+        None => format!("{}\\{}", namespace, LAUNCHER_MUTEX_BASE_NAME),
+    }
+}
+
+/// Lock file used instead of the mutex outside Windows.
+pub fn launcher_lock_path(base_dir: &Path, instance_name: Option<&str>) -> PathBuf {
+    let file_name = match instance_name {
+        Some(name) => format!("launcher-{}.lock", name),
+        None => "launcher.lock".to_string(),
+    };
+    get_state_dir(base_dir).join(file_name)
+}
+
+/// Held for the launcher's lifetime; released on drop (or process exit).
+#[derive(Debug)]
+pub struct LauncherLock {
+    #[cfg(windows)]
+    handle: winapi::um::winnt::HANDLE,
+    #[cfg(not(windows))]
+    _file: File,
+}
+
+impl LauncherLock {
+    /// `Ok(None)` when another launcher holds the lock; an error means the
+    /// lock could not be checked at all.
+    #[cfg(windows)]
+    pub fn acquire(mutex_name: &str, _lock_path: &Path) -> Result<Option<Self>, String> {
+        use std::ffi::OsStr;
+        use std::os::windows::ffi::OsStrExt;
+        use std::ptr::null_mut;
+        use winapi::shared::minwindef::TRUE;
+        use winapi::shared::winerror::ERROR_ALREADY_EXISTS;
+        use winapi::um::errhandlingapi::GetLastError;
+        use winapi::um::handleapi::CloseHandle;
+        use winapi::um::synchapi::CreateMutexW;
+
+        let wide_name: Vec<u16> = OsStr::new(mutex_name)
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect();
+        unsafe {
+            let handle = CreateMutexW(null_mut(), TRUE, wide_name.as_ptr());
+            if handle.is_null() {
+                return Err(format!(
+                    "CreateMutexW({}) failed (error {})",
+                    mutex_name,
+                    GetLastError()
+                ));
+            }
+            if GetLastError() == ERROR_ALREADY_EXISTS {
+                CloseHandle(handle);
+                return Ok(None);
+            }
+            Ok(Some(LauncherLock { handle }))
+        }
+    }
+
+    #[cfg(not(windows))]
+    pub fn acquire(_mutex_name: &str, lock_path: &Path) -> Result<Option<Self>, String> {
+        if let Some(parent) = lock_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(lock_path)
+            .map_err(|e| format!("Failed to open {:?}: {}", lock_path, e))?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(LauncherLock { _file: file })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(err)) => {
+                Err(format!("Failed to lock {:?}: {}", lock_path, err))
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for LauncherLock {
+    fn drop(&mut self) {
+        use winapi::um::handleapi::CloseHandle;
+        use winapi::um::synchapi::ReleaseMutex;
+
+        unsafe {
+            ReleaseMutex(self.handle);
+            CloseHandle(self.handle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SingletonScope;
+
+    #[test]
+    fn mutex_name_follows_scope_and_instance() {
+        let machine = InstanceScope::for_session(SingletonScope::PerMachine, 8765, None, 1);
+        assert_eq!(launcher_mutex_name(&machine), "Global\\YakuLingo_Launcher");
+        let user = InstanceScope::for_session(SingletonScope::PerUser, 8765, Some("work"), 1);
+        assert_eq!(launcher_mutex_name(&user), "Local\\YakuLingo_Launcher-work");
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn second_launcher_finds_the_lock_taken() {
+        let dir = crate::test_util::temp_dir("launcher-lock");
+        let path = dir.join("launcher.lock");
+        let first = LauncherLock::acquire("unused", &path).unwrap();
+        assert!(first.is_some());
+        assert!(LauncherLock::acquire("unused", &path).unwrap().is_none());
+        drop(first);
+        assert!(LauncherLock::acquire("unused", &path).unwrap().is_some());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
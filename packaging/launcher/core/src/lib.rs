@@ -0,0 +1,1037 @@
+//! Shared launcher logic for YakuLingo.
+//!
+//! The `YakuLingo.exe` binary is a thin wrapper around [`run`]; everything
+//! that decides how the Python app is found, prepared and started lives here
+//! so launcher variants cannot drift apart again.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub mod app_output;
+pub mod appcontainer;
+pub mod arch;
+pub mod backend;
+pub mod cli;
+pub mod config;
+pub mod crash;
+pub mod dialog;
+pub mod elevation;
+pub mod entrypoint;
+pub mod environment;
+pub mod events;
+pub mod fingerprint;
+pub mod heartbeat;
+pub mod instance;
+pub mod launch;
+pub mod launcher_lock;
+pub mod log;
+pub mod paths;
+pub mod progress;
+pub mod python;
+pub mod registry;
+pub mod runtime;
+pub mod schedule;
+pub mod shutdown;
+pub mod snapshot;
+pub mod splash;
+pub mod state;
+pub mod status;
+pub mod stderr_tail;
+pub mod supervisor;
+pub mod tail;
+pub mod transfer;
+pub mod update;
+pub mod verify;
+pub mod watchdog;
+pub mod watcher;
+
+use crate::arch::{check_state_arch, launcher_arch, ArchCheck};
+use crate::cli::LaunchOptions;
+use crate::config::{load_config, user_config_path, LauncherConfig, CONFIG_FILE_NAME};
+use crate::dialog::{set_quiet, show_info};
+use crate::elevation::relaunch_elevated;
+use crate::entrypoint::resolve_entrypoint;
+use crate::environment::{
+    env_token, environment_prepared, mark_environment_prepared, setup_environment,
+    setup_instance_environment, EnvFilter, LaunchSource,
+};
+use crate::events::{InstallerEvents, StoppedGuard};
+use crate::fingerprint::machine_fingerprint;
+use crate::heartbeat::Heartbeat;
+use crate::instance::{
+    check_app_status, current_session_id, loopback_works, probe_connect, probe_instance,
+    read_reported_port, record_reported_port, select_instance_check, InstanceCheck, InstanceProbe,
+    InstanceScope, LaunchDecision, SystemProbe,
+};
+use crate::launch::{launch_app_detached, AppSpec, LaunchError, WINDOW_WAIT_SEC};
+use crate::launcher_lock::{launcher_lock_path, launcher_mutex_name, LauncherLock};
+use crate::log::{
+    breadcrumb, init_log_path, log_event, rotate_log, set_log_rotation, set_verbose, DebugOutput,
+    LogRotation,
+};
+use crate::paths::{
+    get_heartbeat_path, get_launcher_context_path, get_pid_file_path, get_state_dir, launcher_dir,
+    launcher_exe, prepare_data_dir, read_pid_file,
+};
+use crate::progress::get_setup_progress_path;
+use crate::python::{
+    check_venv_python, find_python_dir, fix_pyvenv_cfg, query_python_info, resolve_python_info,
+};
+use crate::registry::{check_launch_policy, publish_to_registry, read_policy_enabled};
+use crate::runtime::verify_runtime;
+use crate::schedule::MaintenanceWindow;
+use crate::shutdown::install_shutdown_handler;
+use crate::splash::resolve_splash_assets;
+use crate::status::{LauncherPhase, StatusFile, StoppedStatus};
+use crate::tail::{attach_console, spawn_tailer};
+use crate::update::apply_deferred_update;
+use crate::verify::{print_report, self_check, CHECK_FAILED_EXIT_CODE};
+use crate::watchdog::{supervise, RestartLimits, SuperviseExit, WatchdogPolicy};
+
+pub const APP_PORT: u16 = 8765;
+pub const USER_EXIT_CODE: i32 = 10;
+pub const INSTANCE_ALREADY_RUNNING_CODE: i32 = 11;
+pub const UPDATE_IN_PROGRESS_CODE: i32 = 20;
+/// Pause between loopback self-test attempts for installer launches.
+const INSTALLER_PROBE_RETRY_MS: u64 = 500;
+
+/// How the launcher relates to the app process after spawning it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchMode {
+    /// Stay alive and restart the app on unexpected exits.
+    Watchdog,
+    /// Spawn detached, record the PID and exit immediately.
+    Detach,
+}
+
+/// How a launch ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LaunchEnd {
+    Done,
+    /// Start an elevated copy of the launcher; see [`LaunchError::RelaunchElevated`].
+    RelaunchElevated,
+}
+
+pub fn run(options: &LaunchOptions) -> Result<(), String> {
+    match start(options)? {
+        LaunchEnd::Done => Ok(()),
+        // Every guard of this launcher (lock, PID file, helpers, status) is
+        // released by now, so the elevated copy starts from a clean slate.
+        LaunchEnd::RelaunchElevated => relaunch_elevated(),
+    }
+}
+
+fn start(options: &LaunchOptions) -> Result<LaunchEnd, String> {
+    let started = Instant::now();
+    // Before anything can fail: proof the launcher ran, even without a log.
+    breadcrumb(&mut DebugOutput, "started");
+
+    // Resolve (and cache) the executable before any update can replace it;
+    // everything below uses this base dir.
+    let exe_path = launcher_exe()?;
+    let base_dir = exe_path
+        .parent()
+        .ok_or("Failed to get executable directory")?
+        .to_path_buf();
+
+    // Validate the data dir first: the log itself lives under it.
+    let data_dir = prepare_data_dir()?;
+    let log_path = init_log_path(&base_dir);
+    if log_path.is_none() {
+        breadcrumb(
+            &mut DebugOutput,
+            "no writable log location - file logging disabled",
+        );
+    }
+    // Follows the log from here on; stopped (after a final flush) when `run`
+    // returns or a console event ends the launcher.
+    let tailer = match &log_path {
+        Some(path) if options.foreground && attach_console() => Some(spawn_tailer(path)),
+        _ => None,
+    };
+    if let Some(tailer) = &tailer {
+        tailer.stop_on_shutdown();
+        install_shutdown_handler(&log_path);
+    }
+    // The tailer already shows every line; otherwise echo them directly.
+    if options.verbose && tailer.is_none() && attach_console() {
+        set_verbose();
+    }
+    log_event(
+        &log_path,
+        &format!(
+            "Launcher start (exe: {:?}, base: {:?}, mode: {:?}, instance: {:?})",
+            exe_path, base_dir, options.mode, options.instance_name
+        ),
+    );
+    if let Some(dir) = &data_dir {
+        log_event(&log_path, &format!("Data dir override: {:?}", dir));
+    }
+
+    let launch_source = LaunchSource::from_env(|name| env::var(name).ok());
+    if launch_source == LaunchSource::Installer {
+        log_event(
+            &log_path,
+            "Launched by installer - full discovery, update check skipped",
+        );
+    }
+    let config = load_launch_config(&base_dir, options, &log_path);
+    let rotation = log_rotation(&config);
+    set_log_rotation(rotation);
+    if let Some(path) = &log_path {
+        match rotate_log(path, &rotation) {
+            Ok(true) => log_event(&log_path, "Rotated launcher.log"),
+            Ok(false) => {}
+            Err(err) => log_event(&log_path, &format!("Log rotation failed: {}", err)),
+        }
+    }
+    if options.kiosk {
+        log_event(&log_path, &format!("Kiosk profile applied: {:?}", config));
+    }
+    if config.quiet_dialogs {
+        set_quiet(&log_path);
+    }
+    if let Err(err) = check_launch_policy(read_policy_enabled()) {
+        log_event(&log_path, "Launch refused: Enabled=0 under HKLM policy key");
+        return Err(err);
+    }
+    if config.machine_fingerprint {
+        match machine_fingerprint() {
+            Some(fingerprint) => {
+                log_event(&log_path, &format!("Machine fingerprint: {}", fingerprint))
+            }
+            None => log_event(&log_path, "Machine fingerprint unavailable"),
+        }
+    }
+    let mut instance = InstanceScope::resolve(
+        config.singleton_scope,
+        config.app_port.unwrap_or(APP_PORT),
+        options.instance_name.as_deref(),
+    );
+    instance.reported_port =
+        read_reported_port(&get_launcher_context_path(&base_dir), instance.port);
+    if let Some(port) = instance.reported_port {
+        log_event(
+            &log_path,
+            &format!(
+                "App reported port {} (requested {}) - probing it first",
+                port, instance.port
+            ),
+        );
+    }
+    log_event(
+        &log_path,
+        &format!(
+            "Singleton scope: {:?} (mutex: {}, port: {})",
+            instance.scope, instance.mutex_name, instance.port
+        ),
+    );
+
+    // Check if already running
+    let allow_multi_instance = env::var("YAKULINGO_ALLOW_MULTI_INSTANCE")
+        .map(|value| value == "1")
+        .unwrap_or(false);
+    // Installer launches retry, as firewall rules may still be settling.
+    let loopback_ok = (0..launch_source.probe_attempts()).any(|attempt| {
+        if attempt > 0 {
+            thread::sleep(Duration::from_millis(INSTALLER_PROBE_RETRY_MS));
+        }
+        loopback_works(probe_connect)
+    });
+    let check = select_instance_check(loopback_ok);
+    if check == InstanceCheck::LockOnly {
+        log_event(
+            &log_path,
+            "WARNING: loopback TCP is blocked on this machine - detecting running instances by mutex/PID file only",
+        );
+    }
+    // Held until this launcher exits, watchdog included.
+    let (_launcher_lock, launcher_running) = if allow_multi_instance {
+        (None, false)
+    } else {
+        match LauncherLock::acquire(
+            &launcher_mutex_name(&instance),
+            &launcher_lock_path(&base_dir, instance.name.as_deref()),
+        ) {
+            Ok(Some(lock)) => (Some(lock), false),
+            Ok(None) => {
+                log_event(&log_path, "Another launcher holds the launcher lock");
+                (None, true)
+            }
+            Err(err) => {
+                log_event(&log_path, &format!("Launcher lock unavailable: {}", err));
+                (None, false)
+            }
+        }
+    };
+    let probe = SystemProbe {
+        launcher_running,
+        tracked_pid: read_pid_file(&get_pid_file_path(&base_dir, instance.name.as_deref())),
+        port_fallback: config.port_fallback,
+        forward_args: options.forward_args.clone(),
+    };
+    let context_path = get_launcher_context_path(&base_dir);
+    focus_or_launch(
+        &probe,
+        &instance,
+        allow_multi_instance,
+        check,
+        started,
+        &log_path,
+        |target| {
+            if target.port != instance.port {
+                // The launcher's PID: the watchdog lives as long as the app,
+                // and a detached app is still found by its mutex.
+                if let Err(err) =
+                    record_reported_port(&context_path, instance.port, target.port, process::id())
+                {
+                    log_event(
+                        &log_path,
+                        &format!("Failed to record fallback port: {}", err),
+                    );
+                }
+            }
+            launch(
+                options,
+                &base_dir,
+                &config,
+                target,
+                allow_multi_instance,
+                &log_path,
+            )
+        },
+    )
+}
+
+/// Hand off to an already-running instance, or run `launch` (Python
+/// discovery onward) when there is none. The running case touches neither
+/// the venv nor the registry so relaunch-to-focus stays fast.
+fn focus_or_launch<P: InstanceProbe>(
+    probe: &P,
+    instance: &InstanceScope,
+    allow_multi_instance: bool,
+    check: InstanceCheck,
+    started: Instant,
+    log_path: &Option<PathBuf>,
+    launch: impl FnOnce(&InstanceScope) -> Result<LaunchEnd, String>,
+) -> Result<LaunchEnd, String> {
+    match probe_instance(probe, instance, allow_multi_instance, check) {
+        LaunchDecision::FocusExisting => {
+            match probe.forward_args(instance.app_port()) {
+                Some(Ok(())) => log_event(log_path, "Forwarded arguments to the running instance"),
+                Some(Err(err)) => log_event(
+                    log_path,
+                    &format!("Forwarding arguments failed ({}) - focusing only", err),
+                ),
+                None => {}
+            }
+            let focused =
+                probe.focus_window(&instance.window_title) || probe.activate(instance.app_port());
+            log_event(
+                log_path,
+                &format!(
+                    "Application already running - focus {} in {}ms",
+                    if focused { "handed off" } else { "failed" },
+                    started.elapsed().as_millis()
+                ),
+            );
+            if !focused {
+                show_info("YakuLingo is already running.");
+            }
+            Ok(LaunchEnd::Done)
+        }
+        LaunchDecision::PortConflict => {
+            if let Some(port) = probe.fallback_port() {
+                log_event(
+                    log_path,
+                    &format!(
+                        "Port {} is in use by another application - starting on port {}",
+                        instance.port, port
+                    ),
+                );
+                return launch(&InstanceScope {
+                    port,
+                    reported_port: None,
+                    ..instance.clone()
+                });
+            }
+            log_event(
+                log_path,
+                &format!("Port {} is in use by another application", instance.port),
+            );
+            Err(format!(
+                "Port {} is already in use.\n\nPlease close the other application and try again.",
+                instance.port
+            ))
+        }
+        LaunchDecision::Launch => launch(instance),
+    }
+}
+
+/// Prepare the venv and environment, then start the app in `options.mode`.
+fn launch(
+    options: &LaunchOptions,
+    base_dir: &Path,
+    config: &LauncherConfig,
+    instance: &InstanceScope,
+    allow_multi_instance: bool,
+    log_path: &Option<PathBuf>,
+) -> Result<LaunchEnd, String> {
+    // Only the launcher that starts the app gets this far, so the status
+    // file is never written by a launcher that is handing off.
+    let status = StatusFile::for_instance(base_dir, instance.name.as_deref(), log_path);
+    status.write(LauncherPhase::Starting, None, 0);
+    let _stopped_status = StoppedStatus(&status);
+    let state_dir = get_state_dir(base_dir);
+    publish_to_registry(config.publish_locations, base_dir, &state_dir, log_path);
+    // A fresh state dir means the app's first-run setup is still ahead.
+    let first_run = check_state_arch(
+        &state_dir,
+        launcher_arch(),
+        config.clear_arch_caches,
+        log_path,
+    ) == ArchCheck::FirstRun;
+    if first_run {
+        // Left over from an interrupted first run.
+        let _ = std::fs::remove_file(get_setup_progress_path(base_dir));
+    }
+    // Before anything is started from the install dir, so the updater can
+    // replace files freely.
+    if LaunchSource::from_env(|name| env::var(name).ok()).checks_updates() {
+        apply_deferred_update(base_dir, &config.updater, log_path);
+    }
+
+    // Check venv exists
+    let venv_dir = base_dir.join(".venv");
+    // Use python.exe (not pythonw.exe) for better subprocess compatibility
+    // Console is hidden via CREATE_NO_WINDOW flag
+    let python_exe = venv_dir.join("Scripts").join("python.exe");
+
+    if let Err(err) = check_venv_python(&python_exe, log_path) {
+        log_event(log_path, ".venv not found - aborting");
+        return Err(err);
+    }
+
+    prepare_environment(
+        base_dir,
+        &venv_dir,
+        &python_exe,
+        config,
+        |name| env::var(name).ok(),
+        log_path,
+    )?;
+    setup_instance_environment(base_dir, instance);
+    log_event(log_path, "Environment variables configured");
+
+    let pid_path = get_pid_file_path(base_dir, instance.name.as_deref());
+    let mut config = config.clone();
+    let events = InstallerEvents::open(instance.name.as_deref(), log_path);
+    // Installers wait on the Stopped event; it is released on every exit
+    // from the watchdog, errors included, but not across config reloads.
+    let _stopped = (options.mode == LaunchMode::Watchdog).then(|| {
+        events.begin();
+        StoppedGuard(&events)
+    });
+    // Ctrl+C or closing the console must not leave the app running.
+    if options.mode == LaunchMode::Watchdog {
+        install_shutdown_handler(log_path);
+    }
+
+    loop {
+        let app = app_spec(
+            &python_exe,
+            base_dir,
+            &config,
+            instance,
+            first_run,
+            &options.forward_args,
+            log_path,
+        )?;
+        match options.mode {
+            // Detached launch: record the PID and exit without supervising
+            LaunchMode::Detach => {
+                match launch_app_detached(&app, instance.port, &pid_path, log_path) {
+                    Ok(_) => {}
+                    Err(LaunchError::Failed(err)) => return Err(err),
+                    Err(LaunchError::RelaunchElevated) => return Ok(LaunchEnd::RelaunchElevated),
+                }
+                log_event(log_path, "Detach mode - launcher exiting without watchdog");
+                return Ok(LaunchEnd::Done);
+            }
+            // Launch application and keep a watchdog loop
+            LaunchMode::Watchdog => {
+                let policy = watchdog_policy(
+                    base_dir,
+                    &config,
+                    allow_multi_instance,
+                    options.no_restart,
+                    log_path,
+                );
+                match supervise(
+                    &app, base_dir, &pid_path, log_path, instance, &policy, &events,
+                )? {
+                    SuperviseExit::Stopped => return Ok(LaunchEnd::Done),
+                    SuperviseExit::RelaunchElevated => return Ok(LaunchEnd::RelaunchElevated),
+                    // Pick up launcher.toml edits for the next run of the app.
+                    SuperviseExit::ConfigChanged => {
+                        config = load_launch_config(base_dir, options, log_path)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Find Python, verify the runtime, patch pyvenv.cfg and set up the app's
+/// environment, unless a parent launcher already did. Only launchers
+/// started from the app can skip this: an elevated relaunch (UAC) starts
+/// from a fresh environment, so it prepares everything again.
+fn prepare_environment(
+    base_dir: &Path,
+    venv_dir: &Path,
+    python_exe: &Path,
+    config: &LauncherConfig,
+    var: impl Fn(&str) -> Option<String>,
+    log_path: &Option<PathBuf>,
+) -> Result<(), String> {
+    let env_token = env_token(base_dir, current_session_id());
+    if environment_prepared(&var, &env_token) {
+        log_event(
+            log_path,
+            "Environment prepared by parent launcher - skipping Python setup",
+        );
+        return Ok(());
+    }
+    // Find Python directory in .uv-python (or pyvenv.cfg home)
+    let python_dir = find_python_dir(
+        base_dir,
+        venv_dir,
+        config.python_version.as_deref(),
+        log_path,
+    )?;
+    log_event(log_path, &format!("Using Python dir: {:?}", python_dir));
+
+    // A broken install would only crash-loop the watchdog.
+    if let Err(err) = verify_runtime(base_dir, &python_dir, venv_dir, config) {
+        log_event(log_path, &format!("Runtime verification failed: {}", err));
+        return Err(err);
+    }
+
+    // Fix pyvenv.cfg for portability
+    fix_pyvenv_cfg(venv_dir, &python_dir)?;
+    log_event(log_path, "pyvenv.cfg patched");
+
+    // Setup environment variables
+    setup_environment(base_dir, venv_dir, &python_dir, log_path);
+    mark_environment_prepared(&env_token);
+
+    match resolve_python_info(
+        python_exe,
+        &get_launcher_context_path(base_dir),
+        LaunchSource::from_env(&var).uses_caches(),
+        query_python_info,
+        log_path,
+    ) {
+        Some(info) => log_event(
+            log_path,
+            &format!(
+                "Python {} ({}) at {}",
+                info.version, info.arch, info.executable
+            ),
+        ),
+        None => log_event(log_path, "Python version query failed"),
+    }
+    Ok(())
+}
+
+/// What to start, from the current configuration.
+fn app_spec(
+    python_exe: &Path,
+    base_dir: &Path,
+    config: &LauncherConfig,
+    instance: &InstanceScope,
+    first_run: bool,
+    app_args: &[String],
+    log_path: &Option<PathBuf>,
+) -> Result<AppSpec, String> {
+    let entrypoint = resolve_entrypoint(
+        base_dir,
+        config.entrypoint.as_deref(),
+        config.entry_module.as_deref(),
+    )
+    .inspect_err(|e| log_event(log_path, &format!("Entrypoint resolution failed: {}", e)))?;
+    log_event(log_path, &format!("Entrypoint: {:?}", entrypoint));
+    // Capturing output is the default; only warn when it was asked for.
+    if (config.capture_stderr_tail || config.capture_app_output == Some(true))
+        && config.app_container
+        && cfg!(windows)
+    {
+        log_event(
+            log_path,
+            "Output capture is not available in the AppContainer - ignoring",
+        );
+    }
+
+    Ok(AppSpec {
+        python_exe: python_exe.to_path_buf(),
+        entrypoint,
+        working_dir: base_dir.to_path_buf(),
+        window_title: instance.window_title.clone(),
+        app_container: config.app_container,
+        heartbeat: (config.heartbeat_timeout_sec > 0).then(|| Heartbeat {
+            path: get_heartbeat_path(base_dir, instance.name.as_deref()),
+            timeout: Duration::from_secs(config.heartbeat_timeout_sec),
+        }),
+        env_filter: EnvFilter {
+            allow: config.env_allowlist.clone(),
+            deny: config.env_denylist.clone(),
+        },
+        setup_progress: first_run.then(|| get_setup_progress_path(base_dir)),
+        capture_stderr: config.capture_stderr_tail,
+        capture_output: config.capture_app_output.unwrap_or(true),
+        window_wait: Duration::from_secs(config.window_wait_sec.unwrap_or(WINDOW_WAIT_SEC)),
+        app_args: app_args.to_vec(),
+        splash: (config.splash || first_run).then(|| {
+            resolve_splash_assets(
+                base_dir,
+                config.splash_image.as_deref(),
+                config.splash_caption.as_deref(),
+            )
+        }),
+    })
+}
+
+/// `--check`: validate what a launch would use (Python, venv, pyvenv.cfg,
+/// port, log) without starting anything, print the JSON report and return
+/// the process exit code.
+pub fn run_check(options: &LaunchOptions) -> i32 {
+    attach_console();
+    let base_dir = match launcher_dir() {
+        Ok(dir) => dir,
+        Err(err) => {
+            eprintln!("{}", err);
+            return CHECK_FAILED_EXIT_CODE;
+        }
+    };
+    // An invalid data dir leaves no log location, which the report shows.
+    let log_path = prepare_data_dir()
+        .ok()
+        .and_then(|_| init_log_path(&base_dir));
+    log_event(
+        &log_path,
+        &format!("Self-check start (base: {:?})", base_dir),
+    );
+    let config = load_launch_config(&base_dir, options, &log_path);
+    let instance = InstanceScope::resolve(
+        config.singleton_scope,
+        config.app_port.unwrap_or(APP_PORT),
+        options.instance_name.as_deref(),
+    );
+    let report = self_check(&base_dir, &log_path, instance.port, check_app_status);
+    log_event(
+        &log_path,
+        &format!("Self-check {}", if report.ok { "passed" } else { "failed" }),
+    );
+    print_report(&report, CHECK_FAILED_EXIT_CODE)
+}
+
+/// The layered config with this run's command-line overrides on top.
+fn load_launch_config(
+    base_dir: &Path,
+    options: &LaunchOptions,
+    log_path: &Option<PathBuf>,
+) -> LauncherConfig {
+    let mut config = load_config(base_dir, options.kiosk, log_path);
+    if let Some(port) = options.port {
+        log_event(log_path, &format!("--port {} overrides app_port", port));
+        config.app_port = Some(port);
+    }
+    config
+}
+
+fn log_rotation(config: &LauncherConfig) -> LogRotation {
+    let defaults = LogRotation::default();
+    LogRotation {
+        max_bytes: config.log_rotate_bytes.unwrap_or(defaults.max_bytes),
+        keep: config.log_keep_rotated.unwrap_or(defaults.keep),
+        compress: config.compress_rotated_logs,
+    }
+}
+
+fn restart_limits(config: &LauncherConfig) -> RestartLimits {
+    let defaults = RestartLimits::default();
+    RestartLimits {
+        max_restarts: config.max_restarts.unwrap_or(defaults.max_restarts),
+        backoff_base: config
+            .restart_backoff_base_sec
+            .map_or(defaults.backoff_base, Duration::from_secs),
+        backoff_max: config
+            .restart_backoff_max_sec
+            .map_or(defaults.backoff_max, Duration::from_secs),
+        reset_after: config
+            .restart_reset_after_sec
+            .map_or(defaults.reset_after, Duration::from_secs),
+        give_up_after: (config.restart_give_up_after_sec > 0)
+            .then(|| Duration::from_secs(config.restart_give_up_after_sec)),
+    }
+}
+
+fn watchdog_policy(
+    base_dir: &Path,
+    config: &LauncherConfig,
+    allow_multi_instance: bool,
+    no_restart: bool,
+    log_path: &Option<PathBuf>,
+) -> WatchdogPolicy {
+    let mut watch_files = Vec::new();
+    if config.watch_config {
+        watch_files.push(base_dir.join(CONFIG_FILE_NAME));
+        watch_files.extend(user_config_path());
+        if let Some(file) = &config.app_config_file {
+            watch_files.push(base_dir.join(file));
+        }
+        log_event(
+            log_path,
+            &format!("Watching config files: {:?}", watch_files),
+        );
+    }
+    WatchdogPolicy {
+        allow_multi_instance,
+        restart_limits: restart_limits(config),
+        restart_always: config.restart_always,
+        crash_snapshots: config.crash_snapshots,
+        restart_cooldown: (config.restart_cooldown_sec > 0)
+            .then(|| Duration::from_secs(config.restart_cooldown_sec)),
+        maintenance_window: maintenance_window(config, log_path),
+        watch_files,
+        helpers: config.helpers.clone(),
+        no_restart,
+    }
+}
+
+fn maintenance_window(
+    config: &LauncherConfig,
+    log_path: &Option<PathBuf>,
+) -> Option<MaintenanceWindow> {
+    let value = config.maintenance_window.as_deref()?;
+    let window = MaintenanceWindow::parse(value);
+    match &window {
+        Some(window) => log_event(log_path, &format!("Maintenance window: {}", window)),
+        None => log_event(
+            log_path,
+            &format!("Ignoring invalid maintenance_window: {:?}", value),
+        ),
+    }
+    window
+}
+
+#[cfg(test)]
+pub(crate) mod test_util {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// Fresh per-process scratch directory under the system temp dir.
+    pub fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!(
+            "yakulingo-launcher-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SingletonScope;
+    use crate::environment::{ENV_PREPARED_ENV, ENV_TOKEN_ENV};
+    use crate::instance::AppStatus;
+    use crate::test_util::temp_dir;
+    use std::cell::Cell;
+    use std::fs;
+
+    struct FakeProbe {
+        mutex_present: bool,
+        app_status: AppStatus,
+        /// The app answers on this port, whatever `app_status` says.
+        running_on: Option<u16>,
+        lock_held: bool,
+        /// `port_fallback` enabled with this free port.
+        fallback_port: Option<u16>,
+        /// What forwarding arguments returns; `None`: nothing to forward.
+        forward_result: Option<Result<(), String>>,
+        forward_ports: std::cell::RefCell<Vec<u16>>,
+        status_probes: Cell<u32>,
+        focus_calls: Cell<u32>,
+    }
+
+    impl FakeProbe {
+        fn new(mutex_present: bool, app_status: AppStatus) -> Self {
+            FakeProbe {
+                mutex_present,
+                app_status,
+                running_on: None,
+                lock_held: false,
+                fallback_port: None,
+                forward_result: None,
+                forward_ports: Default::default(),
+                status_probes: Cell::new(0),
+                focus_calls: Cell::new(0),
+            }
+        }
+    }
+
+    impl InstanceProbe for FakeProbe {
+        fn mutex_present(&self, _mutex_name: &str) -> bool {
+            self.mutex_present
+        }
+
+        fn lock_held(&self) -> bool {
+            self.lock_held
+        }
+
+        fn app_status(&self, port: u16) -> AppStatus {
+            self.status_probes.set(self.status_probes.get() + 1);
+            if self.running_on == Some(port) {
+                return AppStatus::Running;
+            }
+            self.app_status
+        }
+
+        fn focus_window(&self, _window_title: &str) -> bool {
+            self.focus_calls.set(self.focus_calls.get() + 1);
+            true
+        }
+
+        fn activate(&self, _port: u16) -> bool {
+            true
+        }
+
+        fn forward_args(&self, port: u16) -> Option<Result<(), String>> {
+            self.forward_ports.borrow_mut().push(port);
+            self.forward_result.clone()
+        }
+
+        fn fallback_port(&self) -> Option<u16> {
+            self.fallback_port
+        }
+    }
+
+    fn run_probe(probe: &FakeProbe) -> (Result<LaunchEnd, String>, bool) {
+        run_probe_with(probe, InstanceCheck::PortProbe)
+    }
+
+    fn run_probe_with(
+        probe: &FakeProbe,
+        check: InstanceCheck,
+    ) -> (Result<LaunchEnd, String>, bool) {
+        let instance = InstanceScope::resolve(SingletonScope::PerMachine, APP_PORT, None);
+        run_probe_on(probe, &instance, check)
+    }
+
+    fn run_probe_on(
+        probe: &FakeProbe,
+        instance: &InstanceScope,
+        check: InstanceCheck,
+    ) -> (Result<LaunchEnd, String>, bool) {
+        let launched = Cell::new(false);
+        let result = focus_or_launch(probe, instance, false, check, Instant::now(), &None, |_| {
+            launched.set(true);
+            Ok(LaunchEnd::Done)
+        });
+        (result, launched.get())
+    }
+
+    #[test]
+    fn running_instance_is_focused_without_python_discovery() {
+        let probe = FakeProbe::new(true, AppStatus::NotRunning);
+        let (result, launched) = run_probe(&probe);
+        assert!(result.is_ok());
+        assert!(
+            !launched,
+            "already-running path must not reach Python discovery"
+        );
+        assert_eq!(
+            probe.status_probes.get(),
+            0,
+            "mutex hit skips the port probe"
+        );
+        assert_eq!(probe.focus_calls.get(), 1);
+    }
+
+    #[test]
+    fn running_app_without_mutex_is_focused() {
+        let probe = FakeProbe::new(false, AppStatus::Running);
+        let (result, launched) = run_probe(&probe);
+        assert!(result.is_ok());
+        assert!(!launched);
+        assert_eq!(probe.focus_calls.get(), 1);
+    }
+
+    #[test]
+    fn app_on_reported_port_is_focused_despite_foreign_configured_port() {
+        let mut instance = InstanceScope::resolve(SingletonScope::PerMachine, APP_PORT, None);
+        instance.reported_port = Some(49152);
+        let probe = FakeProbe {
+            running_on: Some(49152),
+            ..FakeProbe::new(false, AppStatus::PortInUse)
+        };
+        let (result, launched) = run_probe_on(&probe, &instance, InstanceCheck::PortProbe);
+        assert!(result.is_ok());
+        assert!(!launched);
+        assert_eq!(probe.focus_calls.get(), 1);
+
+        // Without the report, the foreign process on the configured port is a conflict.
+        instance.reported_port = None;
+        let (result, launched) = run_probe_on(&probe, &instance, InstanceCheck::PortProbe);
+        assert!(result.unwrap_err().contains("already in use"));
+        assert!(!launched);
+    }
+
+    #[test]
+    fn port_conflict_stops_before_discovery() {
+        let (result, launched) = run_probe(&FakeProbe::new(false, AppStatus::PortInUse));
+        assert!(result.unwrap_err().contains("already in use"));
+        assert!(!launched);
+    }
+
+    #[test]
+    fn port_conflict_falls_back_to_a_free_port_when_enabled() {
+        let probe = FakeProbe {
+            fallback_port: Some(49200),
+            ..FakeProbe::new(false, AppStatus::PortInUse)
+        };
+        let instance = InstanceScope::resolve(SingletonScope::PerMachine, APP_PORT, None);
+        let launched_on = Cell::new(None);
+        let result = focus_or_launch(
+            &probe,
+            &instance,
+            false,
+            InstanceCheck::PortProbe,
+            Instant::now(),
+            &None,
+            |target| {
+                launched_on.set(Some(target.port));
+                Ok(LaunchEnd::RelaunchElevated)
+            },
+        );
+        // The launch result (here an accepted elevation) reaches `run` unchanged.
+        assert_eq!(result, Ok(LaunchEnd::RelaunchElevated));
+        assert_eq!(launched_on.get(), Some(49200));
+        assert_eq!(probe.focus_calls.get(), 0);
+    }
+
+    #[test]
+    fn arguments_are_forwarded_before_focusing() {
+        let mut instance = InstanceScope::resolve(SingletonScope::PerMachine, APP_PORT, None);
+        instance.reported_port = Some(49152);
+        for forward_result in [Ok(()), Err("HTTP 404".to_string())] {
+            let probe = FakeProbe {
+                forward_result: Some(forward_result),
+                ..FakeProbe::new(true, AppStatus::NotRunning)
+            };
+            let (result, launched) = run_probe_on(&probe, &instance, InstanceCheck::PortProbe);
+            assert!(result.is_ok());
+            assert!(!launched);
+            assert_eq!(*probe.forward_ports.borrow(), vec![49152]);
+            assert_eq!(probe.focus_calls.get(), 1, "focus follows either way");
+        }
+
+        let probe = FakeProbe::new(false, AppStatus::NotRunning);
+        let (_, launched) = run_probe(&probe);
+        assert!(launched);
+        assert!(
+            probe.forward_ports.borrow().is_empty(),
+            "a new app gets none"
+        );
+    }
+
+    #[test]
+    fn no_instance_launches() {
+        let probe = FakeProbe::new(false, AppStatus::NotRunning);
+        let (result, launched) = run_probe(&probe);
+        assert!(result.is_ok());
+        assert!(launched);
+        assert_eq!(probe.focus_calls.get(), 0);
+    }
+
+    #[test]
+    fn blocked_loopback_uses_lock_instead_of_port_probe() {
+        let probe = FakeProbe {
+            lock_held: true,
+            ..FakeProbe::new(false, AppStatus::PortInUse)
+        };
+        let (result, launched) = run_probe_with(&probe, InstanceCheck::LockOnly);
+        assert!(result.is_ok());
+        assert!(!launched);
+        assert_eq!(probe.status_probes.get(), 0, "port probe is skipped");
+        assert_eq!(probe.focus_calls.get(), 1);
+
+        let probe = FakeProbe::new(false, AppStatus::PortInUse);
+        let (result, launched) = run_probe_with(&probe, InstanceCheck::LockOnly);
+        assert!(result.is_ok());
+        assert!(launched, "no lock holder: launch");
+        assert_eq!(probe.status_probes.get(), 0);
+    }
+
+    #[test]
+    fn restart_limits_default_to_the_constants() {
+        assert_eq!(
+            restart_limits(&LauncherConfig::default()),
+            RestartLimits::default()
+        );
+        let config = LauncherConfig {
+            max_restarts: Some(0),
+            restart_backoff_base_sec: Some(5),
+            restart_backoff_max_sec: Some(12),
+            restart_give_up_after_sec: 900,
+            ..Default::default()
+        };
+        let limits = restart_limits(&config);
+        assert_eq!(limits.max_restarts, 0);
+        assert_eq!(limits.backoff_base, Duration::from_secs(5));
+        assert_eq!(limits.backoff_max, Duration::from_secs(12));
+        assert_eq!(limits.reset_after, RestartLimits::default().reset_after);
+        assert_eq!(limits.give_up_after, Some(Duration::from_secs(900)));
+    }
+
+    #[test]
+    fn prepared_environment_skips_python_setup() {
+        let base = temp_dir("prepare-env");
+        let venv = base.join(".venv");
+        fs::create_dir_all(&venv).unwrap();
+        fs::write(venv.join("pyvenv.cfg"), "home = untouched\n").unwrap();
+        let python_exe = venv.join("Scripts").join("python.exe");
+        let log_path = Some(base.join("launcher.log"));
+        let config = LauncherConfig::default();
+        let token = env_token(&base, current_session_id());
+        let prepared = |name: &str| match name {
+            ENV_PREPARED_ENV => Some("1".to_string()),
+            ENV_TOKEN_ENV => Some(token.clone()),
+            _ => None,
+        };
+
+        // There is no .uv-python here: discovery would fail if it ran.
+        prepare_environment(&base, &venv, &python_exe, &config, prepared, &log_path).unwrap();
+        assert_eq!(
+            fs::read_to_string(venv.join("pyvenv.cfg")).unwrap(),
+            "home = untouched\n",
+            "pyvenv.cfg is not patched"
+        );
+        let log = fs::read_to_string(base.join("launcher.log")).unwrap();
+        assert!(log.contains("skipping Python setup"));
+
+        // Without the marker the same install goes through discovery.
+        let err = prepare_environment(&base, &venv, &python_exe, &config, |_| None, &log_path)
+            .unwrap_err();
+        assert!(err.contains("Python not found"));
+        let _ = fs::remove_dir_all(&base);
+    }
+}
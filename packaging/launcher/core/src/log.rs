@@ -0,0 +1,376 @@
+//! Launcher log file (`launcher.log`).
+
+use std::env;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::paths::get_data_dir;
+
+pub fn init_log_path(base_dir: &Path) -> Option<PathBuf> {
+    let mut candidate = get_data_dir().map(|dir| dir.join("logs")).or_else(|| {
+        env::var("LOCALAPPDATA")
+            .map(PathBuf::from)
+            .map(|p| p.join("YakuLingo").join("logs"))
+            .ok()
+    });
+
+    if candidate.is_none() {
+        candidate = Some(base_dir.join("logs"));
+    }
+
+    if let Some(dir) = candidate {
+        if fs::create_dir_all(&dir).is_ok() {
+            return Some(dir.join("launcher.log"));
+        }
+    }
+
+    None
+}
+
+/// Whether `log_path` can be appended to (creating it if needed).
+pub fn check_log_writable(log_path: &Option<PathBuf>) -> Result<String, String> {
+    let path = log_path
+        .as_ref()
+        .ok_or("no writable log location (file logging disabled)")?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map(|_| path.display().to_string())
+        .map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+/// Where breadcrumbs go: a seam so the message can be tested without a debugger.
+pub trait BreadcrumbSink {
+    fn emit(&mut self, line: &str);
+}
+
+/// `OutputDebugStringW` on Windows (visible in DebugView), stderr elsewhere.
+pub struct DebugOutput;
+
+impl BreadcrumbSink for DebugOutput {
+    #[cfg(windows)]
+    fn emit(&mut self, line: &str) {
+        use std::ffi::OsStr;
+        use std::iter::once;
+        use std::os::windows::ffi::OsStrExt;
+        use winapi::um::debugapi::OutputDebugStringW;
+
+        let wide: Vec<u16> = OsStr::new(line).encode_wide().chain(once(0)).collect();
+        unsafe { OutputDebugStringW(wide.as_ptr()) };
+    }
+
+    #[cfg(not(windows))]
+    fn emit(&mut self, line: &str) {
+        eprintln!("{}", line);
+    }
+}
+
+/// One line outside the log file, so a launch leaves a trace even when no
+/// log location is writable.
+pub fn breadcrumb(sink: &mut impl BreadcrumbSink, message: &str) {
+    sink.emit(&format!(
+        "[YakuLingo launcher {} pid {}] {}",
+        env!("CARGO_PKG_VERSION"),
+        std::process::id(),
+        message
+    ));
+}
+
+/// `launcher.log` is rotated once it grows past this size.
+pub const LOG_ROTATE_BYTES: u64 = 1024 * 1024;
+/// Rotated files kept (`launcher.log.1` newest .. `launcher.log.3`).
+pub const LOG_KEEP_ROTATED: u32 = 3;
+
+/// Set once the config is loaded; `log_event` rotates before appending.
+static LOG_ROTATION: OnceLock<LogRotation> = OnceLock::new();
+/// Set by `--verbose`: `log_event` also writes each line to stderr.
+static ECHO_TO_CONSOLE: OnceLock<()> = OnceLock::new();
+
+/// When and how `launcher.log` is rotated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogRotation {
+    pub max_bytes: u64,
+    /// Rotated files kept; 0 keeps none (the log just starts over).
+    pub keep: u32,
+    /// Gzip rotated files.
+    pub compress: bool,
+}
+
+impl Default for LogRotation {
+    fn default() -> Self {
+        LogRotation {
+            max_bytes: LOG_ROTATE_BYTES,
+            keep: LOG_KEEP_ROTATED,
+            compress: false,
+        }
+    }
+}
+
+/// Rotate in every later `log_event`, so a long watchdog session stays
+/// within the limit too. Only the first call takes effect.
+pub fn set_log_rotation(rotation: LogRotation) {
+    let _ = LOG_ROTATION.set(rotation);
+}
+
+/// `launcher.log.<index>`, or `launcher.log.<index>.gz` when compressed.
+pub fn rotated_log_path(log_path: &Path, index: u32, compressed: bool) -> PathBuf {
+    let mut name = log_path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    if compressed {
+        name.push(".gz");
+    }
+    PathBuf::from(name)
+}
+
+/// Rotate `log_path` when it is larger than `max_bytes`: older rotations
+/// shift up by one (those beyond `keep` are dropped) and the current log
+/// becomes `.1`, gzipped with `compress`. The active log is never
+/// compressed. Returns whether a rotation happened.
+pub fn rotate_log(log_path: &Path, rotation: &LogRotation) -> io::Result<bool> {
+    match fs::metadata(log_path) {
+        Ok(meta) if meta.len() > rotation.max_bytes => {}
+        Ok(_) => return Ok(false),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err),
+    }
+    if rotation.keep == 0 {
+        fs::remove_file(log_path)?;
+        return Ok(true);
+    }
+    let (keep, compress) = (rotation.keep, rotation.compress);
+    for compressed in [false, true] {
+        let oldest = rotated_log_path(log_path, keep, compressed);
+        if oldest.exists() {
+            fs::remove_file(oldest)?;
+        }
+        for index in (1..keep).rev() {
+            let from = rotated_log_path(log_path, index, compressed);
+            if from.exists() {
+                fs::rename(from, rotated_log_path(log_path, index + 1, compressed))?;
+            }
+        }
+    }
+    if !compress {
+        fs::rename(log_path, rotated_log_path(log_path, 1, false))?;
+        return Ok(true);
+    }
+    // Compress next to the target and rename, so a crash never leaves a
+    // truncated `.gz` behind.
+    let target = rotated_log_path(log_path, 1, true);
+    let partial = target.with_extension("gz.tmp");
+    let mut encoder = GzEncoder::new(File::create(&partial)?, Compression::default());
+    io::copy(&mut File::open(log_path)?, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+    fs::rename(&partial, &target)?;
+    fs::remove_file(log_path)?;
+    Ok(true)
+}
+
+/// Read a current or rotated log, decompressing `.gz` files.
+pub fn read_log_file(path: &Path) -> io::Result<String> {
+    let mut content = String::new();
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        GzDecoder::new(File::open(path)?).read_to_string(&mut content)?;
+    } else {
+        File::open(path)?.read_to_string(&mut content)?;
+    }
+    Ok(content)
+}
+
+/// The newest rotated log (`launcher.log.1.gz` or `launcher.log.1`), if any.
+pub fn read_previous_log(log_path: &Path) -> Option<String> {
+    [true, false]
+        .into_iter()
+        .map(|compressed| rotated_log_path(log_path, 1, compressed))
+        .find(|path| path.is_file())
+        .and_then(|path| read_log_file(&path).ok())
+}
+
+pub fn log_event(log_path: &Option<PathBuf>, message: &str) {
+    if let Some(path) = log_path {
+        append_log_line(path, message, LOG_ROTATION.get());
+    }
+    if ECHO_TO_CONSOLE.get().is_some() {
+        eprintln!("[{}] {}", unix_timestamp(), message);
+    }
+}
+
+/// Echo every later `log_event` line to stderr (`--verbose`).
+pub fn set_verbose() {
+    let _ = ECHO_TO_CONSOLE.set(());
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_else(|_| Duration::from_secs(0))
+        .as_secs()
+}
+
+/// A failed rotation (say, another launcher has the log open) is dropped:
+/// the line is appended to the oversized file rather than lost.
+fn append_log_line(path: &Path, message: &str, rotation: Option<&LogRotation>) {
+    if let Some(rotation) = rotation {
+        let _ = rotate_log(path, rotation);
+    }
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(file, "[{}] {}", unix_timestamp(), message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    fn small(compress: bool) -> LogRotation {
+        LogRotation {
+            max_bytes: 16,
+            compress,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn log_event_appends_timestamped_lines() {
+        let dir = temp_dir("log-append");
+        let path = Some(dir.join("launcher.log"));
+
+        log_event(&path, "first");
+        log_event(&path, "second");
+
+        let content = fs::read_to_string(path.as_ref().unwrap()).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with('[') && lines[0].ends_with("] first"));
+        assert!(lines[1].ends_with("] second"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn small_log_is_not_rotated() {
+        let dir = temp_dir("log-small");
+        let path = dir.join("launcher.log");
+        assert!(!rotate_log(&path, &small(false)).unwrap(), "missing log");
+        fs::write(&path, "short\n").unwrap();
+        assert!(!rotate_log(&path, &small(false)).unwrap());
+        assert!(path.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rotation_shifts_and_drops_the_oldest() {
+        let dir = temp_dir("log-rotate");
+        let path = dir.join("launcher.log");
+        for run in 1..=LOG_KEEP_ROTATED + 1 {
+            fs::write(&path, format!("run {} with a long enough line\n", run)).unwrap();
+            assert!(rotate_log(&path, &small(false)).unwrap());
+        }
+        assert!(!path.exists());
+        assert_eq!(
+            read_log_file(&rotated_log_path(&path, 1, false)).unwrap(),
+            "run 4 with a long enough line\n"
+        );
+        assert!(read_log_file(&rotated_log_path(&path, 3, false))
+            .unwrap()
+            .starts_with("run 2"));
+        assert!(!rotated_log_path(&path, 4, false).exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn appending_rotates_with_the_configured_count() {
+        let dir = temp_dir("log-append-rotate");
+        let path = dir.join("launcher.log");
+        let rotation = LogRotation {
+            keep: 1,
+            ..small(false)
+        };
+        for message in ["first long line", "second long line", "third"] {
+            append_log_line(&path, message, Some(&rotation));
+        }
+        assert!(fs::read_to_string(&path).unwrap().ends_with("] third\n"));
+        assert!(fs::read_to_string(rotated_log_path(&path, 1, false))
+            .unwrap()
+            .ends_with("] second long line\n"));
+        assert!(!rotated_log_path(&path, 2, false).exists());
+
+        let none_kept = LogRotation {
+            keep: 0,
+            ..small(false)
+        };
+        append_log_line(&path, "fourth", Some(&none_kept));
+        assert!(fs::read_to_string(&path).unwrap().ends_with("] fourth\n"));
+        assert!(fs::read_to_string(rotated_log_path(&path, 1, false))
+            .unwrap()
+            .ends_with("] second long line\n"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn failed_rotation_keeps_appending() {
+        let dir = temp_dir("log-rotate-fail");
+        let path = dir.join("launcher.log");
+        fs::write(&path, "an oversized existing log\n").unwrap();
+        // A directory where `.1` should go makes the rotation fail.
+        fs::create_dir_all(rotated_log_path(&path, 1, false)).unwrap();
+        let rotation = LogRotation {
+            keep: 1,
+            ..small(false)
+        };
+        assert!(rotate_log(&path, &rotation).is_err());
+        append_log_line(&path, "still logged", Some(&rotation));
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("an oversized existing log\n"));
+        assert!(content.ends_with("] still logged\n"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn compressed_rotation_round_trips() {
+        let dir = temp_dir("log-gzip");
+        let path = dir.join("launcher.log");
+        let original: String = (0..200)
+            .map(|i| format!("[{}] Python process spawned, watchdog active\n", i))
+            .collect();
+        fs::write(&path, &original).unwrap();
+        fs::write(rotated_log_path(&path, 1, false), "older, uncompressed\n").unwrap();
+
+        assert!(rotate_log(&path, &small(true)).unwrap());
+        let compressed = rotated_log_path(&path, 1, true);
+        assert!(!path.exists(), "the next launch starts a fresh log");
+        assert!(fs::metadata(&compressed).unwrap().len() < original.len() as u64);
+        assert_eq!(read_log_file(&compressed).unwrap(), original);
+        assert_eq!(
+            read_log_file(&rotated_log_path(&path, 2, false)).unwrap(),
+            "older, uncompressed\n"
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn breadcrumb_identifies_the_launcher() {
+        struct Lines(Vec<String>);
+        impl BreadcrumbSink for Lines {
+            fn emit(&mut self, line: &str) {
+                self.0.push(line.to_string());
+            }
+        }
+
+        let mut sink = Lines(Vec::new());
+        breadcrumb(&mut sink, "started");
+        assert_eq!(sink.0.len(), 1);
+        assert!(sink.0[0].starts_with("[YakuLingo launcher "));
+        assert!(sink.0[0].contains(&format!("pid {}]", std::process::id())));
+        assert!(sink.0[0].ends_with("] started"));
+    }
+}
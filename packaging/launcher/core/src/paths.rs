@@ -0,0 +1,274 @@
+//! Per-user locations shared with the Python side (`~/.yakulingo`, or
+//! `YAKULINGO_DATA_DIR` when set).
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+use crate::log::log_event;
+
+/// Redirects state and logs (roaming/redirected profiles) when set.
+pub const DATA_DIR_ENV: &str = "YAKULINGO_DATA_DIR";
+/// Appended by Linux to `/proc/self/exe` once the running binary is replaced.
+const DELETED_EXE_SUFFIX: &str = " (deleted)";
+
+static LAUNCHER_EXE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Path of the launcher binary, resolved on first call and cached. `run`
+/// calls this before anything else, so an in-place update that renames or
+/// replaces the binary later cannot change (or break) the answer.
+pub fn launcher_exe() -> Result<&'static Path, String> {
+    if let Some(exe) = LAUNCHER_EXE.get() {
+        return Ok(exe);
+    }
+    let exe = env::current_exe().map_err(|e| format!("Failed to get executable path: {}", e))?;
+    let exe = resolve_exe_path(&exe)?;
+    Ok(LAUNCHER_EXE.get_or_init(|| exe))
+}
+
+/// Directory holding the launcher binary: the install dir.
+pub fn launcher_dir() -> Result<PathBuf, String> {
+    launcher_exe()?
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| "Failed to get executable directory".to_string())
+}
+
+/// Undo the deleted-binary suffix: the replacement sits at the original
+/// path. If that directory is gone too there is no sane base dir.
+pub fn resolve_exe_path(exe: &Path) -> Result<PathBuf, String> {
+    let original = match exe
+        .to_str()
+        .and_then(|path| path.strip_suffix(DELETED_EXE_SUFFIX))
+    {
+        Some(value) => PathBuf::from(value),
+        None => return Ok(exe.to_path_buf()),
+    };
+    if original.parent().is_some_and(Path::is_dir) {
+        Ok(original)
+    } else {
+        Err(format!(
+            "Launcher executable was removed while running: {:?}\n\nPlease start YakuLingo again.",
+            exe
+        ))
+    }
+}
+
+/// `YAKULINGO_DATA_DIR`, when set and non-empty.
+pub fn get_data_dir() -> Option<PathBuf> {
+    env::var_os(DATA_DIR_ENV)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Check that `dir` can hold YakuLingo's data, creating it if needed.
+pub fn validate_data_dir(dir: &Path) -> Result<PathBuf, String> {
+    if !dir.is_absolute() {
+        return Err(format!(
+            "{} must be an absolute path: {:?}",
+            DATA_DIR_ENV, dir
+        ));
+    }
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("{} {:?} cannot be created: {}", DATA_DIR_ENV, dir, e))?;
+    if !dir.is_dir() {
+        return Err(format!("{} {:?} is not a directory", DATA_DIR_ENV, dir));
+    }
+    Ok(dir.to_path_buf())
+}
+
+/// Validate `YAKULINGO_DATA_DIR` before anything is written under it. The
+/// variable stays set, so the app inherits the same location.
+pub fn prepare_data_dir() -> Result<Option<PathBuf>, String> {
+    match get_data_dir() {
+        Some(dir) => validate_data_dir(&dir).map(Some),
+        None => Ok(None),
+    }
+}
+
+pub fn get_home_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        if let Ok(profile) = env::var("USERPROFILE") {
+            return Some(PathBuf::from(profile));
+        }
+        let drive = env::var("HOMEDRIVE").ok();
+        let path = env::var("HOMEPATH").ok();
+        if let (Some(drive), Some(path)) = (drive, path) {
+            return Some(PathBuf::from(format!("{}{}", drive, path)));
+        }
+    }
+    env::var("HOME").ok().map(PathBuf::from)
+}
+
+/// `YAKULINGO_DATA_DIR`, else `~/.yakulingo`, else the install dir when no
+/// home directory is known.
+pub fn get_state_dir(base_dir: &Path) -> PathBuf {
+    if let Some(dir) = get_data_dir() {
+        return dir;
+    }
+    match get_home_dir() {
+        Some(home) => home.join(".yakulingo"),
+        None => base_dir.to_path_buf(),
+    }
+}
+
+pub fn get_launcher_state_path(base_dir: &Path) -> Option<PathBuf> {
+    Some(get_state_dir(base_dir).join("launcher_state.json"))
+}
+
+/// Diagnostics the launcher caches between launches (`launcher_context.json`).
+pub fn get_launcher_context_path(base_dir: &Path) -> PathBuf {
+    get_state_dir(base_dir).join("launcher_context.json")
+}
+
+pub fn get_pid_file_path(base_dir: &Path, instance_name: Option<&str>) -> Option<PathBuf> {
+    let file_name = match instance_name {
+        Some(name) => format!("launcher-{}.pid", name),
+        None => "launcher.pid".to_string(),
+    };
+    Some(get_state_dir(base_dir).join(file_name))
+}
+
+/// Heartbeat file the app touches while alive (`~/.yakulingo/heartbeat[-<name>]`).
+pub fn get_heartbeat_path(base_dir: &Path, instance_name: Option<&str>) -> PathBuf {
+    let file_name = match instance_name {
+        Some(name) => format!("heartbeat-{}", name),
+        None => "heartbeat".to_string(),
+    };
+    get_state_dir(base_dir).join(file_name)
+}
+
+/// Profile directory for a named instance (`~/.yakulingo/instances/<name>`).
+pub fn get_instance_profile_dir(base_dir: &Path, instance_name: &str) -> PathBuf {
+    get_state_dir(base_dir)
+        .join("instances")
+        .join(instance_name)
+}
+
+/// Record the app PID (best-effort) so later invocations can find the process.
+pub fn write_pid_file(path: &Option<PathBuf>, pid: u32, log_path: &Option<PathBuf>) {
+    let path = match path {
+        Some(value) => value,
+        None => return,
+    };
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Err(err) = fs::write(path, format!("{}\n", pid)) {
+        log_event(log_path, &format!("Failed to write PID file: {}", err));
+    }
+}
+
+/// Replace `path` with `content` through a temp file and a rename, so a
+/// reader (the app, another launcher) never sees a half-written file. The
+/// temp name carries the PID: writers in other processes do not collide.
+pub fn write_file_atomic(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+    let mut staged = path.as_os_str().to_owned();
+    staged.push(format!(".{}.tmp", process::id()));
+    let staged = PathBuf::from(staged);
+    fs::write(&staged, content).map_err(|e| format!("Failed to write {:?}: {}", staged, e))?;
+    fs::rename(&staged, path).map_err(|e| {
+        let _ = fs::remove_file(&staged);
+        format!("Failed to replace {:?}: {}", path, e)
+    })
+}
+
+/// Modification time of `path`, if it exists.
+pub fn file_mtime(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// PID recorded by `write_pid_file`, if the file exists and parses.
+pub fn read_pid_file(path: &Option<PathBuf>) -> Option<u32> {
+    let content = fs::read_to_string(path.as_ref()?).ok()?;
+    content.trim().parse().ok()
+}
+
+pub fn remove_pid_file(path: &Option<PathBuf>) {
+    if let Some(path) = path {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crash::{get_crash_history_path, get_startup_error_path};
+    use crate::log::init_log_path;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn data_dir_override_applies_to_all_artifacts() {
+        let dir = temp_dir("data-dir");
+        let data_dir = dir.join("roaming").join("YakuLingo");
+        let base_dir = dir.join("install");
+
+        // The only test that sets this variable; other tests never read it.
+        env::set_var(DATA_DIR_ENV, &data_dir);
+        let prepared = prepare_data_dir();
+        let paths = vec![
+            get_launcher_state_path(&base_dir).unwrap(),
+            get_pid_file_path(&base_dir, None).unwrap(),
+            get_pid_file_path(&base_dir, Some("work")).unwrap(),
+            get_heartbeat_path(&base_dir, None),
+            get_instance_profile_dir(&base_dir, "work"),
+            get_startup_error_path(&base_dir),
+            get_crash_history_path(&base_dir),
+            init_log_path(&base_dir).unwrap(),
+        ];
+        env::remove_var(DATA_DIR_ENV);
+
+        assert_eq!(prepared.unwrap(), Some(data_dir.clone()));
+        assert!(data_dir.is_dir());
+        for path in paths {
+            assert!(
+                path.starts_with(&data_dir),
+                "{:?} ignores the data dir",
+                path
+            );
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn deleted_exe_suffix_is_stripped_or_rejected() {
+        let dir = temp_dir("exe-deleted");
+        let exe = dir.join("YakuLingo");
+        assert_eq!(resolve_exe_path(&exe).unwrap(), exe);
+
+        let deleted = PathBuf::from(format!("{} (deleted)", exe.display()));
+        assert_eq!(resolve_exe_path(&deleted).unwrap(), exe);
+
+        let gone = PathBuf::from(format!(
+            "{} (deleted)",
+            dir.join("removed").join("YakuLingo").display()
+        ));
+        assert!(resolve_exe_path(&gone)
+            .unwrap_err()
+            .contains("removed while running"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn relative_data_dir_is_rejected() {
+        let err = validate_data_dir(Path::new("relative/data")).unwrap_err();
+        assert!(err.contains("absolute"));
+    }
+
+    #[test]
+    fn atomic_write_replaces_the_file_without_leftovers() {
+        let dir = temp_dir("atomic-write");
+        let path = dir.join("state").join("launcher_context.json");
+        write_file_atomic(&path, "{\"python\": {}}").unwrap();
+        write_file_atomic(&path, "{\"app_ports\": {}}").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"app_ports\": {}}");
+        assert_eq!(fs::read_dir(dir.join("state")).unwrap().count(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
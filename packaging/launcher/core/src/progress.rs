@@ -0,0 +1,151 @@
+//! First-run setup progress reported by the app.
+//!
+//! On a first run the app may take minutes before its window opens (it
+//! compiles every module first). The launcher names
+//! `~/.yakulingo/setup_progress.json` in `YAKULINGO_SETUP_PROGRESS_FILE`,
+//! and the app writes its current step there:
+//!
+//! ```json
+//! {"percent": 42, "message": "Loading YakuLingo components"}
+//! ```
+//!
+//! The launcher polls the file while waiting for the window, logs each new
+//! status and shows it on the splash.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::Deserialize;
+
+use crate::paths::{file_mtime, get_state_dir};
+
+/// How long the first-run window wait lasts, instead of the usual 30s.
+pub const FIRST_RUN_WINDOW_WAIT_SEC: u64 = 600;
+
+pub fn get_setup_progress_path(base_dir: &Path) -> PathBuf {
+    get_state_dir(base_dir).join("setup_progress.json")
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SetupProgress {
+    /// 0-100; absent while the step is indeterminate.
+    #[serde(default)]
+    pub percent: Option<f64>,
+    #[serde(default)]
+    pub message: String,
+}
+
+impl SetupProgress {
+    /// Status line, e.g. `Downloading browsers (42%)`.
+    pub fn status_text(&self) -> String {
+        let message = match self.message.trim() {
+            "" => "Setting up",
+            message => message,
+        };
+        match self.percent.filter(|percent| percent.is_finite()) {
+            Some(percent) => format!("{} ({:.0}%)", message, percent.clamp(0.0, 100.0)),
+            None => message.to_string(),
+        }
+    }
+}
+
+/// Follows `setup_progress.json`, re-reading it only when it changes.
+#[derive(Debug)]
+pub struct ProgressWatcher {
+    path: PathBuf,
+    mtime: Option<SystemTime>,
+    current: Option<SetupProgress>,
+}
+
+impl ProgressWatcher {
+    pub fn new(path: PathBuf) -> Self {
+        ProgressWatcher {
+            path,
+            mtime: None,
+            current: None,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn current(&self) -> Option<&SetupProgress> {
+        self.current.as_ref()
+    }
+
+    /// Re-read the file when it changed; returns the new status when it
+    /// differs from the last one. A half-written file keeps the previous status.
+    pub fn poll(&mut self) -> Option<&SetupProgress> {
+        let mtime = file_mtime(&self.path);
+        if mtime.is_none() || mtime == self.mtime {
+            return None;
+        }
+        let progress: SetupProgress = fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())?;
+        self.mtime = mtime;
+        if self.current.as_ref() == Some(&progress) {
+            return None;
+        }
+        self.current = Some(progress);
+        self.current.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn write(path: &Path, content: &str, mtime_secs: u64) {
+        fs::write(path, content).unwrap();
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(UNIX_EPOCH + Duration::from_secs(mtime_secs))
+            .unwrap();
+    }
+
+    #[test]
+    fn progress_file_updates_are_reflected() {
+        let dir = temp_dir("progress-updates");
+        let path = dir.join("setup_progress.json");
+        let mut watcher = ProgressWatcher::new(path.clone());
+        assert_eq!(watcher.poll(), None, "no file yet");
+
+        write(&path, "{\"message\": \"Downloading browsers\"}", 1_000);
+        assert_eq!(
+            watcher.poll().map(SetupProgress::status_text).as_deref(),
+            Some("Downloading browsers")
+        );
+        assert_eq!(watcher.poll(), None, "unchanged file");
+
+        write(
+            &path,
+            "{\"percent\": 42.4, \"message\": \"Downloading browsers\"}",
+            1_001,
+        );
+        assert_eq!(
+            watcher.poll().map(SetupProgress::status_text).as_deref(),
+            Some("Downloading browsers (42%)")
+        );
+
+        write(&path, "{\"percent\": 9", 1_002);
+        assert_eq!(watcher.poll(), None, "half-written file");
+        assert_eq!(
+            watcher.current().map(SetupProgress::status_text).as_deref(),
+            Some("Downloading browsers (42%)")
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn status_text_clamps_and_defaults() {
+        let progress = SetupProgress {
+            percent: Some(130.0),
+            message: " ".to_string(),
+        };
+        assert_eq!(progress.status_text(), "Setting up (100%)");
+    }
+}
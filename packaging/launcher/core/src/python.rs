@@ -0,0 +1,758 @@
+//! Python runtime discovery and pyvenv.cfg portability fixes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::log::log_event;
+use crate::paths::{file_mtime, write_file_atomic};
+
+pub fn read_pyvenv_home(venv_dir: &Path) -> Option<PathBuf> {
+    let cfg_path = venv_dir.join("pyvenv.cfg");
+    if !cfg_path.exists() {
+        return None;
+    }
+    let content = fs::read_to_string(&cfg_path).ok()?;
+    content.lines().find_map(|line| {
+        let (_, value) = line.split_once('=')?;
+        let value = value.trim();
+        (pyvenv_key(line).as_deref() == Some("home") && !value.is_empty())
+            .then(|| PathBuf::from(value))
+    })
+}
+
+pub fn parse_cpython_version(name: &str) -> Option<(u32, u32, u32)> {
+    if !name.starts_with("cpython-") {
+        return None;
+    }
+    let tail = &name["cpython-".len()..];
+    let version_part = tail.split('-').next()?;
+    let mut iter = version_part.split('.');
+    let major: u32 = iter.next()?.parse().ok()?;
+    let minor: u32 = iter.next().unwrap_or("0").parse().ok()?;
+    let patch: u32 = iter.next().unwrap_or("0").parse().ok()?;
+    Some((major, minor, patch))
+}
+
+/// Whether `version` is the `python_version` setting (`3`, `3.11` or
+/// `3.11.9`): every component given must match.
+pub fn version_matches(version: (u32, u32, u32), preferred: &str) -> bool {
+    let (major, minor, patch) = version;
+    let parts: Vec<&str> = preferred.trim().split('.').collect();
+    parts.len() <= 3
+        && parts
+            .iter()
+            .zip([major, minor, patch])
+            .all(|(part, actual)| part.parse::<u32>() == Ok(actual))
+}
+
+/// (path, version, modified time, directory name) of a cpython-* entry.
+type PythonCandidate = (PathBuf, (u32, u32, u32), Option<SystemTime>, String);
+
+/// Find Python directory in pyvenv.cfg home or, failing that, the newest
+/// cpython-* in .uv-python (the newest matching `preferred` when set).
+pub fn find_python_dir(
+    base_dir: &Path,
+    venv_dir: &Path,
+    preferred: Option<&str>,
+    log_path: &Option<PathBuf>,
+) -> Result<PathBuf, String> {
+    if let Some(home) = read_pyvenv_home(venv_dir) {
+        let resolved = if home.is_absolute() {
+            home
+        } else {
+            base_dir.join(home)
+        };
+        if resolved.exists() {
+            log_event(
+                log_path,
+                &format!("Using pyvenv.cfg home for Python dir: {:?}", resolved),
+            );
+            return Ok(resolved);
+        }
+        log_event(
+            log_path,
+            &format!("pyvenv.cfg home not found: {:?}", resolved),
+        );
+    }
+
+    let not_found = || {
+        "Python not found in .uv-python directory.\n\nPlease reinstall the application.".to_string()
+    };
+    let uv_python_dir = base_dir.join(".uv-python");
+    if !uv_python_dir.exists() {
+        return Err(not_found());
+    }
+
+    let entries = fs::read_dir(&uv_python_dir)
+        .map_err(|e| format!("Failed to read .uv-python directory: {}", e))?;
+
+    let mut candidates: Vec<(PathBuf, (u32, u32, u32), String)> = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+        if !name.starts_with("cpython-") || !path.is_dir() {
+            continue;
+        }
+        match parse_cpython_version(&name) {
+            Some(version) => candidates.push((path, version, name)),
+            None => log_event(
+                log_path,
+                &format!("Skipping {:?}: no version in the directory name", name),
+            ),
+        }
+    }
+
+    if let Some(preferred) = preferred {
+        let matching: Vec<_> = candidates
+            .iter()
+            .filter(|(_, version, _)| version_matches(*version, preferred))
+            .cloned()
+            .collect();
+        if !matching.is_empty() {
+            candidates = matching;
+        } else if !candidates.is_empty() {
+            log_event(
+                log_path,
+                &format!(
+                    "No Python dir matches python_version {:?} - using the newest",
+                    preferred
+                ),
+            );
+        }
+    }
+
+    // The usual install: nothing to choose between.
+    if candidates.len() == 1 {
+        return Ok(candidates.remove(0).0);
+    }
+    let mut candidates: Vec<PythonCandidate> = candidates
+        .into_iter()
+        .map(|(path, version, name)| {
+            let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+            (path, version, modified, name)
+        })
+        .collect();
+    candidates.sort_by(|a, b| {
+        let (_, version_a, modified_a, name_a) = a;
+        let (_, version_b, modified_b, name_b) = b;
+        version_b
+            .cmp(version_a)
+            .then_with(|| match (modified_a, modified_b) {
+                (Some(ma), Some(mb)) => mb.cmp(ma),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            })
+            .then_with(|| name_b.cmp(name_a))
+    });
+
+    let (selected, ..) = candidates.into_iter().next().ok_or_else(not_found)?;
+    log_event(
+        log_path,
+        &format!(
+            "Multiple Python dirs found in .uv-python; selected {:?}",
+            selected
+        ),
+    );
+    Ok(selected)
+}
+
+/// Key of a `key = value` line in pyvenv.cfg, lowercased.
+fn pyvenv_key(line: &str) -> Option<String> {
+    let (key, _) = line.split_once('=')?;
+    Some(key.trim().to_lowercase())
+}
+
+/// pyvenv.cfg with `home` pointing at `expected_home` and the
+/// `include-system-site-packages` line in its canonical form. Every other
+/// line (`prompt`, `uv`, `version_info`, comments...) is kept as is, and so
+/// are the line endings.
+pub fn rewrite_pyvenv_cfg(content: &str, expected_home: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut found_home = false;
+    for line in content.lines() {
+        match pyvenv_key(line).as_deref() {
+            Some("home") => {
+                lines.push(format!("home = {}", expected_home));
+                found_home = true;
+            }
+            Some("include-system-site-packages") => {
+                let (_, value) = line.split_once('=').unwrap_or_default();
+                lines.push(format!(
+                    "include-system-site-packages = {}",
+                    value.trim().to_lowercase()
+                ));
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    if !found_home {
+        lines.insert(0, format!("home = {}", expected_home));
+    }
+
+    let line_ending = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut new_content = lines.join(line_ending);
+    if content.ends_with(line_ending) {
+        new_content.push_str(line_ending);
+    }
+    new_content
+}
+
+/// Fix pyvenv.cfg home path for portability (only if needed). The previous
+/// file is kept as `pyvenv.cfg.bak`, and the new one is written next to it
+/// and renamed into place so an interrupted write never leaves a torn file.
+pub fn fix_pyvenv_cfg(venv_dir: &Path, python_dir: &Path) -> Result<(), String> {
+    let cfg_path = venv_dir.join("pyvenv.cfg");
+
+    if !cfg_path.exists() {
+        return Ok(()); // Skip if not exists
+    }
+
+    let current_content =
+        fs::read_to_string(&cfg_path).map_err(|e| format!("Failed to read pyvenv.cfg: {}", e))?;
+    let new_content = rewrite_pyvenv_cfg(&current_content, &python_dir.display().to_string());
+    if new_content == current_content {
+        return Ok(());
+    }
+
+    fs::copy(&cfg_path, cfg_path.with_extension("cfg.bak"))
+        .map_err(|e| format!("Failed to back up pyvenv.cfg: {}", e))?;
+    let staged = cfg_path.with_extension("cfg.tmp");
+    fs::write(&staged, new_content).map_err(|e| format!("Failed to write pyvenv.cfg: {}", e))?;
+    fs::rename(&staged, &cfg_path).map_err(|e| {
+        let _ = fs::remove_file(&staged);
+        format!("Failed to replace pyvenv.cfg: {}", e)
+    })?;
+
+    Ok(())
+}
+
+/// The venv's python.exe is the bundled interpreter: present, and not the
+/// Store alias stub.
+pub fn check_venv_python(python_exe: &Path, log_path: &Option<PathBuf>) -> Result<(), String> {
+    // Before `exists`, which follows the alias to the Store stub.
+    check_not_store_stub(python_exe, exe_traits(python_exe), log_path)?;
+    if !python_exe.is_file() {
+        return Err(format!(
+            ".venv not found ({}).\n\nPlease reinstall the application.",
+            python_exe.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `fix_pyvenv_cfg` could do its job, without changing anything:
+/// the file is current, absent, or writable.
+pub fn check_pyvenv_cfg(venv_dir: &Path, python_dir: &Path) -> Result<String, String> {
+    let cfg_path = venv_dir.join("pyvenv.cfg");
+    if !cfg_path.exists() {
+        return Ok("absent (nothing to patch)".to_string());
+    }
+    let content =
+        fs::read_to_string(&cfg_path).map_err(|e| format!("Failed to read pyvenv.cfg: {}", e))?;
+    if rewrite_pyvenv_cfg(&content, &python_dir.display().to_string()) == content {
+        return Ok("home is current".to_string());
+    }
+    fs::OpenOptions::new()
+        .append(true)
+        .open(&cfg_path)
+        .map(|_| "needs patching (writable)".to_string())
+        .map_err(|e| format!("needs patching but is not writable: {}", e))
+}
+
+/// Reparse tag of App Execution Aliases such as the Microsoft Store
+/// `python.exe` stub in `%LOCALAPPDATA%\Microsoft\WindowsApps`.
+pub const IO_REPARSE_TAG_APPEXECLINK: u32 = 0x8000001B;
+
+/// The on-disk traits of an executable that give away an alias stub.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExeTraits {
+    pub len: u64,
+    /// Reparse tag when the file is a reparse point.
+    pub reparse_tag: Option<u32>,
+}
+
+/// Stands in for the tag of a reparse point whose tag cannot be read (0 is
+/// reserved and never a real tag).
+pub const UNREADABLE_REPARSE_TAG: u32 = 0;
+
+/// Whether `traits` describe an App Execution Alias, which opens the Store
+/// instead of running Python. Other reparse points, such as the symlinks of
+/// `venv --symlinks`, are zero-length too and are not aliases; an unreadable
+/// tag on a zero-length file is treated as one.
+pub fn is_app_execution_alias(traits: &ExeTraits) -> bool {
+    match traits.reparse_tag {
+        Some(IO_REPARSE_TAG_APPEXECLINK) => true,
+        Some(UNREADABLE_REPARSE_TAG) => traits.len == 0,
+        _ => false,
+    }
+}
+
+/// `ExeTraits` of `path` itself (not following reparse points).
+#[cfg(windows)]
+pub fn exe_traits(path: &Path) -> Option<ExeTraits> {
+    use std::ffi::OsStr;
+    use std::iter::once;
+    use std::mem::zeroed;
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::fs::MetadataExt;
+    use winapi::um::fileapi::{FindClose, FindFirstFileW};
+    use winapi::um::handleapi::INVALID_HANDLE_VALUE;
+    use winapi::um::winnt::FILE_ATTRIBUTE_REPARSE_POINT;
+
+    let meta = fs::symlink_metadata(path).ok()?;
+    let mut traits = ExeTraits {
+        len: meta.len(),
+        reparse_tag: None,
+    };
+    if meta.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT == 0 {
+        return Some(traits);
+    }
+    let wide: Vec<u16> = OsStr::new(path).encode_wide().chain(once(0)).collect();
+    unsafe {
+        let mut data = zeroed();
+        let handle = FindFirstFileW(wide.as_ptr(), &mut data);
+        if handle == INVALID_HANDLE_VALUE {
+            traits.reparse_tag = Some(UNREADABLE_REPARSE_TAG);
+            return Some(traits);
+        }
+        FindClose(handle);
+        // For reparse points `dwReserved0` holds the reparse tag.
+        traits.reparse_tag = Some(data.dwReserved0);
+    }
+    Some(traits)
+}
+
+#[cfg(not(windows))]
+pub fn exe_traits(path: &Path) -> Option<ExeTraits> {
+    let meta = fs::symlink_metadata(path).ok()?;
+    Some(ExeTraits {
+        len: meta.len(),
+        reparse_tag: None,
+    })
+}
+
+/// Refuse an interpreter that is an App Execution Alias stub.
+pub fn check_not_store_stub(
+    python_exe: &Path,
+    traits: Option<ExeTraits>,
+    log_path: &Option<PathBuf>,
+) -> Result<(), String> {
+    if !traits.is_some_and(|traits| is_app_execution_alias(&traits)) {
+        return Ok(());
+    }
+    log_event(
+        log_path,
+        &format!(
+            "{:?} is an App Execution Alias (Microsoft Store Python stub) - aborting",
+            python_exe
+        ),
+    );
+    Err("python.exe points at the Microsoft Store Python stub instead of the bundled Python.\n\nPlease reinstall the application.".to_string())
+}
+
+/// Prints version, pointer width and `sys.executable`, one per line.
+const PYTHON_INFO_SCRIPT: &str =
+    "import sys,platform;print(platform.python_version());print(platform.architecture()[0]);print(sys.executable)";
+const PYTHON_QUERY_TIMEOUT_MS: u64 = 5000;
+
+/// What the resolved interpreter reports about itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PythonInfo {
+    pub version: String,
+    pub arch: String,
+    pub executable: String,
+}
+
+/// `PythonInfo` cached in `launcher_context.json`, valid while the
+/// interpreter path and mtime are unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedPython {
+    path: String,
+    mtime_ms: u64,
+    #[serde(flatten)]
+    info: PythonInfo,
+}
+
+pub fn parse_python_info(stdout: &str) -> Option<PythonInfo> {
+    let mut lines = stdout.lines().map(str::trim);
+    let info = PythonInfo {
+        version: lines.next()?.to_string(),
+        arch: lines.next()?.to_string(),
+        executable: lines.next()?.to_string(),
+    };
+    (!info.version.is_empty()).then_some(info)
+}
+
+/// Run `python_exe` with the info script; `None` when it fails or does not
+/// finish within the timeout (it is killed then).
+pub fn query_python_info(python_exe: &Path) -> Option<PythonInfo> {
+    let mut command = Command::new(python_exe);
+    command
+        .args(["-c", PYTHON_INFO_SCRIPT])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let mut child = command.spawn().ok()?;
+    let deadline = Instant::now() + Duration::from_millis(PYTHON_QUERY_TIMEOUT_MS);
+    while child.try_wait().ok()?.is_none() {
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    let output = child.wait_with_output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_python_info(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// The interpreter's `PythonInfo`: from `context_path` when cached for the
+/// same path and mtime (and `use_cache`), otherwise from `query`, which is
+/// then cached. Other keys in the context file are kept.
+pub fn resolve_python_info(
+    python_exe: &Path,
+    context_path: &Path,
+    use_cache: bool,
+    query: impl FnOnce(&Path) -> Option<PythonInfo>,
+    log_path: &Option<PathBuf>,
+) -> Option<PythonInfo> {
+    let path = python_exe.display().to_string();
+    let mtime_ms = file_mtime(python_exe)
+        .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok())
+        .map(|since| since.as_millis() as u64)?;
+    let mut context: Map<String, Value> = fs::read_to_string(context_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    let cached = context
+        .get("python")
+        .and_then(|value| serde_json::from_value::<CachedPython>(value.clone()).ok())
+        .filter(|cached| cached.path == path && cached.mtime_ms == mtime_ms);
+    if let (true, Some(cached)) = (use_cache, cached) {
+        return Some(cached.info);
+    }
+
+    let info = query(python_exe)?;
+    let entry = CachedPython {
+        path,
+        mtime_ms,
+        info: info.clone(),
+    };
+    context.insert(
+        "python".to_string(),
+        serde_json::to_value(entry).unwrap_or(Value::Null),
+    );
+    let written = serde_json::to_string_pretty(&context)
+        .map_err(|e| e.to_string())
+        .and_then(|content| write_file_atomic(context_path, &content));
+    if let Err(err) = written {
+        log_event(
+            log_path,
+            &format!("Failed to write launcher context: {}", err),
+        );
+    }
+    Some(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn parse_cpython_version_reads_uv_directory_names() {
+        assert_eq!(
+            parse_cpython_version("cpython-3.11.9-windows-x86_64-none"),
+            Some((3, 11, 9))
+        );
+        assert_eq!(parse_cpython_version("cpython-3.12"), Some((3, 12, 0)));
+        assert_eq!(parse_cpython_version("cpython-latest"), None);
+        assert_eq!(parse_cpython_version("pypy-3.10"), None);
+    }
+
+    #[test]
+    fn find_python_dir_prefers_highest_version() {
+        let base = temp_dir("find-python");
+        let venv = base.join(".venv");
+        fs::create_dir_all(&venv).unwrap();
+        for name in [
+            "cpython-3.10.4-windows-x86_64-none",
+            "cpython-3.11.9-windows-x86_64-none",
+            "cpython-3.11.2-windows-x86_64-none",
+        ] {
+            fs::create_dir_all(base.join(".uv-python").join(name)).unwrap();
+        }
+
+        let selected = find_python_dir(&base, &venv, None, &None).unwrap();
+        assert_eq!(
+            selected,
+            base.join(".uv-python")
+                .join("cpython-3.11.9-windows-x86_64-none")
+        );
+
+        // A configured version wins over a newer one; an unmatched one is
+        // ignored.
+        let selected = find_python_dir(&base, &venv, Some("3.10"), &None).unwrap();
+        assert!(selected.ends_with("cpython-3.10.4-windows-x86_64-none"));
+        let selected = find_python_dir(&base, &venv, Some("3.11.2"), &None).unwrap();
+        assert!(selected.ends_with("cpython-3.11.2-windows-x86_64-none"));
+        let selected = find_python_dir(&base, &venv, Some("3.13"), &None).unwrap();
+        assert!(selected.ends_with("cpython-3.11.9-windows-x86_64-none"));
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn find_python_dir_skips_unparseable_names() {
+        let base = temp_dir("find-python-unparseable");
+        let venv = base.join(".venv");
+        fs::create_dir_all(&venv).unwrap();
+        let uv_python = base.join(".uv-python");
+        fs::create_dir_all(uv_python.join("cpython-latest")).unwrap();
+        let err = find_python_dir(&base, &venv, None, &None).unwrap_err();
+        assert!(err.starts_with("Python not found in .uv-python directory."));
+
+        // The single valid directory is taken as-is.
+        fs::create_dir_all(uv_python.join("cpython-3.12.4-windows-x86_64-none")).unwrap();
+        fs::create_dir_all(uv_python.join("cpython-3.99.x")).unwrap();
+        let log_path = Some(base.join("launcher.log"));
+        let selected = find_python_dir(&base, &venv, None, &log_path).unwrap();
+        assert_eq!(
+            selected,
+            uv_python.join("cpython-3.12.4-windows-x86_64-none")
+        );
+        let log = fs::read_to_string(base.join("launcher.log")).unwrap();
+        assert!(log.contains("Skipping \"cpython-latest\""));
+        assert!(log.contains("Skipping \"cpython-3.99.x\""));
+        assert!(!log.contains("Multiple Python dirs"));
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn python_version_setting_matches_given_components() {
+        assert!(version_matches((3, 11, 9), "3"));
+        assert!(version_matches((3, 11, 9), "3.11"));
+        assert!(version_matches((3, 11, 9), " 3.11.9 "));
+        assert!(!version_matches((3, 11, 9), "3.1"));
+        assert!(!version_matches((3, 11, 9), "3.11.9.1"));
+        assert!(!version_matches((3, 11, 9), "latest"));
+    }
+
+    #[test]
+    fn fix_pyvenv_cfg_rewrites_home_and_keeps_other_lines() {
+        let base = temp_dir("pyvenv-fix");
+        let venv = base.join(".venv");
+        fs::create_dir_all(&venv).unwrap();
+        let cfg = venv.join("pyvenv.cfg");
+        fs::write(
+            &cfg,
+            "home = C:\\old\\python\r\ninclude-system-site-packages = false\r\nprompt = yakulingo\r\n",
+        )
+        .unwrap();
+        let python_dir = base.join("python");
+
+        fix_pyvenv_cfg(&venv, &python_dir).unwrap();
+
+        let content = fs::read_to_string(&cfg).unwrap();
+        assert_eq!(
+            content,
+            format!(
+                "home = {}\r\ninclude-system-site-packages = false\r\nprompt = yakulingo\r\n",
+                python_dir.display()
+            )
+        );
+        assert_eq!(read_pyvenv_home(&venv), Some(python_dir));
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn unknown_pyvenv_keys_survive_the_rewrite() {
+        let original = "home = /opt/old\n\
+                        implementation = CPython\n\
+                        uv = 0.4.18\n\
+                        Include-System-Site-Packages=FALSE\n\
+                        version_info = 3.11.9\n\
+                        homebrew = keep me\n\
+                        # comment\n\
+                        executable = /opt/old/bin/python3.11\n\
+                        prompt = yakulingo\n";
+        let rewritten = rewrite_pyvenv_cfg(original, "/opt/new");
+        assert_eq!(
+            rewritten,
+            original
+                .replace("home = /opt/old", "home = /opt/new")
+                .replace(
+                    "Include-System-Site-Packages=FALSE",
+                    "include-system-site-packages = false"
+                )
+        );
+        // Already normalized: nothing to write.
+        assert_eq!(rewrite_pyvenv_cfg(&rewritten, "/opt/new"), rewritten);
+        assert_eq!(
+            rewrite_pyvenv_cfg("prompt = x", "/opt/new"),
+            "home = /opt/new\nprompt = x"
+        );
+    }
+
+    #[test]
+    fn pyvenv_rewrite_keeps_a_backup_and_no_temp_file() {
+        let base = temp_dir("pyvenv-backup");
+        let venv = base.join(".venv");
+        fs::create_dir_all(&venv).unwrap();
+        let cfg = venv.join("pyvenv.cfg");
+        let original = "homebrew = x\nhome = /opt/old\nuv = 0.4.18\n";
+        fs::write(&cfg, original).unwrap();
+        let python_dir = base.join("python");
+        assert_eq!(
+            check_pyvenv_cfg(&venv, &python_dir).as_deref(),
+            Ok("needs patching (writable)")
+        );
+        assert_eq!(fs::read_to_string(&cfg).unwrap(), original);
+
+        fix_pyvenv_cfg(&venv, &python_dir).unwrap();
+
+        assert_eq!(
+            check_pyvenv_cfg(&venv, &python_dir).as_deref(),
+            Ok("home is current")
+        );
+        assert_eq!(read_pyvenv_home(&venv), Some(python_dir.clone()));
+        assert_eq!(
+            fs::read_to_string(venv.join("pyvenv.cfg.bak")).unwrap(),
+            original
+        );
+        assert!(!venv.join("pyvenv.cfg.tmp").exists());
+        let rewritten = fs::read_to_string(&cfg).unwrap();
+        assert!(rewritten.starts_with("homebrew = x\n"));
+        assert!(rewritten.ends_with("uv = 0.4.18\n"));
+
+        // A correct file is left alone, backup included.
+        fs::remove_file(venv.join("pyvenv.cfg.bak")).unwrap();
+        fix_pyvenv_cfg(&venv, &python_dir).unwrap();
+        assert!(!venv.join("pyvenv.cfg.bak").exists());
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn python_info_is_cached_per_interpreter_path_and_mtime() {
+        use std::cell::Cell;
+
+        let dir = temp_dir("python-info");
+        let python_exe = dir.join("python.exe");
+        let context_path = dir.join("state").join("launcher_context.json");
+        fs::write(&python_exe, "").unwrap();
+        fs::create_dir_all(dir.join("state")).unwrap();
+        fs::write(&context_path, "{\"other\": 1}").unwrap();
+        let queries = Cell::new(0);
+        let query = |_: &Path| {
+            queries.set(queries.get() + 1);
+            parse_python_info("3.12.4\n64bit\nC:\\YakuLingo\\.venv\\Scripts\\python.exe\n")
+        };
+
+        let info = resolve_python_info(&python_exe, &context_path, true, query, &None).unwrap();
+        assert_eq!(info.version, "3.12.4");
+        assert_eq!(info.arch, "64bit");
+        assert_eq!(queries.get(), 1, "miss: nothing cached");
+        assert_eq!(
+            resolve_python_info(&python_exe, &context_path, true, query, &None),
+            Some(info.clone())
+        );
+        assert_eq!(queries.get(), 1, "hit: same path and mtime");
+        resolve_python_info(&python_exe, &context_path, false, query, &None);
+        assert_eq!(queries.get(), 2, "cache bypassed");
+
+        fs::File::options()
+            .write(true)
+            .open(&python_exe)
+            .unwrap()
+            .set_modified(UNIX_EPOCH + Duration::from_secs(1_000_000))
+            .unwrap();
+        resolve_python_info(&python_exe, &context_path, true, query, &None);
+        assert_eq!(queries.get(), 3, "miss: interpreter replaced");
+
+        let other_exe = dir.join("python3.exe");
+        fs::write(&other_exe, "").unwrap();
+        resolve_python_info(&other_exe, &context_path, true, query, &None);
+        assert_eq!(queries.get(), 4, "miss: different interpreter");
+
+        let context: Value =
+            serde_json::from_str(&fs::read_to_string(&context_path).unwrap()).unwrap();
+        assert_eq!(context["other"], 1, "unrelated keys are kept");
+
+        assert_eq!(
+            resolve_python_info(&dir.join("missing.exe"), &context_path, true, query, &None),
+            None
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn parse_python_info_needs_all_lines() {
+        assert_eq!(parse_python_info("3.12.4\n64bit\n"), None);
+        assert_eq!(parse_python_info(""), None);
+    }
+
+    #[test]
+    fn app_execution_alias_stub_is_refused() {
+        let stub = ExeTraits {
+            len: 0,
+            reparse_tag: Some(IO_REPARSE_TAG_APPEXECLINK),
+        };
+        assert!(is_app_execution_alias(&stub));
+        assert!(
+            check_not_store_stub(Path::new("python.exe"), Some(stub), &None)
+                .unwrap_err()
+                .contains("Microsoft Store")
+        );
+        assert!(
+            is_app_execution_alias(&ExeTraits {
+                len: 0,
+                reparse_tag: Some(UNREADABLE_REPARSE_TAG),
+            }),
+            "zero-byte reparse point with an unreadable tag"
+        );
+    }
+
+    #[test]
+    fn regular_interpreters_and_links_are_accepted() {
+        const IO_REPARSE_TAG_SYMLINK: u32 = 0xA000000C;
+        let regular = ExeTraits {
+            len: 104_448,
+            reparse_tag: None,
+        };
+        // symlink_metadata reports a length of 0 for symlinks.
+        let symlink = ExeTraits {
+            len: 0,
+            reparse_tag: Some(IO_REPARSE_TAG_SYMLINK),
+        };
+        assert!(!is_app_execution_alias(&regular));
+        assert!(!is_app_execution_alias(&symlink));
+        assert_eq!(
+            check_not_store_stub(Path::new("python.exe"), Some(regular), &None),
+            Ok(())
+        );
+        assert_eq!(
+            check_not_store_stub(Path::new("python.exe"), None, &None),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! Publishing resolved directories to `Software\YakuLingo` for other tools.
+//!
+//! Installers and support utilities read `StateDir`, `LogDir` and
+//! `InstallDir` instead of re-implementing the launcher's path resolution.
+//! Everything here is best-effort: failures are logged, never fatal.
+//!
+//! The one read is the administrator policy under
+//! `HKLM\Software\Policies\YakuLingo`, a GPO-driven kill switch.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::log::log_event;
+
+pub const REGISTRY_KEY: &str = "Software\\YakuLingo";
+pub const POLICY_KEY: &str = "Software\\Policies\\YakuLingo";
+pub const POLICY_DISABLED_MESSAGE: &str = "YakuLingo is disabled by administrator policy.";
+
+/// Which registry hives receive the directory values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PublishLocations {
+    Off,
+    /// `HKCU\Software\YakuLingo`.
+    #[default]
+    User,
+    /// `HKCU` and `HKLM\Software\YakuLingo` (HKLM needs write access).
+    Machine,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistryRoot {
+    CurrentUser,
+    LocalMachine,
+}
+
+impl PublishLocations {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "off" | "0" => Some(PublishLocations::Off),
+            "user" => Some(PublishLocations::User),
+            "machine" => Some(PublishLocations::Machine),
+            _ => None,
+        }
+    }
+
+    pub fn roots(self) -> &'static [RegistryRoot] {
+        match self {
+            PublishLocations::Off => &[],
+            PublishLocations::User => &[RegistryRoot::CurrentUser],
+            PublishLocations::Machine => &[RegistryRoot::CurrentUser, RegistryRoot::LocalMachine],
+        }
+    }
+}
+
+/// String-value writes, behind a seam so the published values can be tested.
+pub trait RegistryWriter {
+    fn set_string(
+        &mut self,
+        root: RegistryRoot,
+        key: &str,
+        name: &str,
+        value: &str,
+    ) -> Result<(), String>;
+}
+
+/// Write the directory values under every root selected by `locations`.
+pub fn publish_locations<W: RegistryWriter>(
+    writer: &mut W,
+    locations: PublishLocations,
+    install_dir: &Path,
+    state_dir: &Path,
+    log_path: &Option<PathBuf>,
+) {
+    let log_dir = log_path.as_ref().and_then(|path| path.parent());
+    let mut values = vec![("InstallDir", install_dir), ("StateDir", state_dir)];
+    if let Some(dir) = log_dir {
+        values.push(("LogDir", dir));
+    }
+
+    for &root in locations.roots() {
+        for (name, dir) in &values {
+            if let Err(err) = writer.set_string(root, REGISTRY_KEY, name, &dir.to_string_lossy()) {
+                log_event(
+                    log_path,
+                    &format!("Failed to publish {} to {:?}: {}", name, root, err),
+                );
+                break;
+            }
+        }
+    }
+}
+
+/// Writes to the real registry.
+#[cfg(windows)]
+pub struct Win32Registry;
+
+#[cfg(windows)]
+impl RegistryWriter for Win32Registry {
+    fn set_string(
+        &mut self,
+        root: RegistryRoot,
+        key: &str,
+        name: &str,
+        value: &str,
+    ) -> Result<(), String> {
+        use std::ffi::OsStr;
+        use std::iter::once;
+        use std::os::windows::ffi::OsStrExt;
+        use std::ptr::null_mut;
+        use winapi::shared::minwindef::HKEY;
+        use winapi::um::winnt::{KEY_SET_VALUE, REG_OPTION_NON_VOLATILE, REG_SZ};
+        use winapi::um::winreg::{
+            RegCloseKey, RegCreateKeyExW, RegSetValueExW, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE,
+        };
+
+        fn wide(value: &str) -> Vec<u16> {
+            OsStr::new(value).encode_wide().chain(once(0)).collect()
+        }
+
+        let hive = match root {
+            RegistryRoot::CurrentUser => HKEY_CURRENT_USER,
+            RegistryRoot::LocalMachine => HKEY_LOCAL_MACHINE,
+        };
+        let key = wide(key);
+        let name = wide(name);
+        let data = wide(value);
+        let mut handle: HKEY = null_mut();
+
+        unsafe {
+            let status = RegCreateKeyExW(
+                hive,
+                key.as_ptr(),
+                0,
+                null_mut(),
+                REG_OPTION_NON_VOLATILE,
+                KEY_SET_VALUE,
+                null_mut(),
+                &mut handle,
+                null_mut(),
+            );
+            if status != 0 {
+                return Err(format!("RegCreateKeyExW failed ({})", status));
+            }
+            let status = RegSetValueExW(
+                handle,
+                name.as_ptr(),
+                0,
+                REG_SZ,
+                data.as_ptr() as *const u8,
+                (data.len() * 2) as u32,
+            );
+            RegCloseKey(handle);
+            if status != 0 {
+                return Err(format!("RegSetValueExW failed ({})", status));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Map the `Enabled` policy value to a launch decision: only an explicit 0
+/// disables the app, an absent value means enabled.
+pub fn check_launch_policy(enabled: Option<u32>) -> Result<(), String> {
+    match enabled {
+        Some(0) => Err(POLICY_DISABLED_MESSAGE.to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// `HKLM\Software\Policies\YakuLingo\Enabled` (DWORD), if set.
+#[cfg(windows)]
+pub fn read_policy_enabled() -> Option<u32> {
+    use std::ffi::OsStr;
+    use std::iter::once;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::null_mut;
+    use winapi::um::winreg::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_DWORD};
+
+    /// Read the 64-bit view even from a 32-bit build.
+    const RRF_SUBKEY_WOW6464KEY: u32 = 0x0001_0000;
+
+    fn wide(value: &str) -> Vec<u16> {
+        OsStr::new(value).encode_wide().chain(once(0)).collect()
+    }
+
+    let key = wide(POLICY_KEY);
+    let name = wide("Enabled");
+    let mut value = 0u32;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            key.as_ptr(),
+            name.as_ptr(),
+            RRF_RT_REG_DWORD | RRF_SUBKEY_WOW6464KEY,
+            null_mut(),
+            &mut value as *mut u32 as *mut _,
+            &mut size,
+        )
+    };
+    if status != 0 {
+        return None;
+    }
+    Some(value)
+}
+
+/// No policy outside Windows.
+#[cfg(not(windows))]
+pub fn read_policy_enabled() -> Option<u32> {
+    None
+}
+
+/// Publish to the real registry (no-op outside Windows).
+pub fn publish_to_registry(
+    locations: PublishLocations,
+    install_dir: &Path,
+    state_dir: &Path,
+    log_path: &Option<PathBuf>,
+) {
+    #[cfg(windows)]
+    publish_locations(
+        &mut Win32Registry,
+        locations,
+        install_dir,
+        state_dir,
+        log_path,
+    );
+    #[cfg(not(windows))]
+    let _ = (locations, install_dir, state_dir, log_path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingWriter {
+        writes: Vec<(RegistryRoot, String, String, String)>,
+        fail_root: Option<RegistryRoot>,
+    }
+
+    impl RegistryWriter for RecordingWriter {
+        fn set_string(
+            &mut self,
+            root: RegistryRoot,
+            key: &str,
+            name: &str,
+            value: &str,
+        ) -> Result<(), String> {
+            if self.fail_root == Some(root) {
+                return Err("access denied".to_string());
+            }
+            self.writes
+                .push((root, key.to_string(), name.to_string(), value.to_string()));
+            Ok(())
+        }
+    }
+
+    fn publish(writer: &mut RecordingWriter, locations: PublishLocations) {
+        let log_path = Some(PathBuf::from("logs").join("launcher.log"));
+        publish_locations(
+            writer,
+            locations,
+            Path::new("install"),
+            Path::new("state"),
+            &log_path,
+        );
+    }
+
+    #[test]
+    fn user_scope_writes_hkcu_values() {
+        let mut writer = RecordingWriter::default();
+        publish(&mut writer, PublishLocations::User);
+
+        let names: Vec<_> = writer
+            .writes
+            .iter()
+            .map(|(root, key, name, value)| {
+                assert_eq!(*root, RegistryRoot::CurrentUser);
+                assert_eq!(key, REGISTRY_KEY);
+                (name.as_str(), value.as_str())
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("InstallDir", "install"),
+                ("StateDir", "state"),
+                ("LogDir", "logs")
+            ]
+        );
+    }
+
+    #[test]
+    fn machine_scope_survives_hklm_failure() {
+        let mut writer = RecordingWriter {
+            fail_root: Some(RegistryRoot::LocalMachine),
+            ..Default::default()
+        };
+        publish(&mut writer, PublishLocations::Machine);
+        assert_eq!(writer.writes.len(), 3);
+
+        let mut writer = RecordingWriter::default();
+        publish(&mut writer, PublishLocations::Machine);
+        assert_eq!(writer.writes.len(), 6);
+        assert_eq!(writer.writes[3].0, RegistryRoot::LocalMachine);
+    }
+
+    #[test]
+    fn only_an_explicit_zero_disables_launch() {
+        assert_eq!(check_launch_policy(None), Ok(()));
+        assert_eq!(check_launch_policy(Some(1)), Ok(()));
+        assert_eq!(check_launch_policy(Some(2)), Ok(()));
+        let err = check_launch_policy(Some(0)).unwrap_err();
+        assert!(err.contains("disabled by administrator policy"));
+    }
+
+    #[test]
+    fn off_writes_nothing() {
+        let mut writer = RecordingWriter::default();
+        publish(&mut writer, PublishLocations::Off);
+        assert!(writer.writes.is_empty());
+    }
+}
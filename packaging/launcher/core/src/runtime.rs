@@ -0,0 +1,226 @@
+//! Install integrity checks run before the app is started.
+//!
+//! An interrupted update can leave the venv half-extracted or the bundled
+//! CPython incomplete. Python then dies at once and the watchdog spends its
+//! restarts on an exit code that explains nothing. `verify_runtime` looks
+//! for the files every working install has and, when the package ships
+//! `runtime_manifest.json`, checks the listed files and sizes too. A failed
+//! check stops the launch with a reinstall message instead of a restart loop.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::config::LauncherConfig;
+use crate::entrypoint::resolve_entrypoint;
+
+pub const RUNTIME_MANIFEST_FILE_NAME: &str = "runtime_manifest.json";
+/// Problems listed in the error before the rest are summarized.
+const MAX_REPORTED_PROBLEMS: usize = 5;
+
+/// `runtime_manifest.json`: paths relative to the install dir, with their
+/// expected size in bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct RuntimeManifest {
+    pub files: BTreeMap<String, u64>,
+}
+
+/// Compare the files listed in `manifest` with what is on disk.
+pub fn check_manifest(base_dir: &Path, manifest: &RuntimeManifest) -> Vec<String> {
+    manifest
+        .files
+        .iter()
+        .filter_map(
+            |(relative, expected)| match fs::metadata(base_dir.join(relative)) {
+                Ok(meta) if meta.len() == *expected => None,
+                Ok(meta) => Some(format!(
+                    "{} is {} bytes, expected {}",
+                    relative,
+                    meta.len(),
+                    expected
+                )),
+                Err(_) => Some(format!("{} is missing", relative)),
+            },
+        )
+        .collect()
+}
+
+fn check_non_empty_file(path: &Path, label: &str, problems: &mut Vec<String>) {
+    match fs::metadata(path) {
+        Ok(meta) if meta.is_file() && meta.len() > 0 => {}
+        Ok(_) => problems.push(format!("{} is empty ({})", label, path.display())),
+        Err(_) => problems.push(format!("{} is missing ({})", label, path.display())),
+    }
+}
+
+/// Whether `site_packages` holds at least one installed distribution.
+fn has_installed_packages(site_packages: &Path) -> bool {
+    fs::read_dir(site_packages).is_ok_and(|entries| {
+        entries
+            .flatten()
+            .any(|entry| entry.file_name().to_string_lossy().ends_with(".dist-info"))
+    })
+}
+
+/// Every problem found with the install; empty when it looks launchable.
+pub fn runtime_problems(
+    base_dir: &Path,
+    python_dir: &Path,
+    venv_dir: &Path,
+    config: &LauncherConfig,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    check_non_empty_file(
+        &venv_dir.join("Scripts").join("python.exe"),
+        "venv python.exe",
+        &mut problems,
+    );
+    check_non_empty_file(
+        &python_dir.join("python.exe"),
+        "Python runtime",
+        &mut problems,
+    );
+    check_non_empty_file(
+        &python_dir.join("Lib").join("os.py"),
+        "Python standard library",
+        &mut problems,
+    );
+    let site_packages = venv_dir.join("Lib").join("site-packages");
+    if !has_installed_packages(&site_packages) {
+        problems.push(format!(
+            "no packages installed in {}",
+            site_packages.display()
+        ));
+    }
+    if let Err(err) = resolve_entrypoint(
+        base_dir,
+        config.entrypoint.as_deref(),
+        config.entry_module.as_deref(),
+    ) {
+        problems.push(err.lines().next().unwrap_or_default().to_string());
+    }
+
+    let manifest_path = base_dir.join(RUNTIME_MANIFEST_FILE_NAME);
+    if let Ok(content) = fs::read_to_string(&manifest_path) {
+        match serde_json::from_str::<RuntimeManifest>(&content) {
+            Ok(manifest) => problems.extend(check_manifest(base_dir, &manifest)),
+            Err(err) => problems.push(format!(
+                "{} is invalid: {}",
+                RUNTIME_MANIFEST_FILE_NAME, err
+            )),
+        }
+    }
+    problems
+}
+
+/// `Err` with a user-facing reinstall message when the install is broken.
+pub fn verify_runtime(
+    base_dir: &Path,
+    python_dir: &Path,
+    venv_dir: &Path,
+    config: &LauncherConfig,
+) -> Result<(), String> {
+    let problems = runtime_problems(base_dir, python_dir, venv_dir, config);
+    if problems.is_empty() {
+        return Ok(());
+    }
+    let mut listed: Vec<String> = problems
+        .iter()
+        .take(MAX_REPORTED_PROBLEMS)
+        .map(|problem| format!("- {}", problem))
+        .collect();
+    if problems.len() > MAX_REPORTED_PROBLEMS {
+        listed.push(format!(
+            "- and {} more",
+            problems.len() - MAX_REPORTED_PROBLEMS
+        ));
+    }
+    Err(format!(
+        "The YakuLingo installation appears to be corrupted:\n{}\n\nPlease reinstall the application.",
+        listed.join("\n")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::path::PathBuf;
+
+    /// A minimal install that passes every check.
+    fn install(name: &str) -> (PathBuf, PathBuf, PathBuf) {
+        let base = temp_dir(name);
+        let venv = base.join(".venv");
+        let python = base.join(".uv-python").join("cpython-3.11.9");
+        fs::create_dir_all(venv.join("Scripts")).unwrap();
+        fs::write(venv.join("Scripts").join("python.exe"), "MZ").unwrap();
+        let dist_info = venv
+            .join("Lib")
+            .join("site-packages")
+            .join("nicegui-2.0.dist-info");
+        fs::create_dir_all(dist_info).unwrap();
+        fs::create_dir_all(python.join("Lib")).unwrap();
+        fs::write(python.join("python.exe"), "MZ").unwrap();
+        fs::write(python.join("Lib").join("os.py"), "import abc\n").unwrap();
+        fs::write(base.join("app.py"), "print()\n").unwrap();
+        (base, python, venv)
+    }
+
+    #[test]
+    fn complete_install_passes() {
+        let (base, python, venv) = install("runtime-ok");
+        assert_eq!(
+            verify_runtime(&base, &python, &venv, &LauncherConfig::default()),
+            Ok(())
+        );
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn missing_app_py_is_reported() {
+        let (base, python, venv) = install("runtime-no-app");
+        fs::remove_file(base.join("app.py")).unwrap();
+        let err = verify_runtime(&base, &python, &venv, &LauncherConfig::default()).unwrap_err();
+        assert!(err.contains("appears to be corrupted"));
+        assert!(err.contains("- app.py not found."));
+        assert!(err.ends_with("Please reinstall the application."));
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn empty_site_packages_is_reported() {
+        let (base, python, venv) = install("runtime-empty-site");
+        let site_packages = venv.join("Lib").join("site-packages");
+        fs::remove_dir_all(&site_packages).unwrap();
+        fs::create_dir_all(&site_packages).unwrap();
+        let problems = runtime_problems(&base, &python, &venv, &LauncherConfig::default());
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("no packages installed in"));
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn manifest_sizes_are_checked() {
+        let (base, python, venv) = install("runtime-manifest");
+        fs::write(
+            base.join(RUNTIME_MANIFEST_FILE_NAME),
+            r#"{"files": {"app.py": 8, ".venv/Scripts/python.exe": 3, "lib/missing.py": 1}}"#,
+        )
+        .unwrap();
+        let problems = runtime_problems(&base, &python, &venv, &LauncherConfig::default());
+        assert_eq!(
+            problems,
+            vec![
+                ".venv/Scripts/python.exe is 2 bytes, expected 3".to_string(),
+                "lib/missing.py is missing".to_string(),
+            ]
+        );
+
+        fs::write(base.join(RUNTIME_MANIFEST_FILE_NAME), "{").unwrap();
+        let problems = runtime_problems(&base, &python, &venv, &LauncherConfig::default());
+        assert!(problems[0].starts_with("runtime_manifest.json is invalid"));
+        let _ = fs::remove_dir_all(&base);
+    }
+}
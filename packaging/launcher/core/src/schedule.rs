@@ -0,0 +1,131 @@
+//! Daily maintenance window during which the watchdog does not restart the app.
+
+use std::fmt;
+
+const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+/// `HH:MM-HH:MM` in local time; `start > end` crosses midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenanceWindow {
+    start_min: u32,
+    end_min: u32,
+}
+
+fn parse_hhmm(value: &str) -> Option<u32> {
+    let (hours, minutes) = value.trim().split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    Some(hours * 60 + minutes)
+}
+
+impl MaintenanceWindow {
+    /// Parse `"02:00-03:00"`. Empty windows (`start == end`) are rejected.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (start, end) = value.split_once('-')?;
+        let window = MaintenanceWindow {
+            start_min: parse_hhmm(start)?,
+            end_min: parse_hhmm(end)?,
+        };
+        (window.start_min != window.end_min).then_some(window)
+    }
+
+    /// Whether `second_of_day` (local, `0..86400`) falls inside the window.
+    pub fn contains(&self, second_of_day: u32) -> bool {
+        let start = self.start_min * 60;
+        let end = self.end_min * 60;
+        if start < end {
+            (start..end).contains(&second_of_day)
+        } else {
+            second_of_day >= start || second_of_day < end
+        }
+    }
+
+    /// Seconds from `second_of_day` until the window closes.
+    pub fn seconds_until_end(&self, second_of_day: u32) -> u32 {
+        let end = self.end_min * 60;
+        (end + SECONDS_PER_DAY - second_of_day % SECONDS_PER_DAY) % SECONDS_PER_DAY
+    }
+}
+
+impl fmt::Display for MaintenanceWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}-{:02}:{:02}",
+            self.start_min / 60,
+            self.start_min % 60,
+            self.end_min / 60,
+            self.end_min % 60
+        )
+    }
+}
+
+/// Current local time as seconds since midnight.
+#[cfg(windows)]
+pub fn local_second_of_day() -> u32 {
+    use winapi::um::minwinbase::SYSTEMTIME;
+    use winapi::um::sysinfoapi::GetLocalTime;
+
+    let mut now: SYSTEMTIME = unsafe { std::mem::zeroed() };
+    unsafe { GetLocalTime(&mut now) };
+    now.wHour as u32 * 3600 + now.wMinute as u32 * 60 + now.wSecond as u32
+}
+
+/// Current time as seconds since midnight. Without a timezone database this
+/// is UTC; the launcher only ships on Windows.
+#[cfg(not(windows))]
+pub fn local_second_of_day() -> u32 {
+    (crate::crash::now_secs() % SECONDS_PER_DAY as u64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hours: u32, minutes: u32) -> u32 {
+        hours * 3600 + minutes * 60
+    }
+
+    #[test]
+    fn parses_and_rejects_windows() {
+        let window = MaintenanceWindow::parse("02:00-03:30").unwrap();
+        assert_eq!(window.to_string(), "02:00-03:30");
+        assert_eq!(
+            MaintenanceWindow::parse(" 2:00 - 3:00 ")
+                .unwrap()
+                .to_string(),
+            "02:00-03:00"
+        );
+        assert_eq!(MaintenanceWindow::parse("02:00-02:00"), None);
+        assert_eq!(MaintenanceWindow::parse("24:00-01:00"), None);
+        assert_eq!(MaintenanceWindow::parse("02:00"), None);
+        assert_eq!(MaintenanceWindow::parse("nightly"), None);
+    }
+
+    #[test]
+    fn same_day_window() {
+        let window = MaintenanceWindow::parse("02:00-03:00").unwrap();
+        assert!(!window.contains(at(1, 59)));
+        assert!(window.contains(at(2, 0)));
+        assert!(window.contains(at(2, 59)));
+        assert!(!window.contains(at(3, 0)));
+        assert!(!window.contains(at(14, 0)));
+        assert_eq!(window.seconds_until_end(at(2, 15)), 45 * 60);
+    }
+
+    #[test]
+    fn window_crossing_midnight() {
+        let window = MaintenanceWindow::parse("23:30-00:30").unwrap();
+        assert!(window.contains(at(23, 45)));
+        assert!(window.contains(at(0, 0)));
+        assert!(window.contains(at(0, 29)));
+        assert!(!window.contains(at(0, 30)));
+        assert!(!window.contains(at(23, 29)));
+        assert!(!window.contains(at(12, 0)));
+        assert_eq!(window.seconds_until_end(at(23, 45)), 45 * 60);
+        assert_eq!(window.seconds_until_end(at(0, 10)), 20 * 60);
+    }
+}
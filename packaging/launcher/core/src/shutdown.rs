@@ -0,0 +1,343 @@
+//! Stopping the app when the launcher itself is told to exit.
+//!
+//! The app runs with its own hidden console, so Ctrl+C, closing the console
+//! the launcher was started from, or a logoff ends the launcher but not the
+//! app, which keeps its port and looks like a running instance to the next
+//! launch. The watchdog therefore records the PID it is waiting on, and a
+//! handler terminates that process before the launcher exits. The PID is
+//! cleared as soon as the app exits, so a successor it started itself (an
+//! update relaunch, a handoff) is never touched.
+//!
+//! On Windows the launcher is a GUI-subsystem program and has a console only
+//! with `--foreground` / `--verbose`, so two handlers are installed: a
+//! console control handler (Ctrl+C, closing that console) and a hidden
+//! top-level window that sees `WM_ENDSESSION` on logoff and shutdown with or
+//! without a console. (A message-only window would not get that broadcast.)
+//! Unix uses a signal handler for SIGINT / SIGTERM / SIGHUP.
+//!
+//! On Windows the handlers then run the hooks registered with
+//! [`on_shutdown`] (the `--foreground` tailer's final flush): the launcher is
+//! ended without unwinding, so nothing is dropped. Unix signal handlers stay
+//! async-signal-safe and skip them.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, Once, OnceLock, PoisonError};
+
+/// PID the watchdog is waiting on; 0 when none.
+static TRACKED_CHILD: AtomicU32 = AtomicU32::new(0);
+/// Where the handler logs what it did (Windows; Unix signal handlers must
+/// not allocate).
+static HANDLER_LOG: OnceLock<Option<PathBuf>> = OnceLock::new();
+/// Work to finish before a console event ends the launcher.
+type ShutdownHook = Box<dyn FnOnce() + Send>;
+static SHUTDOWN_HOOKS: Mutex<Vec<ShutdownHook>> = Mutex::new(Vec::new());
+
+/// Marks `pid` as the app to stop on exit until dropped.
+#[derive(Debug)]
+pub struct TrackedChild(u32);
+
+impl TrackedChild {
+    pub fn new(pid: u32) -> Self {
+        TRACKED_CHILD.store(pid, Ordering::SeqCst);
+        TrackedChild(pid)
+    }
+}
+
+impl Drop for TrackedChild {
+    fn drop(&mut self) {
+        let _ = TRACKED_CHILD.compare_exchange(self.0, 0, Ordering::SeqCst, Ordering::SeqCst);
+    }
+}
+
+/// The app PID currently tracked, if any.
+pub fn tracked_child() -> Option<u32> {
+    match TRACKED_CHILD.load(Ordering::SeqCst) {
+        0 => None,
+        pid => Some(pid),
+    }
+}
+
+/// Terminate the tracked app, returning its PID if one was tracked.
+pub fn terminate_tracked_child() -> Option<u32> {
+    let pid = TRACKED_CHILD.swap(0, Ordering::SeqCst);
+    if pid == 0 {
+        return None;
+    }
+    terminate_process(pid);
+    Some(pid)
+}
+
+/// Run `hook` when the shutdown handler fires.
+pub fn on_shutdown(hook: impl FnOnce() + Send + 'static) {
+    SHUTDOWN_HOOKS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(Box::new(hook));
+}
+
+/// Run (and forget) the registered hooks, in registration order.
+#[cfg(any(windows, test))]
+fn run_shutdown_hooks() {
+    let hooks = std::mem::take(
+        &mut *SHUTDOWN_HOOKS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner),
+    );
+    for hook in hooks {
+        hook();
+    }
+}
+
+#[cfg(windows)]
+fn terminate_process(pid: u32) {
+    use winapi::shared::minwindef::FALSE;
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::{OpenProcess, TerminateProcess};
+    use winapi::um::winnt::PROCESS_TERMINATE;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_TERMINATE, FALSE, pid);
+        if !handle.is_null() {
+            TerminateProcess(handle, 1);
+            CloseHandle(handle);
+        }
+    }
+}
+
+#[cfg(unix)]
+pub(crate) mod signals {
+    /// `errno` when the process exists but may not be signalled.
+    pub const EPERM: i32 = 1;
+    pub const SIGHUP: i32 = 1;
+    pub const SIGINT: i32 = 2;
+    pub const SIGTERM: i32 = 15;
+    /// `SIG_DFL`.
+    pub const DEFAULT_HANDLER: usize = 0;
+
+    extern "C" {
+        pub fn signal(signum: i32, handler: usize) -> usize;
+        pub fn kill(pid: i32, sig: i32) -> i32;
+        pub fn raise(sig: i32) -> i32;
+    }
+}
+
+#[cfg(unix)]
+fn terminate_process(pid: u32) {
+    if let Ok(pid) = i32::try_from(pid) {
+        unsafe {
+            signals::kill(pid, signals::SIGTERM);
+        }
+    }
+}
+
+#[cfg(not(any(windows, unix)))]
+fn terminate_process(_pid: u32) {}
+
+/// Install the handler for the rest of the launcher's life; later calls do
+/// nothing. The watchdog needs it to stop the app (a detached app is meant to
+/// outlive the launcher), `--foreground` to flush its tailer.
+pub fn install_shutdown_handler(log_path: &Option<PathBuf>) {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| install(log_path));
+}
+
+/// What either Windows handler does before the launcher is ended.
+#[cfg(windows)]
+fn stop_before_exit(event: &str) {
+    if let Some(pid) = terminate_tracked_child() {
+        let log_path = HANDLER_LOG.get().cloned().flatten();
+        crate::log::log_event(
+            &log_path,
+            &format!("{} - terminated app (pid {})", event, pid),
+        );
+    }
+    run_shutdown_hooks();
+}
+
+#[cfg(windows)]
+fn install(log_path: &Option<PathBuf>) {
+    use winapi::shared::minwindef::{BOOL, DWORD, FALSE, TRUE};
+    use winapi::um::consoleapi::SetConsoleCtrlHandler;
+
+    unsafe extern "system" fn handler(ctrl_type: DWORD) -> BOOL {
+        stop_before_exit(&format!("Console control event {}", ctrl_type));
+        // Let the default handler end the launcher.
+        FALSE
+    }
+
+    let _ = HANDLER_LOG.set(log_path.clone());
+    unsafe {
+        if SetConsoleCtrlHandler(Some(handler), TRUE) == 0 {
+            crate::log::log_event(log_path, "Failed to install console control handler");
+        }
+    }
+    let session_log = log_path.clone();
+    let spawned = std::thread::Builder::new()
+        .name("session-end".to_string())
+        .spawn(move || unsafe { session_end::run(&session_log) });
+    if let Err(err) = spawned {
+        crate::log::log_event(
+            log_path,
+            &format!("Failed to start the session-end watcher: {}", err),
+        );
+    }
+}
+
+/// The hidden window that stops the app on logoff / shutdown.
+#[cfg(windows)]
+mod session_end {
+    use std::ffi::OsStr;
+    use std::iter::once;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::PathBuf;
+    use std::ptr::{null, null_mut};
+
+    use winapi::shared::minwindef::{LPARAM, LRESULT, TRUE, UINT, WPARAM};
+    use winapi::shared::windef::HWND;
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    use winapi::um::winuser::{
+        CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW,
+        TranslateMessage, MSG, WM_ENDSESSION, WM_QUERYENDSESSION, WNDCLASSW, WS_POPUP,
+    };
+
+    use crate::log::log_event;
+
+    fn wide(value: &str) -> Vec<u16> {
+        OsStr::new(value).encode_wide().chain(once(0)).collect()
+    }
+
+    /// Create the window (never shown) and pump its messages for the rest
+    /// of the launcher's life.
+    pub unsafe fn run(log_path: &Option<PathBuf>) {
+        let instance = GetModuleHandleW(null());
+        let class_name = wide("YakuLingoLauncherSession");
+        let class = WNDCLASSW {
+            lpfnWndProc: Some(window_proc),
+            hInstance: instance,
+            lpszClassName: class_name.as_ptr(),
+            ..std::mem::zeroed()
+        };
+        RegisterClassW(&class);
+        let hwnd = CreateWindowExW(
+            0,
+            class_name.as_ptr(),
+            class_name.as_ptr(),
+            WS_POPUP,
+            0,
+            0,
+            0,
+            0,
+            null_mut(),
+            null_mut(),
+            instance,
+            null_mut(),
+        );
+        if hwnd.is_null() {
+            log_event(
+                log_path,
+                &format!(
+                    "Failed to create the session-end window ({})",
+                    std::io::Error::last_os_error()
+                ),
+            );
+            return;
+        }
+
+        let mut message: MSG = std::mem::zeroed();
+        while GetMessageW(&mut message, null_mut(), 0, 0) > 0 {
+            TranslateMessage(&message);
+            DispatchMessageW(&message);
+        }
+    }
+
+    unsafe extern "system" fn window_proc(
+        hwnd: HWND,
+        message: UINT,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        match message {
+            // Never veto the logoff.
+            WM_QUERYENDSESSION => TRUE as LRESULT,
+            // The session really ends; the launcher may be killed once this
+            // returns.
+            WM_ENDSESSION => {
+                if wparam != 0 {
+                    super::stop_before_exit("Session ending");
+                }
+                0
+            }
+            _ => DefWindowProcW(hwnd, message, wparam, lparam),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn install(log_path: &Option<PathBuf>) {
+    extern "C" fn handler(sig: i32) {
+        // Async-signal-safe only: an atomic swap, kill, then the default
+        // action for the signal.
+        terminate_tracked_child();
+        unsafe {
+            signals::signal(sig, signals::DEFAULT_HANDLER);
+            signals::raise(sig);
+        }
+    }
+
+    let _ = HANDLER_LOG.set(log_path.clone());
+    for sig in [signals::SIGINT, signals::SIGTERM, signals::SIGHUP] {
+        unsafe {
+            signals::signal(sig, handler as extern "C" fn(i32) as usize);
+        }
+    }
+}
+
+#[cfg(not(any(windows, unix)))]
+fn install(_log_path: &Option<PathBuf>) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One test: the tracked PID is process-wide state.
+    #[test]
+    fn tracked_child_is_terminated_and_cleared() {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            use std::process::Command;
+
+            let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+            let tracked = TrackedChild::new(child.id());
+            assert_eq!(tracked_child(), Some(child.id()));
+            assert_eq!(terminate_tracked_child(), Some(child.id()));
+            assert_eq!(child.wait().unwrap().signal(), Some(signals::SIGTERM));
+            assert_eq!(tracked_child(), None);
+            drop(tracked);
+        }
+        assert_eq!(terminate_tracked_child(), None);
+
+        // A guard only clears its own PID.
+        let first = TrackedChild::new(1_000_001);
+        let second = TrackedChild::new(1_000_002);
+        drop(first);
+        assert_eq!(tracked_child(), Some(1_000_002));
+        drop(second);
+        assert_eq!(tracked_child(), None);
+    }
+
+    #[test]
+    fn shutdown_hooks_run_once_in_order() {
+        use std::sync::Arc;
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        for id in [1, 2] {
+            let calls = Arc::clone(&calls);
+            on_shutdown(move || calls.lock().unwrap().push(id));
+        }
+        run_shutdown_hooks();
+        run_shutdown_hooks();
+        assert_eq!(*calls.lock().unwrap(), vec![1, 2]);
+    }
+}
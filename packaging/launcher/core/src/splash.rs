@@ -0,0 +1,614 @@
+//! Optional startup splash (`splash = true`): a small window with an image
+//! and caption shown until the app window appears.
+//!
+//! White-label deployments drop `splash.png` (or `splash.bmp`) next to the
+//! exe, or name an image and caption with `splash_image` / `splash_caption`.
+//! Anything missing falls back to the built-in icon and caption. A first
+//! run shows it regardless, with the app's setup progress as the caption.
+//! The window never takes focus and is closed when the launcher's wait for
+//! the app ends (Windows only).
+
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_SPLASH_CAPTION: &str = "Starting YakuLingo…";
+/// Image files looked for beside the exe, in order.
+pub const SPLASH_IMAGE_NAMES: [&str; 2] = ["splash.png", "splash.bmp"];
+/// Larger images are scaled down to fit.
+#[cfg(any(windows, test))]
+const SPLASH_MAX_IMAGE: (u32, u32) = (480, 320);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplashAssets {
+    /// Custom image; `None` means the built-in icon.
+    pub image: Option<PathBuf>,
+    pub caption: String,
+}
+
+/// Resolve the splash assets. A configured image (relative to `base_dir`)
+/// wins when it exists, then the default file names; a blank caption is
+/// treated as unset.
+pub fn resolve_splash_assets(
+    base_dir: &Path,
+    configured_image: Option<&str>,
+    configured_caption: Option<&str>,
+) -> SplashAssets {
+    let configured_image = configured_image
+        .map(str::trim)
+        .filter(|path| !path.is_empty());
+    let image = configured_image
+        .into_iter()
+        .chain(SPLASH_IMAGE_NAMES)
+        .map(|name| base_dir.join(name))
+        .find(|path| path.is_file());
+    let caption = configured_caption
+        .map(str::trim)
+        .filter(|caption| !caption.is_empty())
+        .unwrap_or(DEFAULT_SPLASH_CAPTION)
+        .to_string();
+    SplashAssets { image, caption }
+}
+
+/// Scale `size` down (never up) to fit `max`, keeping the aspect ratio.
+#[cfg(any(windows, test))]
+fn fit_within(size: (u32, u32), max: (u32, u32)) -> (u32, u32) {
+    let (width, height) = size;
+    if width <= max.0 && height <= max.1 {
+        return size;
+    }
+    let scale = f64::min(
+        f64::from(max.0) / f64::from(width),
+        f64::from(max.1) / f64::from(height),
+    );
+    let scaled = |value: u32| ((f64::from(value) * scale).round() as u32).max(1);
+    (scaled(width), scaled(height))
+}
+
+/// Composite premultiplied BGRA pixels onto the white background, so
+/// transparent areas of a PNG do not come out black.
+#[cfg(any(windows, test))]
+fn flatten_on_white(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let uncovered = 255 - pixel[3];
+        for channel in &mut pixel[..3] {
+            *channel = channel.saturating_add(uncovered);
+        }
+        pixel[3] = 255;
+    }
+}
+
+/// Splash window running on its own thread; closed when dropped.
+#[cfg(windows)]
+pub struct SplashWindow {
+    /// `HWND` as an integer: the handle is used from the launcher thread.
+    hwnd: usize,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+#[cfg(not(windows))]
+pub struct SplashWindow;
+
+impl SplashWindow {
+    /// Replace the caption, e.g. with the setup progress.
+    #[cfg(windows)]
+    pub fn set_caption(&self, caption: &str) {
+        unsafe { win::post_caption(self.hwnd, caption) }
+    }
+
+    #[cfg(not(windows))]
+    pub fn set_caption(&self, _caption: &str) {}
+}
+
+#[cfg(windows)]
+impl Drop for SplashWindow {
+    fn drop(&mut self) {
+        unsafe {
+            winapi::um::winuser::PostMessageW(
+                self.hwnd as winapi::shared::windef::HWND,
+                winapi::um::winuser::WM_CLOSE,
+                0,
+                0,
+            );
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Show the splash without activating it. An image that cannot be decoded
+/// falls back to the icon; `None` when no window could be created.
+#[cfg(windows)]
+pub fn show_splash(assets: &SplashAssets, log_path: &Option<PathBuf>) -> Option<SplashWindow> {
+    use crate::log::log_event;
+
+    let image = assets
+        .image
+        .as_deref()
+        .and_then(|path| match unsafe { win::load_image(path) } {
+            Ok(image) => Some(image),
+            Err(err) => {
+                log_event(
+                    log_path,
+                    &format!("Splash image {:?} not shown: {}", path, err),
+                );
+                None
+            }
+        });
+    let caption = assets.caption.clone();
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let thread = std::thread::spawn(move || unsafe { win::run(image, &caption, sender) });
+    match receiver.recv() {
+        Ok(Ok(hwnd)) => Some(SplashWindow {
+            hwnd,
+            thread: Some(thread),
+        }),
+        Ok(Err(err)) => {
+            log_event(log_path, &format!("Splash window not shown: {}", err));
+            let _ = thread.join();
+            None
+        }
+        Err(_) => None,
+    }
+}
+
+#[cfg(not(windows))]
+pub fn show_splash(_assets: &SplashAssets, _log_path: &Option<PathBuf>) -> Option<SplashWindow> {
+    None
+}
+
+#[cfg(windows)]
+mod win {
+    use super::{fit_within, flatten_on_white, SPLASH_MAX_IMAGE};
+    use std::ffi::OsStr;
+    use std::iter::once;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use std::ptr::{null, null_mut};
+    use std::sync::mpsc::Sender;
+    use winapi::shared::minwindef::{LPARAM, LRESULT, TRUE, UINT, WPARAM};
+    use winapi::shared::windef::{HBRUSH, HICON, HWND, RECT};
+    use winapi::shared::winerror::{HRESULT, SUCCEEDED};
+    use winapi::shared::wtypesbase::CLSCTX_INPROC_SERVER;
+    use winapi::um::combaseapi::{CoCreateInstance, CoInitializeEx, CoUninitialize};
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    use winapi::um::objbase::COINIT_APARTMENTTHREADED;
+    use winapi::um::unknwnbase::IUnknown;
+    use winapi::um::wincodec::{
+        CLSID_WICImagingFactory, GUID_WICPixelFormat32bppPBGRA, IWICBitmapSource,
+        IWICImagingFactory, WICConvertBitmapSource, WICDecodeMetadataCacheOnDemand,
+    };
+    use winapi::um::wingdi::{
+        CreateSolidBrush, DeleteObject, GetStockObject, SelectObject, SetBkMode, SetStretchBltMode,
+        StretchDIBits, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DEFAULT_GUI_FONT, DIB_RGB_COLORS,
+        HALFTONE, SRCCOPY, TRANSPARENT, WHITE_BRUSH,
+    };
+    use winapi::um::winnt::GENERIC_READ;
+    use winapi::um::winuser::*;
+    use winapi::Interface;
+
+    const PADDING: i32 = 24;
+    const ICON_SIZE: i32 = 64;
+    const CAPTION_HEIGHT: i32 = 40;
+    const MIN_WIDTH: i32 = 320;
+    /// Indeterminate progress bar along the bottom edge.
+    const BAR_HEIGHT: i32 = 4;
+    const BAR_SEGMENT: i32 = 80;
+    const BAR_STEP: i32 = 6;
+    const BAR_COLOR: u32 = 0x00F6_823B;
+    const BAR_TIMER_ID: usize = 1;
+    const BAR_TIMER_MS: UINT = 30;
+    /// Carries a boxed UTF-16 caption in `lparam`.
+    const WM_SET_CAPTION: UINT = WM_APP + 1;
+    /// Decoded images larger than this on either side are rejected.
+    const MAX_DECODE_SIDE: u32 = 8192;
+    /// `DI_NORMAL` (winapi 0.3 does not define it).
+    const DI_NORMAL: UINT = 0x0003;
+
+    /// Decoded image, top-down BGRA already composited onto white.
+    pub struct Image {
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+    }
+
+    /// What `WM_PAINT` draws; owned by the window thread.
+    struct Paint {
+        image: Option<Image>,
+        /// Size the image (or icon) is drawn at.
+        shown: (i32, i32),
+        caption: Vec<u16>,
+        /// Right edge of the moving bar segment.
+        bar_offset: i32,
+    }
+
+    fn wide(value: &str) -> Vec<u16> {
+        OsStr::new(value).encode_wide().chain(once(0)).collect()
+    }
+
+    fn check(hr: HRESULT, what: &str) -> Result<(), String> {
+        if SUCCEEDED(hr) {
+            Ok(())
+        } else {
+            Err(format!("{} failed (0x{:08X})", what, hr as u32))
+        }
+    }
+
+    /// Hand a new caption to the window thread, which takes ownership.
+    pub unsafe fn post_caption(hwnd: usize, caption: &str) {
+        let caption = Box::into_raw(Box::new(wide(caption)));
+        if PostMessageW(hwnd as HWND, WM_SET_CAPTION, 0, caption as LPARAM) == 0 {
+            drop(Box::from_raw(caption));
+        }
+    }
+
+    /// Releases a COM interface pointer.
+    struct Com<T>(*mut T);
+
+    impl<T> Drop for Com<T> {
+        fn drop(&mut self) {
+            if !self.0.is_null() {
+                unsafe {
+                    (*(self.0 as *mut IUnknown)).Release();
+                }
+            }
+        }
+    }
+
+    /// Decode a PNG or BMP with WIC.
+    pub unsafe fn load_image(path: &Path) -> Result<Image, String> {
+        let initialized = SUCCEEDED(CoInitializeEx(null_mut(), COINIT_APARTMENTTHREADED));
+        let image = decode(path);
+        if initialized {
+            CoUninitialize();
+        }
+        image
+    }
+
+    unsafe fn decode(path: &Path) -> Result<Image, String> {
+        let mut factory = Com::<IWICImagingFactory>(null_mut());
+        check(
+            CoCreateInstance(
+                &CLSID_WICImagingFactory,
+                null_mut(),
+                CLSCTX_INPROC_SERVER,
+                &IWICImagingFactory::uuidof(),
+                &mut factory.0 as *mut _ as *mut _,
+            ),
+            "Creating the WIC factory",
+        )?;
+        let path: Vec<u16> = path.as_os_str().encode_wide().chain(once(0)).collect();
+        let mut decoder = Com(null_mut());
+        check(
+            (*factory.0).CreateDecoderFromFilename(
+                path.as_ptr(),
+                null(),
+                GENERIC_READ,
+                WICDecodeMetadataCacheOnDemand,
+                &mut decoder.0,
+            ),
+            "Opening the image",
+        )?;
+        let mut frame = Com(null_mut());
+        check((*decoder.0).GetFrame(0, &mut frame.0), "Reading the image")?;
+        let mut converted = Com::<IWICBitmapSource>(null_mut());
+        check(
+            WICConvertBitmapSource(
+                &GUID_WICPixelFormat32bppPBGRA,
+                frame.0 as *const IWICBitmapSource,
+                &mut converted.0,
+            ),
+            "Converting the image",
+        )?;
+        let (mut width, mut height) = (0, 0);
+        check(
+            (*converted.0).GetSize(&mut width, &mut height),
+            "Reading the image size",
+        )?;
+        if width == 0 || height == 0 || width > MAX_DECODE_SIDE || height > MAX_DECODE_SIDE {
+            return Err(format!("unsupported size {}x{}", width, height));
+        }
+        let mut pixels = vec![0u8; width as usize * height as usize * 4];
+        check(
+            (*converted.0).CopyPixels(null(), width * 4, pixels.len() as UINT, pixels.as_mut_ptr()),
+            "Copying the image",
+        )?;
+        flatten_on_white(&mut pixels);
+        Ok(Image {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Create the window, report its handle (or why it failed) on `ready`,
+    /// then pump messages until `WM_CLOSE`.
+    pub unsafe fn run(image: Option<Image>, caption: &str, ready: Sender<Result<usize, String>>) {
+        let instance = GetModuleHandleW(null());
+        let class_name = wide("YakuLingoSplash");
+        let class = WNDCLASSW {
+            lpfnWndProc: Some(window_proc),
+            hInstance: instance,
+            hCursor: LoadCursorW(null_mut(), IDC_APPSTARTING),
+            hbrBackground: GetStockObject(WHITE_BRUSH as i32) as HBRUSH,
+            lpszClassName: class_name.as_ptr(),
+            ..std::mem::zeroed()
+        };
+        // Already registered when a restart shows the splash again.
+        RegisterClassW(&class);
+
+        let shown = match &image {
+            Some(image) => {
+                let (width, height) = fit_within((image.width, image.height), SPLASH_MAX_IMAGE);
+                (width as i32, height as i32)
+            }
+            None => (ICON_SIZE, ICON_SIZE),
+        };
+        let width = (shown.0 + 2 * PADDING).max(MIN_WIDTH);
+        let height = shown.1 + 2 * PADDING + CAPTION_HEIGHT;
+        let paint = Box::into_raw(Box::new(Paint {
+            image,
+            shown,
+            caption: wide(caption),
+            bar_offset: 0,
+        }));
+
+        let hwnd = CreateWindowExW(
+            WS_EX_TOOLWINDOW | WS_EX_TOPMOST | WS_EX_NOACTIVATE,
+            class_name.as_ptr(),
+            (*paint).caption.as_ptr(),
+            WS_POPUP | WS_BORDER,
+            (GetSystemMetrics(SM_CXSCREEN) - width) / 2,
+            (GetSystemMetrics(SM_CYSCREEN) - height) / 2,
+            width,
+            height,
+            null_mut(),
+            null_mut(),
+            instance,
+            null_mut(),
+        );
+        if hwnd.is_null() {
+            drop(Box::from_raw(paint));
+            let _ = ready.send(Err(format!(
+                "CreateWindowExW failed ({})",
+                std::io::Error::last_os_error()
+            )));
+            return;
+        }
+        SetWindowLongPtrW(hwnd, GWLP_USERDATA, paint as isize);
+        SetTimer(hwnd, BAR_TIMER_ID, BAR_TIMER_MS, None);
+        ShowWindow(hwnd, SW_SHOWNOACTIVATE);
+        UpdateWindow(hwnd);
+        let _ = ready.send(Ok(hwnd as usize));
+
+        let mut message: MSG = std::mem::zeroed();
+        while GetMessageW(&mut message, null_mut(), 0, 0) > 0 {
+            TranslateMessage(&message);
+            DispatchMessageW(&message);
+        }
+        // Captions still queued when the window closed are dropped with
+        // the queue; each is a few bytes.
+        drop(Box::from_raw(paint));
+    }
+
+    unsafe extern "system" fn window_proc(
+        hwnd: HWND,
+        message: UINT,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        let paint = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut Paint;
+        match message {
+            WM_PAINT if !paint.is_null() => {
+                draw(hwnd, &*paint);
+                return 0;
+            }
+            WM_SET_CAPTION => {
+                let caption = Box::from_raw(lparam as *mut Vec<u16>);
+                if !paint.is_null() {
+                    (*paint).caption = *caption;
+                    SetWindowTextW(hwnd, (*paint).caption.as_ptr());
+                    InvalidateRect(hwnd, null(), TRUE);
+                }
+                return 0;
+            }
+            WM_TIMER if wparam == BAR_TIMER_ID && !paint.is_null() => {
+                let mut client: RECT = std::mem::zeroed();
+                GetClientRect(hwnd, &mut client);
+                let paint = &mut *paint;
+                paint.bar_offset = (paint.bar_offset + BAR_STEP) % (client.right + BAR_SEGMENT);
+                InvalidateRect(hwnd, &bar_rect(&client), TRUE);
+                return 0;
+            }
+            // A click must not take focus from the app coming up.
+            WM_MOUSEACTIVATE => return MA_NOACTIVATE as LRESULT,
+            WM_CLOSE => {
+                KillTimer(hwnd, BAR_TIMER_ID);
+                DestroyWindow(hwnd);
+                return 0;
+            }
+            WM_DESTROY => {
+                PostQuitMessage(0);
+                return 0;
+            }
+            _ => {}
+        }
+        DefWindowProcW(hwnd, message, wparam, lparam)
+    }
+
+    fn bar_rect(client: &RECT) -> RECT {
+        RECT {
+            left: 0,
+            top: client.bottom - BAR_HEIGHT,
+            right: client.right,
+            bottom: client.bottom,
+        }
+    }
+
+    unsafe fn draw(hwnd: HWND, paint: &Paint) {
+        let mut ps: PAINTSTRUCT = std::mem::zeroed();
+        let hdc = BeginPaint(hwnd, &mut ps);
+        let mut client: RECT = std::mem::zeroed();
+        GetClientRect(hwnd, &mut client);
+        let (width, height) = paint.shown;
+        let left = (client.right - width) / 2;
+
+        match &paint.image {
+            Some(image) => {
+                let mut info: BITMAPINFO = std::mem::zeroed();
+                info.bmiHeader = BITMAPINFOHEADER {
+                    biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                    biWidth: image.width as i32,
+                    // Negative: rows are stored top-down.
+                    biHeight: -(image.height as i32),
+                    biPlanes: 1,
+                    biBitCount: 32,
+                    biCompression: BI_RGB,
+                    ..std::mem::zeroed()
+                };
+                SetStretchBltMode(hdc, HALFTONE);
+                StretchDIBits(
+                    hdc,
+                    left,
+                    PADDING,
+                    width,
+                    height,
+                    0,
+                    0,
+                    image.width as i32,
+                    image.height as i32,
+                    image.pixels.as_ptr() as *const _,
+                    &info,
+                    DIB_RGB_COLORS,
+                    SRCCOPY,
+                );
+            }
+            None => {
+                // The exe's own icon (resource 1), else the stock one.
+                let mut icon = LoadImageW(
+                    GetModuleHandleW(null()),
+                    MAKEINTRESOURCEW(1),
+                    IMAGE_ICON,
+                    ICON_SIZE,
+                    ICON_SIZE,
+                    0,
+                ) as HICON;
+                if icon.is_null() {
+                    icon = LoadIconW(null_mut(), IDI_APPLICATION);
+                }
+                DrawIconEx(
+                    hdc,
+                    left,
+                    PADDING,
+                    icon,
+                    ICON_SIZE,
+                    ICON_SIZE,
+                    0,
+                    null_mut(),
+                    DI_NORMAL,
+                );
+            }
+        }
+
+        let mut caption_rect = RECT {
+            left: PADDING,
+            top: PADDING + height,
+            right: client.right - PADDING,
+            bottom: client.bottom - BAR_HEIGHT,
+        };
+        SelectObject(hdc, GetStockObject(DEFAULT_GUI_FONT as i32));
+        SetBkMode(hdc, TRANSPARENT as i32);
+        DrawTextW(
+            hdc,
+            paint.caption.as_ptr(),
+            -1,
+            &mut caption_rect,
+            DT_CENTER | DT_VCENTER | DT_SINGLELINE | DT_END_ELLIPSIS,
+        );
+
+        let bar = bar_rect(&client);
+        let segment = RECT {
+            left: (paint.bar_offset - BAR_SEGMENT).max(0),
+            right: paint.bar_offset.min(client.right),
+            ..bar
+        };
+        let brush = CreateSolidBrush(BAR_COLOR);
+        FillRect(hdc, &segment, brush);
+        DeleteObject(brush as _);
+        EndPaint(hwnd, &ps);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::fs;
+
+    #[test]
+    fn absent_assets_use_defaults() {
+        let dir = temp_dir("splash-absent");
+        assert_eq!(
+            resolve_splash_assets(&dir, Some("brand/missing.png"), Some("  ")),
+            SplashAssets {
+                image: None,
+                caption: DEFAULT_SPLASH_CAPTION.to_string(),
+            }
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn files_beside_exe_are_found_png_first() {
+        let dir = temp_dir("splash-beside");
+        fs::write(dir.join("splash.bmp"), "").unwrap();
+        assert_eq!(
+            resolve_splash_assets(&dir, None, None).image,
+            Some(dir.join("splash.bmp"))
+        );
+        fs::write(dir.join("splash.png"), "").unwrap();
+        assert_eq!(
+            resolve_splash_assets(&dir, None, None).image,
+            Some(dir.join("splash.png"))
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn large_images_are_scaled_down_to_fit() {
+        assert_eq!(fit_within((200, 100), SPLASH_MAX_IMAGE), (200, 100));
+        assert_eq!(fit_within((960, 320), SPLASH_MAX_IMAGE), (480, 160));
+        assert_eq!(fit_within((400, 1280), SPLASH_MAX_IMAGE), (100, 320));
+        assert_eq!(fit_within((10_000, 1), SPLASH_MAX_IMAGE), (480, 1));
+    }
+
+    #[test]
+    fn transparent_pixels_become_white() {
+        // Premultiplied BGRA: transparent, half-covered black, opaque red.
+        let mut pixels = [0, 0, 0, 0, 0, 0, 0, 128, 0, 0, 255, 255];
+        flatten_on_white(&mut pixels);
+        assert_eq!(
+            pixels,
+            [255, 255, 255, 255, 127, 127, 127, 255, 0, 0, 255, 255]
+        );
+    }
+
+    #[test]
+    fn configured_assets_win() {
+        let dir = temp_dir("splash-configured");
+        fs::write(dir.join("splash.png"), "").unwrap();
+        fs::create_dir_all(dir.join("brand")).unwrap();
+        fs::write(dir.join("brand").join("acme.bmp"), "").unwrap();
+        assert_eq!(
+            resolve_splash_assets(
+                &dir,
+                Some("brand/acme.bmp"),
+                Some("Starting Acme Translate")
+            ),
+            SplashAssets {
+                image: Some(dir.join("brand/acme.bmp")),
+                caption: "Starting Acme Translate".to_string(),
+            }
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,189 @@
+//! `launcher_state.json`: one-way signal from the app to stop the watchdog.
+
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+
+use crate::log::log_event;
+
+const LAUNCHER_STATE_TTL_SEC: u64 = 300;
+
+/// Why the app asked the watchdog to stop restarting it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateReason {
+    UserExit,
+    UpdateInProgress,
+}
+
+impl fmt::Display for StateReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            StateReason::UserExit => "user_exit",
+            StateReason::UpdateInProgress => "update_in_progress",
+        })
+    }
+}
+
+/// `{"reason": "user_exit", "ts": 1700000000.5}` as written by
+/// `write_launcher_state` on the Python side.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LauncherState {
+    pub reason: StateReason,
+    /// Unix seconds (Python `time.time()`).
+    pub ts: f64,
+}
+
+impl LauncherState {
+    /// Whole seconds of `ts`; `None` for negative or non-finite values.
+    pub fn ts_secs(&self) -> Option<u64> {
+        (self.ts.is_finite() && self.ts >= 0.0).then(|| self.ts.floor() as u64)
+    }
+}
+
+pub fn parse_launcher_state(content: &str) -> Result<LauncherState, String> {
+    serde_json::from_str(content).map_err(|e| e.to_string())
+}
+
+pub fn read_and_clear_launcher_state(
+    path: &Option<PathBuf>,
+    log_path: &Option<PathBuf>,
+) -> Option<StateReason> {
+    let path = path.as_ref()?;
+    if !path.exists() {
+        return None;
+    }
+    let content = match fs::read_to_string(path) {
+        Ok(value) => value,
+        Err(err) => {
+            log_event(log_path, &format!("Failed to read launcher state: {}", err));
+            return None;
+        }
+    };
+    let _ = fs::remove_file(path);
+
+    let state = match parse_launcher_state(&content) {
+        Ok(state) => state,
+        Err(err) => {
+            log_event(
+                log_path,
+                &format!("Invalid launcher state ({}); clearing file", err),
+            );
+            return None;
+        }
+    };
+    let ts_secs = match state.ts_secs() {
+        Some(value) => value,
+        None => {
+            log_event(log_path, "Invalid launcher state timestamp; clearing file");
+            return None;
+        }
+    };
+
+    let now_secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_else(|_| Duration::from_secs(0))
+        .as_secs();
+    if now_secs < ts_secs || now_secs - ts_secs > LAUNCHER_STATE_TTL_SEC {
+        log_event(log_path, "Stale launcher state detected; clearing file");
+        return None;
+    }
+
+    Some(state.reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::path::Path;
+
+    fn now_secs() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn write_state(dir: &Path, content: &str) -> Option<PathBuf> {
+        let path = dir.join("launcher_state.json");
+        fs::write(&path, content).unwrap();
+        Some(path)
+    }
+
+    #[test]
+    fn parse_accepts_float_ts_and_rejects_missing_fields() {
+        let state =
+            parse_launcher_state("{\"ts\": 1700000000.75, \"reason\": \"user_exit\"}").unwrap();
+        assert_eq!(state.reason, StateReason::UserExit);
+        assert_eq!(state.ts_secs(), Some(1700000000));
+        assert!(parse_launcher_state("{\"reason\": \"user_exit\"}").is_err());
+        assert!(parse_launcher_state("{\"reason\": \"user_exit\", \"ts\": \"abc\"}").is_err());
+        assert!(parse_launcher_state("{\"reason\": \"restart\", \"ts\": 1}").is_err());
+    }
+
+    #[test]
+    fn malformed_or_nested_state_is_cleared() {
+        let dir = temp_dir("state-invalid");
+        let now = now_secs();
+        for content in [
+            "{\"reason\": \"user_exit\", \"ts\": ".to_string(),
+            format!(
+                "{{\"reason\": {{\"kind\": \"user_exit\"}}, \"ts\": {}}}",
+                now
+            ),
+            format!("{{\"reason\": \"user_exit\", \"ts\": -{}}}", now),
+            // "user_exit" only as part of another value.
+            format!("{{\"reason\": \"not_a_user_exit\", \"ts\": {}}}", now),
+            format!("{{\"note\": \"user_exit\", \"ts\": {}}}", now),
+        ] {
+            let path = write_state(&dir, &content);
+            assert_eq!(
+                read_and_clear_launcher_state(&path, &None),
+                None,
+                "{}",
+                content
+            );
+            assert!(!path.unwrap().exists());
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fresh_state_is_returned_and_cleared() {
+        let dir = temp_dir("state-fresh");
+        let path = dir.join("launcher_state.json");
+        fs::write(
+            &path,
+            format!("{{\"reason\": \"user_exit\", \"ts\": {}}}", now_secs()),
+        )
+        .unwrap();
+
+        let reason = read_and_clear_launcher_state(&Some(path.clone()), &None);
+        assert_eq!(reason, Some(StateReason::UserExit));
+        assert!(!path.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn stale_state_is_ignored_and_cleared() {
+        let dir = temp_dir("state-stale");
+        let path = dir.join("launcher_state.json");
+        let ts = now_secs() - LAUNCHER_STATE_TTL_SEC - 10;
+        fs::write(
+            &path,
+            format!("{{\"reason\": \"update_in_progress\", \"ts\": {}}}", ts),
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_and_clear_launcher_state(&Some(path.clone()), &None),
+            None
+        );
+        assert!(!path.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
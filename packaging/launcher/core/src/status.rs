@@ -0,0 +1,198 @@
+//! `launcher_status.json`: the launcher's current phase, for the app and
+//! the updater.
+//!
+//! `launcher_state.json` only carries signals from the app to the launcher.
+//! This file goes the other way: the launcher that starts the app rewrites
+//! it at each transition (starting, watching, restarting, gave up, stopped)
+//! with the app PID and restart count, so an updater can wait until the
+//! launcher is idle before replacing files. Writes go through a temp file
+//! and a rename, and are best-effort: a locked or read-only path is logged
+//! and never stops a launch.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use serde::{Deserialize, Serialize};
+
+use crate::crash::now_secs;
+use crate::log::log_event;
+use crate::paths::{get_state_dir, write_file_atomic};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LauncherPhase {
+    /// Preparing the environment; no app yet.
+    Starting,
+    /// The watchdog is waiting on `child_pid`.
+    WatchdogActive,
+    /// The app exited and the watchdog is waiting to start it again.
+    Restarting,
+    /// Restart limit reached; the launcher is exiting.
+    GaveUp,
+    /// The launcher exited (or handed the app off) and touches nothing more.
+    Stopped,
+}
+
+impl LauncherPhase {
+    /// Whether the launcher will not write the file again.
+    pub fn is_terminal(self) -> bool {
+        matches!(self, LauncherPhase::GaveUp | LauncherPhase::Stopped)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LauncherStatus {
+    pub phase: LauncherPhase,
+    pub launcher_pid: u32,
+    pub child_pid: Option<u32>,
+    pub restart_attempts: u32,
+    /// Unix seconds of the transition.
+    pub ts: u64,
+}
+
+/// `launcher_status[-<name>].json` next to `launcher_state.json`.
+pub fn get_launcher_status_path(base_dir: &Path, instance_name: Option<&str>) -> PathBuf {
+    let file_name = match instance_name {
+        Some(name) => format!("launcher_status-{}.json", name),
+        None => "launcher_status.json".to_string(),
+    };
+    get_state_dir(base_dir).join(file_name)
+}
+
+pub fn read_launcher_status(path: &Path) -> Option<LauncherStatus> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// Writer for one launcher's status file.
+#[derive(Debug, Clone)]
+pub struct StatusFile {
+    path: PathBuf,
+    log_path: Option<PathBuf>,
+}
+
+impl StatusFile {
+    pub fn new(path: PathBuf, log_path: &Option<PathBuf>) -> Self {
+        StatusFile {
+            path,
+            log_path: log_path.clone(),
+        }
+    }
+
+    pub fn for_instance(
+        base_dir: &Path,
+        instance_name: Option<&str>,
+        log_path: &Option<PathBuf>,
+    ) -> Self {
+        Self::new(get_launcher_status_path(base_dir, instance_name), log_path)
+    }
+
+    /// Replace the file with `phase` (best-effort).
+    pub fn write(&self, phase: LauncherPhase, child_pid: Option<u32>, restart_attempts: u32) {
+        let status = LauncherStatus {
+            phase,
+            launcher_pid: process::id(),
+            child_pid,
+            restart_attempts,
+            ts: now_secs(),
+        };
+        if let Err(err) = self.replace(&status) {
+            log_event(
+                &self.log_path,
+                &format!("Failed to write launcher status ({:?}): {}", phase, err),
+            );
+        }
+    }
+
+    fn replace(&self, status: &LauncherStatus) -> Result<(), String> {
+        let content = serde_json::to_string(status).map_err(|e| e.to_string())?;
+        write_file_atomic(&self.path, &content)
+    }
+
+    /// Record `stopped`, unless this launcher already left a terminal phase
+    /// (a `gave_up` stays visible).
+    pub fn finish(&self) {
+        let own =
+            read_launcher_status(&self.path).filter(|status| status.launcher_pid == process::id());
+        match own {
+            Some(status) if status.phase.is_terminal() => {}
+            own => self.write(
+                LauncherPhase::Stopped,
+                None,
+                own.map_or(0, |status| status.restart_attempts),
+            ),
+        }
+    }
+}
+
+/// Writes `stopped` when the launch ends, errors included.
+pub struct StoppedStatus<'a>(pub &'a StatusFile);
+
+impl Drop for StoppedStatus<'_> {
+    fn drop(&mut self) {
+        self.0.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn transitions_replace_the_file_and_end_stopped() {
+        let dir = temp_dir("status-transitions");
+        let path = dir.join("launcher_status.json");
+        let status = StatusFile::new(path.clone(), &None);
+        {
+            let _stopped = StoppedStatus(&status);
+            status.write(LauncherPhase::Starting, None, 0);
+            assert_eq!(
+                read_launcher_status(&path).unwrap().phase,
+                LauncherPhase::Starting
+            );
+            status.write(LauncherPhase::WatchdogActive, Some(4242), 2);
+            let current = read_launcher_status(&path).unwrap();
+            assert_eq!(current.child_pid, Some(4242));
+            assert_eq!(current.restart_attempts, 2);
+            assert_eq!(current.launcher_pid, process::id());
+            assert!(!dir
+                .join(format!("launcher_status.json.{}.tmp", process::id()))
+                .exists());
+        }
+        let stopped = read_launcher_status(&path).unwrap();
+        assert_eq!(stopped.phase, LauncherPhase::Stopped);
+        assert_eq!(stopped.child_pid, None);
+        assert_eq!(stopped.restart_attempts, 2);
+        let json = fs::read_to_string(&path).unwrap();
+        assert!(json.contains("\"phase\":\"stopped\""));
+
+        // A give-up is not overwritten on the way out.
+        status.write(LauncherPhase::GaveUp, None, 3);
+        status.finish();
+        assert_eq!(
+            read_launcher_status(&path).unwrap().phase,
+            LauncherPhase::GaveUp
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unwritable_path_is_ignored() {
+        let dir = temp_dir("status-unwritable");
+        // The status path is a directory: the rename fails.
+        let path = dir.join("launcher_status.json");
+        fs::create_dir_all(path.join("inner")).unwrap();
+        let log_path = Some(dir.join("launcher.log"));
+        let status = StatusFile::new(path.clone(), &log_path);
+        status.write(LauncherPhase::Starting, None, 0);
+        status.finish();
+        assert!(path.is_dir());
+        assert!(!dir
+            .join(format!("launcher_status.json.{}.tmp", process::id()))
+            .exists());
+        let log = fs::read_to_string(dir.join("launcher.log")).unwrap();
+        assert!(log.contains("Failed to write launcher status (Starting)"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,118 @@
+//! The last lines of the app's stderr, kept for crash diagnosis only.
+//!
+//! With `capture_stderr_tail` the app's stderr is piped into a reader
+//! thread that keeps the newest `STDERR_TAIL_LINES` lines in memory. The
+//! watchdog takes them when the app exits with a crash code (for the crash
+//! history and the restart-limit dialog) and drops them otherwise.
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Enough for a Python traceback's tail.
+pub const STDERR_TAIL_LINES: usize = 50;
+/// How long `finish` waits for the reader to drain what the app wrote last.
+pub(crate) const DRAIN_WAIT_MS: u64 = 500;
+
+/// Keeps the newest `capacity` lines.
+#[derive(Debug)]
+pub struct LineRing {
+    lines: VecDeque<String>,
+    capacity: usize,
+}
+
+impl LineRing {
+    pub fn new(capacity: usize) -> Self {
+        LineRing {
+            lines: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, line: String) {
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+
+    /// The kept lines joined with `\n`, oldest first.
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Reader thread following one app process's stderr.
+pub struct StderrTail {
+    ring: Arc<Mutex<LineRing>>,
+    reader: JoinHandle<()>,
+}
+
+impl StderrTail {
+    /// Start reading `stderr` (lossily decoded) into a ring of `capacity` lines.
+    pub fn capture(stderr: impl Read + Send + 'static, capacity: usize) -> Self {
+        let ring = Arc::new(Mutex::new(LineRing::new(capacity)));
+        let shared = ring.clone();
+        let reader = thread::spawn(move || {
+            let mut reader = BufReader::new(stderr);
+            let mut line = Vec::new();
+            while matches!(reader.read_until(b'\n', &mut line), Ok(n) if n > 0) {
+                let text = String::from_utf8_lossy(&line).trim_end().to_string();
+                if let Ok(mut ring) = shared.lock() {
+                    ring.push(text);
+                }
+                line.clear();
+            }
+        });
+        StderrTail { ring, reader }
+    }
+
+    /// The captured tail once the app has exited; `None` if it wrote nothing.
+    /// Waits briefly for the pipe to drain, but not for a process that
+    /// inherited it and is still running.
+    pub fn finish(self) -> Option<String> {
+        let deadline = Instant::now() + Duration::from_millis(DRAIN_WAIT_MS);
+        while !self.reader.is_finished() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let text = self.ring.lock().ok()?.text();
+        (!text.trim().is_empty()).then_some(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn ring_keeps_only_the_newest_lines() {
+        let mut ring = LineRing::new(3);
+        for n in 1..=5 {
+            ring.push(format!("line {}", n));
+        }
+        assert_eq!(ring.text(), "line 3\nline 4\nline 5");
+    }
+
+    #[test]
+    fn capture_surfaces_the_traceback_tail() {
+        let mut output: String = (1..=60).map(|n| format!("noise {}\n", n)).collect();
+        output.push_str("Traceback (most recent call last):\r\nImportError: nicegui");
+        let tail = StderrTail::capture(Cursor::new(output.into_bytes()), STDERR_TAIL_LINES)
+            .finish()
+            .unwrap();
+        let lines: Vec<_> = tail.lines().collect();
+        assert_eq!(lines.len(), STDERR_TAIL_LINES);
+        assert_eq!(lines[0], "noise 13");
+        assert_eq!(lines[STDERR_TAIL_LINES - 1], "ImportError: nicegui");
+
+        let silent = StderrTail::capture(Cursor::new(Vec::new()), STDERR_TAIL_LINES);
+        assert_eq!(silent.finish(), None);
+    }
+}
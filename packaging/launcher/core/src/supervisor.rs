@@ -0,0 +1,369 @@
+//! Helper processes supervised alongside the app.
+//!
+//! `[[helpers]]` entries in `launcher.toml` (e.g. a local model server) are
+//! started before the app and restarted independently, each per its own
+//! policy, for as long as the watchdog runs. The app itself stays the
+//! primary child: its exit codes decide when supervision ends. With no
+//! entries the watchdog supervises the app alone.
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::heartbeat::HEARTBEAT_POLL_MS;
+use crate::instance::probe_connect;
+use crate::launch::install_dir_command;
+use crate::log::log_event;
+use crate::watchdog::{RESTART_BACKOFF_BASE_SEC, RESTART_BACKOFF_MAX_SEC, RESTART_RESET_AFTER_SEC};
+
+/// How long the app launch waits for helpers with a `ready_port`.
+pub const HELPER_READY_TIMEOUT_SEC: u64 = 30;
+
+/// When an exited helper is started again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    /// Restart after a non-zero exit only.
+    #[default]
+    OnFailure,
+    Always,
+    Never,
+}
+
+impl RestartPolicy {
+    pub fn should_restart(self, exit_code: i32) -> bool {
+        match self {
+            RestartPolicy::OnFailure => exit_code != 0,
+            RestartPolicy::Always => true,
+            RestartPolicy::Never => false,
+        }
+    }
+}
+
+/// One `[[helpers]]` entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HelperSpec {
+    pub name: String,
+    /// Program relative to the install dir, then its arguments.
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub restart: RestartPolicy,
+    /// The helper counts as ready once `127.0.0.1:<port>` accepts connections.
+    #[serde(default)]
+    pub ready_port: Option<u16>,
+}
+
+/// A running helper, behind a seam so restarts can be tested without
+/// spawning processes.
+pub trait ChildProcess {
+    fn try_wait(&mut self) -> Result<Option<i32>, String>;
+    fn kill(&mut self) -> Result<(), String>;
+}
+
+impl ChildProcess for Child {
+    fn try_wait(&mut self) -> Result<Option<i32>, String> {
+        Child::try_wait(self)
+            .map(|status| status.map(|status| status.code().unwrap_or(-1)))
+            .map_err(|e| format!("Failed to poll helper: {}", e))
+    }
+
+    fn kill(&mut self) -> Result<(), String> {
+        Child::kill(self).map_err(|e| format!("Failed to stop helper: {}", e))?;
+        Child::wait(self)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to wait for helper: {}", e))
+    }
+}
+
+/// Spawn a helper from the install dir.
+pub fn spawn_helper(base_dir: &Path, spec: &HelperSpec) -> Result<Box<dyn ChildProcess>, String> {
+    let mut command = install_dir_command(base_dir, &spec.command)
+        .ok_or_else(|| format!("helper {} has no command", spec.name))?;
+    command
+        .spawn()
+        .map(|child| Box::new(child) as Box<dyn ChildProcess>)
+        .map_err(|e| format!("Failed to start helper {}: {}", spec.name, e))
+}
+
+struct Helper {
+    spec: HelperSpec,
+    process: Option<Box<dyn ChildProcess>>,
+    started: Instant,
+    /// Set while an exited helper waits out its backoff.
+    restart_at: Option<Instant>,
+    backoff: Duration,
+}
+
+/// The helpers of one watchdog run; dropping it stops them all.
+pub struct HelperSupervisor<S>
+where
+    S: FnMut(&HelperSpec) -> Result<Box<dyn ChildProcess>, String>,
+{
+    helpers: Vec<Helper>,
+    spawn: S,
+}
+
+impl<S> HelperSupervisor<S>
+where
+    S: FnMut(&HelperSpec) -> Result<Box<dyn ChildProcess>, String>,
+{
+    /// Start every helper in `specs`.
+    pub fn start(specs: &[HelperSpec], spawn: S, now: Instant, log_path: &Option<PathBuf>) -> Self {
+        let mut supervisor = HelperSupervisor {
+            helpers: specs
+                .iter()
+                .map(|spec| Helper {
+                    spec: spec.clone(),
+                    process: None,
+                    started: now,
+                    restart_at: Some(now),
+                    backoff: Duration::from_secs(RESTART_BACKOFF_BASE_SEC),
+                })
+                .collect(),
+            spawn,
+        };
+        supervisor.poll(now, log_path);
+        supervisor
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.helpers.is_empty()
+    }
+
+    /// Reap exited helpers and (re)start the ones whose backoff has passed.
+    pub fn poll(&mut self, now: Instant, log_path: &Option<PathBuf>) {
+        for helper in &mut self.helpers {
+            if let Some(process) = helper.process.as_mut() {
+                let exit_code = match process.try_wait() {
+                    Ok(Some(code)) => code,
+                    Ok(None) => continue,
+                    Err(err) => {
+                        log_event(log_path, &err);
+                        continue;
+                    }
+                };
+                helper.process = None;
+                if now.duration_since(helper.started) > Duration::from_secs(RESTART_RESET_AFTER_SEC)
+                {
+                    helper.backoff = Duration::from_secs(RESTART_BACKOFF_BASE_SEC);
+                }
+                if !helper.spec.restart.should_restart(exit_code) {
+                    log_event(
+                        log_path,
+                        &format!(
+                            "Helper {} exited (code {}) - not restarting",
+                            helper.spec.name, exit_code
+                        ),
+                    );
+                    continue;
+                }
+                log_event(
+                    log_path,
+                    &format!(
+                        "Helper {} exited (code {}), restarting in {}s",
+                        helper.spec.name,
+                        exit_code,
+                        helper.backoff.as_secs()
+                    ),
+                );
+                helper.restart_at = Some(now + helper.backoff);
+                helper.backoff =
+                    (helper.backoff * 2).min(Duration::from_secs(RESTART_BACKOFF_MAX_SEC));
+            }
+            if helper.restart_at.is_some_and(|at| now >= at) {
+                helper.restart_at = None;
+                helper.started = now;
+                match (self.spawn)(&helper.spec) {
+                    Ok(process) => {
+                        helper.process = Some(process);
+                        log_event(log_path, &format!("Helper {} started", helper.spec.name));
+                    }
+                    Err(err) => {
+                        log_event(log_path, &err);
+                        if helper.spec.restart != RestartPolicy::Never {
+                            helper.restart_at = Some(now + helper.backoff);
+                            helper.backoff = (helper.backoff * 2)
+                                .min(Duration::from_secs(RESTART_BACKOFF_MAX_SEC));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Wait (up to `timeout`) for helpers with a `ready_port` to accept
+    /// connections, so the app does not start against a cold helper.
+    pub fn wait_ready(&self, timeout: Duration, log_path: &Option<PathBuf>) {
+        let deadline = Instant::now() + timeout;
+        for helper in &self.helpers {
+            let port = match (helper.spec.ready_port, &helper.process) {
+                (Some(port), Some(_)) => port,
+                _ => continue,
+            };
+            let addr = SocketAddr::from(([127, 0, 0, 1], port));
+            loop {
+                if probe_connect(addr).is_ok() {
+                    log_event(log_path, &format!("Helper {} ready", helper.spec.name));
+                    break;
+                }
+                if Instant::now() >= deadline {
+                    log_event(
+                        log_path,
+                        &format!(
+                            "Helper {} not ready on port {} after {}s - starting app anyway",
+                            helper.spec.name,
+                            port,
+                            timeout.as_secs()
+                        ),
+                    );
+                    return;
+                }
+                thread::sleep(Duration::from_millis(HEARTBEAT_POLL_MS));
+            }
+        }
+    }
+}
+
+impl<S> Drop for HelperSupervisor<S>
+where
+    S: FnMut(&HelperSpec) -> Result<Box<dyn ChildProcess>, String>,
+{
+    fn drop(&mut self) {
+        for helper in &mut self.helpers {
+            if let Some(mut process) = helper.process.take() {
+                let _ = process.kill();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    /// Per helper name: how often it was started, and the exit code once
+    /// the test has ended it.
+    type ChildTable = HashMap<String, (u32, Option<i32>)>;
+
+    /// Self-test children: each runs until the test sets an exit code for it.
+    #[derive(Clone, Default)]
+    struct Children(Rc<RefCell<ChildTable>>);
+
+    impl Children {
+        fn exit(&self, name: &str, code: i32) {
+            self.0.borrow_mut().get_mut(name).unwrap().1 = Some(code);
+        }
+
+        fn starts(&self, name: &str) -> u32 {
+            self.0.borrow().get(name).map_or(0, |entry| entry.0)
+        }
+    }
+
+    struct FakeChild {
+        name: String,
+        children: Children,
+    }
+
+    impl ChildProcess for FakeChild {
+        fn try_wait(&mut self) -> Result<Option<i32>, String> {
+            Ok(self.children.0.borrow()[&self.name].1)
+        }
+
+        fn kill(&mut self) -> Result<(), String> {
+            self.children.exit(&self.name, -1);
+            Ok(())
+        }
+    }
+
+    fn spawner(
+        children: &Children,
+    ) -> impl FnMut(&HelperSpec) -> Result<Box<dyn ChildProcess>, String> {
+        let children = children.clone();
+        move |spec| {
+            let mut map = children.0.borrow_mut();
+            let entry = map.entry(spec.name.clone()).or_default();
+            *entry = (entry.0 + 1, None);
+            Ok(Box::new(FakeChild {
+                name: spec.name.clone(),
+                children: children.clone(),
+            }))
+        }
+    }
+
+    fn spec(name: &str, restart: RestartPolicy) -> HelperSpec {
+        HelperSpec {
+            name: name.to_string(),
+            command: vec![format!("{}.exe", name)],
+            restart,
+            ready_port: None,
+        }
+    }
+
+    #[test]
+    fn helpers_restart_independently() {
+        let children = Children::default();
+        let t0 = Instant::now();
+        let specs = [
+            spec("model-server", RestartPolicy::OnFailure),
+            spec("indexer", RestartPolicy::Always),
+        ];
+        let mut supervisor = HelperSupervisor::start(&specs, spawner(&children), t0, &None);
+        assert_eq!(children.starts("model-server"), 1);
+        assert_eq!(children.starts("indexer"), 1);
+
+        children.exit("model-server", 3);
+        supervisor.poll(t0, &None);
+        assert_eq!(children.starts("model-server"), 1, "waits out the backoff");
+        supervisor.poll(t0 + Duration::from_secs(1), &None);
+        assert_eq!(children.starts("model-server"), 2);
+        assert_eq!(children.starts("indexer"), 1, "untouched by the other exit");
+
+        children.exit("indexer", 0);
+        supervisor.poll(t0 + Duration::from_secs(2), &None);
+        supervisor.poll(t0 + Duration::from_secs(3), &None);
+        assert_eq!(children.starts("indexer"), 2);
+        assert_eq!(children.starts("model-server"), 2);
+
+        children.exit("model-server", 3);
+        supervisor.poll(t0 + Duration::from_secs(4), &None);
+        supervisor.poll(t0 + Duration::from_secs(5), &None);
+        assert_eq!(children.starts("model-server"), 2, "backoff doubled to 2s");
+        supervisor.poll(t0 + Duration::from_secs(6), &None);
+        assert_eq!(children.starts("model-server"), 3);
+    }
+
+    #[test]
+    fn restart_policy_is_honoured() {
+        let children = Children::default();
+        let t0 = Instant::now();
+        let specs = [
+            spec("on-failure", RestartPolicy::OnFailure),
+            spec("never", RestartPolicy::Never),
+        ];
+        let mut supervisor = HelperSupervisor::start(&specs, spawner(&children), t0, &None);
+        children.exit("on-failure", 0);
+        children.exit("never", 1);
+        for secs in 0..5 {
+            supervisor.poll(t0 + Duration::from_secs(secs), &None);
+        }
+        assert_eq!(children.starts("on-failure"), 1);
+        assert_eq!(children.starts("never"), 1);
+    }
+
+    #[test]
+    fn dropping_the_supervisor_stops_helpers() {
+        let children = Children::default();
+        let specs = [spec("model-server", RestartPolicy::Always)];
+        let supervisor = HelperSupervisor::start(&specs, spawner(&children), Instant::now(), &None);
+        assert!(!supervisor.is_empty());
+        drop(supervisor);
+        assert_eq!(children.0.borrow()["model-server"].1, Some(-1));
+    }
+}
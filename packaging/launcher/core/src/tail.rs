@@ -0,0 +1,242 @@
+//! `--foreground`: stream `launcher.log` to a console while the launcher runs.
+//!
+//! A tailer thread follows the log file, so lines written by the app (when it
+//! shares the log) show up next to the launcher's own.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// How often the tailer checks the log for new lines.
+pub const TAIL_POLL_MS: u64 = 200;
+
+/// Follows a file from a byte offset, emitting complete lines only.
+#[derive(Debug)]
+pub struct LogTailer {
+    path: PathBuf,
+    offset: u64,
+    partial: Vec<u8>,
+}
+
+impl LogTailer {
+    /// Follow `path` from its current end, so only new lines are emitted.
+    pub fn from_end(path: &Path) -> Self {
+        let offset = std::fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+        LogTailer {
+            path: path.to_path_buf(),
+            offset,
+            partial: Vec::new(),
+        }
+    }
+
+    /// Write lines appended since the last poll to `out`; returns how many.
+    /// A shrunk file (rotated or truncated) is followed from the start.
+    pub fn poll(&mut self, out: &mut impl Write) -> io::Result<usize> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err),
+        };
+        if file.metadata()?.len() < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut appended = Vec::new();
+        self.offset += file.read_to_end(&mut appended)? as u64;
+        self.partial.extend_from_slice(&appended);
+
+        let complete = match self.partial.iter().rposition(|&byte| byte == b'\n') {
+            Some(end) => end + 1,
+            None => return Ok(0),
+        };
+        let lines: Vec<u8> = self.partial.drain(..complete).collect();
+        out.write_all(&lines)?;
+        out.flush()?;
+        Ok(lines.iter().filter(|&&byte| byte == b'\n').count())
+    }
+}
+
+/// Running tailer thread; stops (after a final poll) when dropped, so every
+/// way out of `run` flushes the last lines. A console control event ends the
+/// launcher without unwinding, so the shutdown handler stops it too.
+pub struct TailerHandle {
+    thread: Arc<TailerThread>,
+}
+
+struct TailerThread {
+    stop: Arc<AtomicBool>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl TailerThread {
+    /// Ask for the final poll and wait for it; later calls do nothing.
+    fn stop(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+        let handle = self
+            .handle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some(handle) = handle {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl TailerHandle {
+    /// Flush the last lines and stop following the log.
+    pub fn stop(&self) {
+        self.thread.stop();
+    }
+
+    /// Also stop from the shutdown handler, before a console event ends the
+    /// launcher.
+    pub fn stop_on_shutdown(&self) {
+        let thread = Arc::clone(&self.thread);
+        crate::shutdown::on_shutdown(move || thread.stop());
+    }
+}
+
+impl Drop for TailerHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Start following `path` to stdout.
+pub fn spawn_tailer(path: &Path) -> TailerHandle {
+    spawn_tailer_to(path, io::stdout())
+}
+
+fn spawn_tailer_to(path: &Path, mut out: impl Write + Send + 'static) -> TailerHandle {
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut tailer = LogTailer::from_end(path);
+    let handle = {
+        let stop = Arc::clone(&stop);
+        thread::spawn(move || loop {
+            let stopping = stop.load(Ordering::SeqCst);
+            let _ = tailer.poll(&mut out);
+            if stopping {
+                break;
+            }
+            thread::sleep(Duration::from_millis(TAIL_POLL_MS));
+        })
+    };
+    TailerHandle {
+        thread: Arc::new(TailerThread {
+            stop,
+            handle: Mutex::new(Some(handle)),
+        }),
+    }
+}
+
+/// Give the GUI-subsystem launcher a console: the parent's when started from
+/// one, otherwise a new window.
+#[cfg(windows)]
+pub fn attach_console() -> bool {
+    use winapi::um::consoleapi::AllocConsole;
+    use winapi::um::wincon::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    unsafe { AttachConsole(ATTACH_PARENT_PROCESS) != 0 || AllocConsole() != 0 }
+}
+
+#[cfg(not(windows))]
+pub fn attach_console() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::fs::{self, OpenOptions};
+
+    fn append(path: &Path, text: &str) {
+        let mut file = OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn appended_lines_are_emitted() {
+        let dir = temp_dir("tail-append");
+        let path = dir.join("launcher.log");
+        fs::write(&path, "[1] before tailing\n").unwrap();
+        let mut tailer = LogTailer::from_end(&path);
+        let mut out = Vec::new();
+
+        assert_eq!(tailer.poll(&mut out).unwrap(), 0);
+        append(&path, "[2] launcher line\n[3] app line\n");
+        assert_eq!(tailer.poll(&mut out).unwrap(), 2);
+        append(&path, "[4] half");
+        assert_eq!(tailer.poll(&mut out).unwrap(), 0, "waits for the newline");
+        append(&path, " written\n");
+        assert_eq!(tailer.poll(&mut out).unwrap(), 1);
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "[2] launcher line\n[3] app line\n[4] half written\n"
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn truncated_log_is_followed_from_start() {
+        let dir = temp_dir("tail-truncate");
+        let path = dir.join("launcher.log");
+        fs::write(&path, "[1] old line that is long\n").unwrap();
+        let mut tailer = LogTailer::from_end(&path);
+        fs::write(&path, "[2] new\n").unwrap();
+        let mut out = Vec::new();
+        assert_eq!(tailer.poll(&mut out).unwrap(), 1);
+        assert_eq!(String::from_utf8(out).unwrap(), "[2] new\n");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// Output shared with the test while the tailer thread writes to it.
+    #[derive(Clone, Default)]
+    struct SharedOut(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedOut {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn stopping_flushes_the_last_lines() {
+        let dir = temp_dir("tail-stop");
+        let path = dir.join("launcher.log");
+        fs::write(&path, "").unwrap();
+        let out = SharedOut::default();
+        let handle = spawn_tailer_to(&path, out.clone());
+
+        append(&path, "[1] terminated app (pid 42)\n");
+        handle.stop();
+        append(&path, "[2] after stop\n");
+        drop(handle);
+
+        let written = out.0.lock().unwrap().clone();
+        assert_eq!(
+            String::from_utf8(written).unwrap(),
+            "[1] terminated app (pid 42)\n"
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_log_emits_nothing() {
+        let dir = temp_dir("tail-missing");
+        let mut tailer = LogTailer::from_end(&dir.join("launcher.log"));
+        assert_eq!(tailer.poll(&mut Vec::new()).unwrap(), 0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
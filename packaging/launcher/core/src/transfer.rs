@@ -0,0 +1,256 @@
+//! `--export-config` / `--import-config`: move a known-good configuration
+//! between machines.
+//!
+//! Export writes the effective configuration (defaults, config files and
+//! `YAKULINGO_*` overrides, flattened into one file) with secret-looking
+//! values replaced by [`REDACTED`]. Import validates a file and only then
+//! installs it as the per-user (or, with `--machine`, the install dir's)
+//! `launcher.toml`, keeping the previous file as `launcher.toml.bak`.
+
+use std::fs;
+use std::path::Path;
+
+use toml::{Table, Value};
+
+use crate::config::{
+    load_config, parse_config_layer, user_config_path, LauncherConfig, CONFIG_FILE_NAME,
+};
+use crate::paths::launcher_dir;
+use crate::tail::attach_console;
+
+/// Placeholder for redacted values; an import refuses files that still have it.
+pub const REDACTED: &str = "<redacted>";
+/// Config keys holding `[program, args...]` command lines.
+const COMMAND_LINE_KEYS: &[&str] = &["command", "updater"];
+const SECRET_WORDS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "api-key",
+    "apikey",
+    "credential",
+];
+
+pub(crate) fn is_secret_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SECRET_WORDS.iter().any(|word| name.contains(word))
+}
+
+/// Redact `--token=...` and the value after a bare `--token` flag.
+fn redact_args(args: &mut [Value]) {
+    let mut redact_next = false;
+    for arg in args {
+        let Value::String(text) = arg else {
+            redact_next = false;
+            continue;
+        };
+        if redact_next && !text.starts_with('-') {
+            *text = REDACTED.to_string();
+            redact_next = false;
+            continue;
+        }
+        redact_next = false;
+        if !text.starts_with('-') {
+            continue;
+        }
+        match text.split_once('=') {
+            Some((flag, _)) if is_secret_name(flag) => *text = format!("{}={}", flag, REDACTED),
+            Some(_) => {}
+            None => redact_next = is_secret_name(text),
+        }
+    }
+}
+
+fn redact_table(table: &mut Table) {
+    for (key, value) in table.iter_mut() {
+        match value {
+            Value::String(text) if is_secret_name(key) => *text = REDACTED.to_string(),
+            Value::Array(items) if COMMAND_LINE_KEYS.contains(&key.as_str()) => redact_args(items),
+            Value::Array(items) => {
+                for item in items {
+                    if let Value::Table(table) = item {
+                        redact_table(table);
+                    }
+                }
+            }
+            Value::Table(table) => redact_table(table),
+            _ => {}
+        }
+    }
+}
+
+fn has_redacted(value: &Value) -> bool {
+    match value {
+        Value::String(text) => text == REDACTED || text.ends_with(&format!("={}", REDACTED)),
+        Value::Array(items) => items.iter().any(has_redacted),
+        Value::Table(table) => table.values().any(has_redacted),
+        _ => false,
+    }
+}
+
+/// The effective configuration as a standalone `launcher.toml`.
+pub fn export_config(config: &LauncherConfig) -> Result<String, String> {
+    let mut table =
+        Table::try_from(config).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    redact_table(&mut table);
+    let body = toml::to_string(&table).map_err(|e| format!("Failed to serialize config: {}", e))?;
+    Ok(format!(
+        "# YakuLingo launcher configuration (effective: defaults, files and environment).\n\
+         # Secret-looking values are replaced with \"{}\".\n\n{}",
+        REDACTED, body
+    ))
+}
+
+/// Validate `content` and install it at `dest`, keeping any previous file
+/// as `launcher.toml.bak`. Nothing is replaced when validation fails.
+pub fn import_config(content: &str, dest: &Path) -> Result<LauncherConfig, String> {
+    let table = parse_config_layer(content).map_err(|e| format!("Invalid config: {}", e))?;
+    if table.values().any(has_redacted) {
+        return Err(format!(
+            "Config still contains {} values; fill them in before importing",
+            REDACTED
+        ));
+    }
+
+    let config: LauncherConfig = table
+        .try_into()
+        .map_err(|e| format!("Invalid config: {}", e))?;
+
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+    let staged = dest.with_extension("toml.tmp");
+    fs::write(&staged, content).map_err(|e| format!("Failed to write {:?}: {}", staged, e))?;
+    if dest.exists() {
+        let backup = dest.with_extension("toml.bak");
+        fs::copy(dest, &backup).map_err(|e| format!("Failed to back up {:?}: {}", dest, e))?;
+    }
+    fs::rename(&staged, dest).map_err(|e| format!("Failed to install {:?}: {}", dest, e))?;
+    Ok(config)
+}
+
+/// `--export-config <path>`: returns the process exit code.
+pub fn run_export_config(path: &Path, kiosk: bool) -> i32 {
+    attach_console();
+    let result = launcher_dir()
+        .and_then(|base_dir| export_config(&load_config(&base_dir, kiosk, &None)))
+        .and_then(|content| {
+            fs::write(path, content).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+        });
+    match result {
+        Ok(()) => {
+            println!("Exported launcher config to {}", path.display());
+            0
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            1
+        }
+    }
+}
+
+/// `--import-config <path> [--machine]`: returns the process exit code.
+pub fn run_import_config(path: &Path, machine: bool) -> i32 {
+    attach_console();
+    let dest = if machine {
+        launcher_dir().map(|dir| dir.join(CONFIG_FILE_NAME))
+    } else {
+        user_config_path().ok_or_else(|| "No per-user config location".to_string())
+    };
+    let result = dest.and_then(|dest| {
+        let content =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        import_config(&content, &dest).map(|_| dest)
+    });
+    match result {
+        Ok(dest) => {
+            println!("Installed launcher config at {}", dest.display());
+            0
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SingletonScope;
+    use crate::supervisor::{HelperSpec, RestartPolicy};
+    use crate::test_util::temp_dir;
+
+    fn sample_config() -> LauncherConfig {
+        LauncherConfig {
+            singleton_scope: SingletonScope::PerUser,
+            heartbeat_timeout_sec: 45,
+            entry_module: Some("yakulingo.main".to_string()),
+            restart_cooldown_sec: 300,
+            env_denylist: vec!["AWS_*".to_string()],
+            helpers: vec![HelperSpec {
+                name: "model-server".to_string(),
+                command: vec!["llm\\server.exe".to_string(), "--port=8766".to_string()],
+                restart: RestartPolicy::Always,
+                ready_port: Some(8766),
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn export_then_import_round_trips() {
+        let dir = temp_dir("config-round-trip");
+        let dest = dir.join("user").join(CONFIG_FILE_NAME);
+        let config = sample_config();
+        let exported = export_config(&config).unwrap();
+        assert_eq!(import_config(&exported, &dest).unwrap(), config);
+        assert_eq!(fs::read_to_string(&dest).unwrap(), exported);
+
+        let defaults = export_config(&LauncherConfig::default()).unwrap();
+        assert_eq!(
+            import_config(&defaults, &dest).unwrap(),
+            LauncherConfig::default()
+        );
+        assert_eq!(
+            fs::read_to_string(dest.with_extension("toml.bak")).unwrap(),
+            exported
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn secrets_are_redacted_and_block_import() {
+        let mut config = sample_config();
+        config.helpers[0].command = ["server.exe", "--api-key", "sk-123", "--token=abc", "-v"]
+            .map(String::from)
+            .to_vec();
+        config.updater = vec!["update.exe".to_string(), "/silent".to_string()];
+        let exported = export_config(&config).unwrap();
+        assert!(!exported.contains("sk-123") && !exported.contains("abc"));
+        assert!(exported.contains("--token=<redacted>"));
+        assert!(exported.contains("/silent"));
+
+        let dir = temp_dir("config-redacted");
+        let dest = dir.join(CONFIG_FILE_NAME);
+        assert!(import_config(&exported, &dest).is_err());
+        assert!(!dest.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn invalid_import_leaves_the_current_file() {
+        let dir = temp_dir("config-import-invalid");
+        let dest = dir.join(CONFIG_FILE_NAME);
+        fs::write(&dest, "heartbeat_timeout_sec = 30\n").unwrap();
+        assert!(import_config("singleton_scope = \"everyone\"\n", &dest).is_err());
+        assert!(import_config("not toml [", &dest).is_err());
+        assert_eq!(
+            fs::read_to_string(&dest).unwrap(),
+            "heartbeat_timeout_sec = 30\n"
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
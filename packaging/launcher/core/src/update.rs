@@ -0,0 +1,156 @@
+//! `deferred_update.json`: an update the app scheduled for the next launch.
+//!
+//! Written by the app in the state dir when an update should wait for a
+//! clean moment instead of the exit-code-20 handoff:
+//!
+//! ```json
+//! {"version": "1.4.0", "ts": 1700000000.0}
+//! ```
+//!
+//! The next launch that actually starts the app runs the configured
+//! `updater` first. The file is consumed whether or not it is valid.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::crash::now_secs;
+use crate::launch::install_dir_command;
+use crate::log::log_event;
+use crate::paths::get_state_dir;
+
+/// Requests older than this are dropped rather than applied by surprise.
+const DEFERRED_UPDATE_TTL_SEC: u64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DeferredUpdate {
+    #[serde(default)]
+    pub version: Option<String>,
+    pub ts: f64,
+}
+
+pub fn get_deferred_update_path(base_dir: &Path) -> PathBuf {
+    get_state_dir(base_dir).join("deferred_update.json")
+}
+
+pub fn parse_deferred_update(content: &str, now_secs: u64) -> Result<DeferredUpdate, String> {
+    let update: DeferredUpdate =
+        serde_json::from_str(content).map_err(|e| format!("invalid JSON: {}", e))?;
+    if !update.ts.is_finite() || update.ts < 0.0 {
+        return Err("invalid timestamp".to_string());
+    }
+    let ts_secs = update.ts.floor() as u64;
+    if now_secs < ts_secs || now_secs - ts_secs > DEFERRED_UPDATE_TTL_SEC {
+        return Err("stale".to_string());
+    }
+    Ok(update)
+}
+
+/// Read and remove `deferred_update.json`; the file is consumed either way,
+/// so a failing updater cannot block every later launch.
+pub fn take_deferred_update(path: &Path, log_path: &Option<PathBuf>) -> Option<DeferredUpdate> {
+    let content = fs::read_to_string(path).ok()?;
+    let _ = fs::remove_file(path);
+    match parse_deferred_update(&content, now_secs()) {
+        Ok(update) => Some(update),
+        Err(reason) => {
+            log_event(
+                log_path,
+                &format!("Ignoring deferred update file ({})", reason),
+            );
+            None
+        }
+    }
+}
+
+/// Apply a pending deferred update by running `updater` (program relative
+/// to `base_dir`, then arguments) and waiting for it. A missing updater
+/// config or a failed run is logged and the launch continues.
+pub fn apply_deferred_update(base_dir: &Path, updater: &[String], log_path: &Option<PathBuf>) {
+    let update = match take_deferred_update(&get_deferred_update_path(base_dir), log_path) {
+        Some(value) => value,
+        None => return,
+    };
+    let version = update.version.as_deref().unwrap_or("unknown");
+    let mut command = match install_dir_command(base_dir, updater) {
+        Some(command) => command,
+        None => {
+            log_event(
+                log_path,
+                &format!(
+                    "Deferred update to {} requested but no updater is configured - ignoring",
+                    version
+                ),
+            );
+            return;
+        }
+    };
+    log_event(
+        log_path,
+        &format!("Running updater for deferred update to {}", version),
+    );
+    command.env("YAKULINGO_UPDATE_VERSION", version);
+    match command.status() {
+        Ok(status) => log_event(
+            log_path,
+            &format!("Updater exited (code {})", status.code().unwrap_or(-1)),
+        ),
+        Err(err) => log_event(log_path, &format!("Failed to run updater: {}", err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn present_valid_request_is_returned_and_cleared() {
+        let dir = temp_dir("update-valid");
+        let path = dir.join("deferred_update.json");
+        fs::write(
+            &path,
+            format!(
+                "{{\"version\": \"1.4.0\", \"ts\": {}.5}}",
+                now_secs() - 3600
+            ),
+        )
+        .unwrap();
+        let update = take_deferred_update(&path, &None).unwrap();
+        assert_eq!(update.version.as_deref(), Some("1.4.0"));
+        assert!(!path.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn stale_or_invalid_request_is_ignored_and_cleared() {
+        let dir = temp_dir("update-stale");
+        let path = dir.join("deferred_update.json");
+        let stale = now_secs() - DEFERRED_UPDATE_TTL_SEC - 10;
+        fs::write(&path, format!("{{\"ts\": {}}}", stale)).unwrap();
+        assert_eq!(take_deferred_update(&path, &None), None);
+        assert!(!path.exists());
+
+        fs::write(&path, "{\"version\": \"1.4.0\"}").unwrap();
+        assert_eq!(take_deferred_update(&path, &None), None);
+        assert!(!path.exists());
+        assert_eq!(
+            parse_deferred_update(&format!("{{\"ts\": {}}}", now_secs() + 600), now_secs()),
+            Err("stale".to_string())
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn absent_request_is_a_no_op() {
+        let dir = temp_dir("update-absent");
+        assert_eq!(
+            take_deferred_update(&dir.join("deferred_update.json"), &None),
+            None
+        );
+        // No updater configured and nothing pending: nothing runs.
+        apply_deferred_update(&dir, &[], &None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,570 @@
+//! `--verify-install <dir>`: check that a freshly built install directory
+//! is launchable, for packaging CI.
+//!
+//! The preflight checks are read-only. With `--headless` the app is also
+//! started without a window on a spare port, must answer the status probe,
+//! and is then asked to shut down. The JSON report goes to stdout; the exit
+//! code is non-zero when any check failed.
+
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::config::{check_config_file, load_machine_config, CONFIG_FILE_NAME};
+use crate::entrypoint::resolve_entrypoint;
+use crate::environment::{setup_environment, EnvFilter};
+use crate::heartbeat::HEARTBEAT_POLL_MS;
+use crate::instance::{check_app_status, request_shutdown, AppStatus};
+use crate::launch::{build_app_command, AppSpec, WINDOW_WAIT_SEC};
+use crate::log::{check_log_writable, log_event};
+use crate::python::{check_pyvenv_cfg, check_venv_python, find_python_dir, fix_pyvenv_cfg};
+use crate::runtime::verify_runtime;
+use crate::supervisor::ChildProcess;
+use crate::tail::attach_console;
+
+/// How long the headless app may take to answer the status probe.
+pub const VERIFY_READY_TIMEOUT_SEC: u64 = 120;
+/// How long it may take to exit after the shutdown request.
+pub const VERIFY_STOP_TIMEOUT_SEC: u64 = 10;
+/// Exit code of `--check` when a check failed (1 is a launcher error).
+pub const CHECK_FAILED_EXIT_CODE: i32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+/// Checks run against `subject`, whose fields lead the JSON report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckReport<S> {
+    #[serde(flatten)]
+    pub subject: S,
+    pub ok: bool,
+    pub checks: Vec<CheckResult>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstallSubject {
+    pub install_dir: String,
+}
+
+pub type VerifyReport = CheckReport<InstallSubject>;
+
+impl VerifyReport {
+    pub fn new(install_dir: &Path) -> Self {
+        CheckReport::for_subject(InstallSubject {
+            install_dir: install_dir.display().to_string(),
+        })
+    }
+}
+
+impl<S> CheckReport<S> {
+    /// A report with no checks yet (and so passing).
+    pub fn for_subject(subject: S) -> Self {
+        CheckReport {
+            subject,
+            ok: true,
+            checks: Vec::new(),
+        }
+    }
+
+    /// Record one check; returns whether it passed.
+    pub fn record(&mut self, name: &str, result: Result<String, String>) -> bool {
+        let ok = result.is_ok();
+        self.ok &= ok;
+        self.checks.push(CheckResult {
+            name: name.to_string(),
+            ok,
+            detail: result.unwrap_or_else(|err| err),
+        });
+        ok
+    }
+}
+
+/// What the preflight resolved, for the headless launch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preflight {
+    pub app: AppSpec,
+    pub venv_dir: PathBuf,
+    pub python_dir: PathBuf,
+}
+
+/// The read-only checks; `Some` when every one passed.
+pub fn preflight(install_dir: &Path, report: &mut VerifyReport) -> Option<Preflight> {
+    if !report.record(
+        "install_dir",
+        if install_dir.is_dir() {
+            Ok("exists".to_string())
+        } else {
+            Err("not a directory".to_string())
+        },
+    ) {
+        return None;
+    }
+    let config_path = install_dir.join(CONFIG_FILE_NAME);
+    let config_ok = report.record(
+        "config",
+        check_config_file(&config_path).map(|()| {
+            if config_path.exists() {
+                "valid".to_string()
+            } else {
+                "absent (defaults)".to_string()
+            }
+        }),
+    );
+    let config = load_machine_config(install_dir);
+
+    let venv_dir = install_dir.join(".venv");
+    let python_exe = venv_dir.join("Scripts").join("python.exe");
+    let venv_ok = report.record(
+        "venv_python",
+        check_venv_python(&python_exe, &None).map(|()| python_exe.display().to_string()),
+    );
+    let python_dir = find_python_dir(
+        install_dir,
+        &venv_dir,
+        config.python_version.as_deref(),
+        &None,
+    );
+    let python_ok = report.record(
+        "python_runtime",
+        python_dir
+            .as_ref()
+            .map(|dir| dir.display().to_string())
+            .map_err(Clone::clone),
+    );
+    let entrypoint = resolve_entrypoint(
+        install_dir,
+        config.entrypoint.as_deref(),
+        config.entry_module.as_deref(),
+    );
+    let entrypoint_ok = report.record(
+        "entrypoint",
+        entrypoint
+            .as_ref()
+            .map(|entrypoint| format!("{:?}", entrypoint))
+            .map_err(Clone::clone),
+    );
+
+    if !(config_ok && venv_ok && python_ok && entrypoint_ok) {
+        return None;
+    }
+    let python_dir = python_dir.ok()?;
+    if !report.record(
+        "runtime",
+        verify_runtime(install_dir, &python_dir, &venv_dir, &config)
+            .map(|()| "complete".to_string()),
+    ) {
+        return None;
+    }
+    Some(Preflight {
+        app: AppSpec {
+            python_exe,
+            entrypoint: entrypoint.ok()?,
+            working_dir: install_dir.to_path_buf(),
+            window_title: "YakuLingo".to_string(),
+            // The sandbox needs per-machine ACL setup CI does not have.
+            app_container: false,
+            heartbeat: None,
+            env_filter: EnvFilter {
+                allow: config.env_allowlist.clone(),
+                deny: config.env_denylist.clone(),
+            },
+            setup_progress: None,
+            capture_stderr: false,
+            capture_output: false,
+            window_wait: Duration::from_secs(config.window_wait_sec.unwrap_or(WINDOW_WAIT_SEC)),
+            app_args: Vec::new(),
+            splash: None,
+        },
+        venv_dir,
+        python_dir,
+    })
+}
+
+/// Start the app, wait until `status` reports it running, then `stop` it and
+/// wait for the exit. Each phase is recorded in `report`.
+pub fn headless_check(
+    report: &mut VerifyReport,
+    spawn: impl FnOnce() -> Result<Box<dyn ChildProcess>, String>,
+    status: impl Fn() -> AppStatus,
+    stop: impl FnOnce() -> bool,
+    ready_timeout: Duration,
+    stop_timeout: Duration,
+) -> bool {
+    let poll = Duration::from_millis(HEARTBEAT_POLL_MS).min(ready_timeout / 10);
+    let mut child = match spawn() {
+        Ok(child) => child,
+        Err(err) => return report.record("launch", Err(err)),
+    };
+    report.record("launch", Ok("started".to_string()));
+
+    let started = Instant::now();
+    let ready = loop {
+        if status() == AppStatus::Running {
+            break Ok(format!(
+                "status probe answered after {}ms",
+                started.elapsed().as_millis()
+            ));
+        }
+        match child.try_wait() {
+            Ok(Some(code)) => break Err(format!("app exited early (code {})", code)),
+            Ok(None) => {}
+            Err(err) => break Err(err),
+        }
+        if started.elapsed() >= ready_timeout {
+            break Err(format!("not ready after {}s", ready_timeout.as_secs()));
+        }
+        thread::sleep(poll);
+    };
+    if !report.record("ready", ready) {
+        let _ = child.kill();
+        return false;
+    }
+
+    let requested = stop();
+    let deadline = Instant::now() + stop_timeout;
+    let stopped = loop {
+        match child.try_wait() {
+            Ok(Some(code)) if requested => break Ok(format!("exited (code {})", code)),
+            Ok(Some(code)) => break Err(format!("exited (code {}) without a request", code)),
+            Ok(None) => {}
+            Err(err) => break Err(err),
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            break Err(if requested {
+                format!("did not exit within {}s", stop_timeout.as_secs())
+            } else {
+                "shutdown request failed".to_string()
+            });
+        }
+        thread::sleep(poll);
+    };
+    report.record("stop", stopped)
+}
+
+/// A port nothing listens on right now.
+fn spare_port() -> Result<u16, String> {
+    TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .map_err(|e| format!("no free port: {}", e))
+}
+
+/// Run every check against `install_dir`.
+pub fn verify_install(install_dir: &Path, headless: bool) -> VerifyReport {
+    let mut report = VerifyReport::new(install_dir);
+    let preflight = match preflight(install_dir, &mut report) {
+        Some(value) => value,
+        None => return report,
+    };
+    if !headless {
+        return report;
+    }
+    // Same preparation as a real launch: the first launch of an unpacked
+    // build rewrites pyvenv.cfg too.
+    if !report.record(
+        "environment",
+        fix_pyvenv_cfg(&preflight.venv_dir, &preflight.python_dir).map(|()| {
+            setup_environment(
+                install_dir,
+                &preflight.venv_dir,
+                &preflight.python_dir,
+                &None,
+            );
+            "prepared".to_string()
+        }),
+    ) {
+        return report;
+    }
+    let port = match spare_port() {
+        Ok(port) => port,
+        Err(err) => {
+            report.record("launch", Err(err));
+            return report;
+        }
+    };
+    let app = preflight.app;
+    headless_check(
+        &mut report,
+        || {
+            let mut command = build_app_command(&app, false);
+            command
+                .env("YAKULINGO_NO_AUTO_OPEN", "1")
+                .env("YAKULINGO_PORT", port.to_string())
+                .env(
+                    "YAKULINGO_INSTANCE_MUTEX",
+                    format!("Local\\YakuLingoVerify-{}", std::process::id()),
+                )
+                .env("YAKULINGO_ALLOW_MULTI_INSTANCE", "1");
+            #[cfg(windows)]
+            {
+                use std::os::windows::process::CommandExt;
+                const CREATE_NO_WINDOW: u32 = 0x08000000;
+                command.creation_flags(CREATE_NO_WINDOW);
+            }
+            command
+                .spawn()
+                .map(|child| Box::new(child) as Box<dyn ChildProcess>)
+                .map_err(|e| format!("Failed to start app: {}", e))
+        },
+        || check_app_status(port),
+        || request_shutdown(port, "verify_install", false),
+        Duration::from_secs(VERIFY_READY_TIMEOUT_SEC),
+        Duration::from_secs(VERIFY_STOP_TIMEOUT_SEC),
+    );
+    report
+}
+
+/// The app port, as a launch would find it.
+fn check_port(port: u16, status: AppStatus) -> Result<String, String> {
+    match status {
+        AppStatus::NotRunning => Ok(format!("{} is free", port)),
+        AppStatus::Running => Ok(format!(
+            "{} answers as YakuLingo (an instance is running)",
+            port
+        )),
+        AppStatus::PortInUse => Err(format!("{} is held by another program", port)),
+    }
+}
+
+/// `--check`: everything a launch checks before starting Python, for the
+/// install the launcher runs from. Nothing is changed or started.
+pub fn self_check(
+    base_dir: &Path,
+    log_path: &Option<PathBuf>,
+    port: u16,
+    status: impl FnOnce(u16) -> AppStatus,
+) -> VerifyReport {
+    let mut report = VerifyReport::new(base_dir);
+    report.record("log", check_log_writable(log_path));
+    report.record("port", check_port(port, status(port)));
+    if let Some(preflight) = preflight(base_dir, &mut report) {
+        report.record(
+            "pyvenv_cfg",
+            check_pyvenv_cfg(&preflight.venv_dir, &preflight.python_dir),
+        );
+    }
+    for check in &report.checks {
+        log_event(
+            log_path,
+            &format!(
+                "Self-check {}: {} ({})",
+                check.name,
+                if check.ok { "ok" } else { "FAILED" },
+                check.detail
+            ),
+        );
+    }
+    report
+}
+
+/// Print `report` as JSON; returns `failed_code` unless every check passed.
+pub fn print_report<S: Serialize>(report: &CheckReport<S>, failed_code: i32) -> i32 {
+    match serde_json::to_string_pretty(report) {
+        Ok(json) => println!("{}", json),
+        Err(err) => eprintln!("Failed to write report: {}", err),
+    }
+    if report.ok {
+        0
+    } else {
+        failed_code
+    }
+}
+
+/// `--verify-install`: print the report and return the process exit code.
+pub fn run_verify_install(install_dir: &Path, headless: bool) -> i32 {
+    attach_console();
+    print_report(&verify_install(install_dir, headless), 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::cell::Cell;
+    use std::fs;
+    use std::rc::Rc;
+
+    /// An install dir the preflight accepts.
+    fn fixture(name: &str) -> PathBuf {
+        let dir = temp_dir(name);
+        let python_dir = dir
+            .join(".uv-python")
+            .join("cpython-3.12.4-windows-x86_64-none");
+        fs::create_dir_all(python_dir.join("Lib")).unwrap();
+        fs::write(python_dir.join("python.exe"), "MZ").unwrap();
+        fs::write(python_dir.join("Lib").join("os.py"), "import abc\n").unwrap();
+        fs::create_dir_all(dir.join(".venv").join("Scripts")).unwrap();
+        fs::write(dir.join(".venv").join("Scripts").join("python.exe"), "MZ").unwrap();
+        let site_packages = dir.join(".venv").join("Lib").join("site-packages");
+        fs::create_dir_all(site_packages.join("nicegui-2.0.dist-info")).unwrap();
+        fs::write(
+            dir.join(".venv").join("pyvenv.cfg"),
+            format!("home = {}\n", python_dir.display()),
+        )
+        .unwrap();
+        fs::write(dir.join("app.py"), "").unwrap();
+        dir
+    }
+
+    /// Self-test child: answers the status probe after a few polls and
+    /// exits once asked to stop.
+    struct SelfTestChild {
+        stop_requested: Rc<Cell<bool>>,
+    }
+
+    impl ChildProcess for SelfTestChild {
+        fn try_wait(&mut self) -> Result<Option<i32>, String> {
+            Ok(self.stop_requested.get().then_some(0))
+        }
+
+        fn kill(&mut self) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    fn names(report: &VerifyReport) -> Vec<(&str, bool)> {
+        report
+            .checks
+            .iter()
+            .map(|check| (check.name.as_str(), check.ok))
+            .collect()
+    }
+
+    #[test]
+    fn fixture_install_passes_preflight_and_headless_launch() {
+        let dir = fixture("verify-ok");
+        let mut report = VerifyReport::new(&dir);
+        let preflight = preflight(&dir, &mut report).expect("preflight passes");
+        assert_eq!(preflight.app.working_dir, dir);
+
+        let stop_requested = Rc::new(Cell::new(false));
+        let polls = Cell::new(0);
+        let ok = headless_check(
+            &mut report,
+            || {
+                Ok(Box::new(SelfTestChild {
+                    stop_requested: Rc::clone(&stop_requested),
+                }) as Box<dyn ChildProcess>)
+            },
+            || {
+                polls.set(polls.get() + 1);
+                if polls.get() >= 3 {
+                    AppStatus::Running
+                } else {
+                    AppStatus::NotRunning
+                }
+            },
+            || {
+                stop_requested.set(true);
+                true
+            },
+            Duration::from_millis(500),
+            Duration::from_millis(500),
+        );
+        assert!(ok);
+        assert!(report.ok);
+        assert_eq!(
+            names(&report),
+            [
+                ("install_dir", true),
+                ("config", true),
+                ("venv_python", true),
+                ("python_runtime", true),
+                ("entrypoint", true),
+                ("runtime", true),
+                ("launch", true),
+                ("ready", true),
+                ("stop", true),
+            ]
+        );
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["ok"], true);
+        assert_eq!(json["install_dir"], dir.display().to_string());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn broken_install_fails_with_a_report() {
+        let dir = fixture("verify-broken");
+        fs::remove_file(dir.join("app.py")).unwrap();
+        fs::write(
+            dir.join(CONFIG_FILE_NAME),
+            "singleton_scope = \"everyone\"\n",
+        )
+        .unwrap();
+        let report = verify_install(&dir, true);
+        assert!(!report.ok);
+        assert_eq!(
+            names(&report),
+            [
+                ("install_dir", true),
+                ("config", false),
+                ("venv_python", true),
+                ("python_runtime", true),
+                ("entrypoint", false),
+            ],
+            "no launch after a failed preflight"
+        );
+        assert!(!verify_install(&dir.join("missing"), false).ok);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn self_check_reports_every_launch_precondition() {
+        let dir = fixture("verify-self-check");
+        let log_path = Some(dir.join("launcher.log"));
+        let report = self_check(&dir, &log_path, 8765, |_| AppStatus::NotRunning);
+        assert!(report.ok, "{:?}", report);
+        assert_eq!(
+            names(&report),
+            [
+                ("log", true),
+                ("port", true),
+                ("install_dir", true),
+                ("config", true),
+                ("venv_python", true),
+                ("python_runtime", true),
+                ("entrypoint", true),
+                ("runtime", true),
+                ("pyvenv_cfg", true),
+            ]
+        );
+        let log = fs::read_to_string(dir.join("launcher.log")).unwrap();
+        assert!(log.contains("Self-check port: ok (8765 is free)"));
+
+        let report = self_check(&dir, &None, 8765, |_| AppStatus::PortInUse);
+        assert!(!report.ok);
+        assert_eq!(names(&report)[..2], [("log", false), ("port", false)]);
+        assert_eq!(print_report(&report, CHECK_FAILED_EXIT_CODE), 2);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn app_that_never_answers_fails_ready() {
+        let dir = temp_dir("verify-not-ready");
+        let mut report = VerifyReport::new(&dir);
+        let ok = headless_check(
+            &mut report,
+            || {
+                Ok(Box::new(SelfTestChild {
+                    stop_requested: Rc::new(Cell::new(false)),
+                }) as Box<dyn ChildProcess>)
+            },
+            || AppStatus::NotRunning,
+            || true,
+            Duration::from_millis(50),
+            Duration::from_millis(50),
+        );
+        assert!(!ok);
+        assert_eq!(names(&report), [("launch", true), ("ready", false)]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,891 @@
+//! Watchdog loop that restarts the app after unexpected exits.
+
+use std::collections::hash_map::RandomState;
+use std::env;
+use std::hash::BuildHasher;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::app_output::{write_app_output, AppOutput, APP_OUTPUT_BYTES};
+use crate::crash::{
+    crash_dialog_message, get_crash_history_path, get_startup_error_path, is_crash_exit, now_secs,
+    record_crash, take_startup_error, CrashRecord, StartupError,
+};
+use crate::dialog::show_error;
+use crate::events::InstallerEvents;
+use crate::heartbeat::{is_heartbeat_stale, Heartbeat, HEARTBEAT_POLL_MS};
+use crate::instance::{
+    check_app_status, is_instance_mutex_present, read_reported_port, request_shutdown, AppStatus,
+    InstanceScope,
+};
+use crate::launch::{launch_app, wait_for_startup, AppProcess, AppSpec, LaunchError};
+use crate::log::log_event;
+use crate::paths::{
+    file_mtime, get_launcher_context_path, get_launcher_state_path, remove_pid_file, write_pid_file,
+};
+use crate::schedule::{local_second_of_day, MaintenanceWindow};
+use crate::shutdown::TrackedChild;
+use crate::snapshot::{snapshot_on_exit, CrashContext, SnapshotPolicy};
+use crate::state::read_and_clear_launcher_state;
+use crate::status::{LauncherPhase, StatusFile};
+use crate::stderr_tail::{StderrTail, STDERR_TAIL_LINES};
+use crate::supervisor::{
+    spawn_helper, ChildProcess, HelperSpec, HelperSupervisor, HELPER_READY_TIMEOUT_SEC,
+};
+use crate::watcher::ConfigWatcher;
+use crate::{INSTANCE_ALREADY_RUNNING_CODE, UPDATE_IN_PROGRESS_CODE, USER_EXIT_CODE};
+
+pub const MAX_RESTARTS: u32 = 3;
+pub const RESTART_BACKOFF_BASE_SEC: u64 = 1;
+pub const RESTART_RESET_AFTER_SEC: u64 = 60;
+/// Default ceiling on a single backoff (also used by helpers).
+pub const RESTART_BACKOFF_MAX_SEC: u64 = 30;
+/// Each restart delay is randomized by up to this fraction either way, so
+/// machines that crash together do not restart together.
+pub const RESTART_JITTER: f64 = 0.25;
+/// How long a config-change restart or installer stop waits for a graceful
+/// exit before killing.
+const GRACEFUL_STOP_SEC: u64 = 10;
+
+/// Restart limits; `launcher.toml` overrides the constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartLimits {
+    /// Unexpected exits restarted before giving up (or cooling down).
+    pub max_restarts: u32,
+    /// First restart delay; later ones double up to `backoff_max`.
+    pub backoff_base: Duration,
+    /// Ceiling on a single delay, jitter included.
+    pub backoff_max: Duration,
+    /// A run at least this long starts a fresh budget.
+    pub reset_after: Duration,
+    /// Give up once restarts have gone on this long without a run reaching
+    /// `reset_after`, whatever the attempt count or `restart_always`.
+    pub give_up_after: Option<Duration>,
+}
+
+impl Default for RestartLimits {
+    fn default() -> Self {
+        RestartLimits {
+            max_restarts: MAX_RESTARTS,
+            backoff_base: Duration::from_secs(RESTART_BACKOFF_BASE_SEC),
+            backoff_max: Duration::from_secs(RESTART_BACKOFF_MAX_SEC),
+            reset_after: Duration::from_secs(RESTART_RESET_AFTER_SEC),
+            give_up_after: None,
+        }
+    }
+}
+
+/// Watchdog behaviour that comes from configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WatchdogPolicy {
+    /// `YAKULINGO_ALLOW_MULTI_INSTANCE=1`: ignore the mutex in the handoff guard.
+    pub allow_multi_instance: bool,
+    pub restart_limits: RestartLimits,
+    /// Never give up after `max_restarts` (kiosks).
+    pub restart_always: bool,
+    /// Write a diagnostic snapshot on crash exits.
+    pub crash_snapshots: SnapshotPolicy,
+    /// Once `max_restarts` is used up, wait this long and try again with a
+    /// fresh budget instead of giving up.
+    pub restart_cooldown: Option<Duration>,
+    /// Daily window in which exits are not restarted until it ends.
+    pub maintenance_window: Option<MaintenanceWindow>,
+    /// Files whose changes restart the app; empty disables the watcher.
+    pub watch_files: Vec<PathBuf>,
+    /// Helper processes supervised alongside the app.
+    pub helpers: Vec<HelperSpec>,
+    /// `--no-restart`: stop after the first exit of any kind.
+    pub no_restart: bool,
+}
+
+/// Why `supervise` returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuperviseExit {
+    /// The app exited for good (user exit, update, handoff, restart limit).
+    Stopped,
+    /// A watched config file changed; the app was stopped so the caller can
+    /// reload configuration and supervise again.
+    ConfigChanged,
+    /// The user accepted restarting as administrator; `run` starts the
+    /// elevated launcher once this one has unwound.
+    RelaunchElevated,
+}
+
+/// What to do after an unexpected exit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RestartDecision {
+    /// Restart after `delay`; `attempt` counts from 1.
+    Restart {
+        delay: Duration,
+        attempt: u32,
+    },
+    /// Budget used up: wait, then start again with a fresh budget.
+    Cooldown(Duration),
+    GiveUp,
+    /// `give_up_after` has passed since the first of these restarts.
+    OutOfTime,
+}
+
+/// A uniformly random value in [-1, 1].
+fn random_unit() -> f64 {
+    // Every `RandomState` is keyed differently; no rand dependency needed.
+    let bits = RandomState::new().hash_one(std::process::id());
+    (bits >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+}
+
+/// `delay` moved by `unit` (in [-1, 1]) times `RESTART_JITTER`, then capped.
+fn jittered(delay: Duration, unit: f64, max: Duration) -> Duration {
+    delay
+        .mul_f64(1.0 + RESTART_JITTER * unit.clamp(-1.0, 1.0))
+        .min(max)
+}
+
+/// Restart attempts left before the watchdog gives up (or cools down).
+#[derive(Debug)]
+struct RestartBudget {
+    attempts: u32,
+    backoff: Duration,
+    backoff_base: Duration,
+    /// When the first restart since the last reset was decided.
+    first_restart: Option<Instant>,
+    /// Source of `random_unit` values; fixed in tests.
+    jitter: fn() -> f64,
+}
+
+impl RestartBudget {
+    fn new(limits: &RestartLimits) -> Self {
+        RestartBudget {
+            attempts: 0,
+            backoff: limits.backoff_base,
+            backoff_base: limits.backoff_base,
+            first_restart: None,
+            jitter: random_unit,
+        }
+    }
+
+    /// A healthy run (or a maintenance wait): a fresh budget and clock.
+    fn reset(&mut self) {
+        self.refill();
+        self.first_restart = None;
+    }
+
+    /// Fresh attempts; the `give_up_after` clock keeps running.
+    fn refill(&mut self) {
+        self.attempts = 0;
+        self.backoff = self.backoff_base;
+    }
+
+    /// Spend one attempt, or decide what happens once none are left.
+    fn next(&mut self, policy: &WatchdogPolicy, now: Instant) -> RestartDecision {
+        let limits = &policy.restart_limits;
+        let first_restart = *self.first_restart.get_or_insert(now);
+        if limits
+            .give_up_after
+            .is_some_and(|limit| now.saturating_duration_since(first_restart) >= limit)
+        {
+            return RestartDecision::OutOfTime;
+        }
+        if !policy.restart_always && self.attempts >= limits.max_restarts {
+            return match policy.restart_cooldown {
+                Some(cooldown) => {
+                    self.refill();
+                    RestartDecision::Cooldown(cooldown)
+                }
+                None => RestartDecision::GiveUp,
+            };
+        }
+        let decision = RestartDecision::Restart {
+            delay: jittered(self.backoff, (self.jitter)(), limits.backoff_max),
+            attempt: self.attempts + 1,
+        };
+        self.attempts = self.attempts.saturating_add(1);
+        self.backoff = self
+            .backoff
+            .saturating_mul(2)
+            .max(Duration::from_secs(1))
+            .min(limits.backoff_max);
+        decision
+    }
+}
+
+/// How `wait_for_exit` returned.
+enum WaitOutcome {
+    Exited(i32),
+    ConfigChanged,
+    StopRequested,
+}
+
+/// Launch the app and keep restarting it until it exits for a reason that
+/// should not trigger a restart (user exit, update, handoff, restart limit).
+pub fn supervise(
+    app: &AppSpec,
+    base_dir: &Path,
+    pid_path: &Option<PathBuf>,
+    log_path: &Option<PathBuf>,
+    instance: &InstanceScope,
+    policy: &WatchdogPolicy,
+    events: &InstallerEvents,
+) -> Result<SuperviseExit, String> {
+    let launcher_state_path = get_launcher_state_path(base_dir);
+    let startup_error_path = get_startup_error_path(base_dir);
+    let crash_history_path = get_crash_history_path(base_dir);
+    let context_path = get_launcher_context_path(base_dir);
+    let mut last_startup_error: Option<StartupError> = None;
+    let mut last_stderr_tail: Option<String> = None;
+    let mut budget = RestartBudget::new(&policy.restart_limits);
+    let status = StatusFile::for_instance(base_dir, instance.name.as_deref(), log_path);
+    let mut watcher =
+        (!policy.watch_files.is_empty()).then(|| ConfigWatcher::new(policy.watch_files.clone()));
+    let mut helpers = HelperSupervisor::start(
+        &policy.helpers,
+        |spec| spawn_helper(base_dir, spec),
+        Instant::now(),
+        log_path,
+    );
+    helpers.wait_ready(Duration::from_secs(HELPER_READY_TIMEOUT_SEC), log_path);
+
+    loop {
+        let start_time = Instant::now();
+        let mut child = match launch_app(app, log_path) {
+            Ok(child) => child,
+            Err(LaunchError::Failed(err)) => return Err(err),
+            Err(LaunchError::RelaunchElevated) => {
+                remove_pid_file(pid_path);
+                return Ok(SuperviseExit::RelaunchElevated);
+            }
+        };
+        // Cleared once the app exits: a successor it starts is left alone.
+        let tracked = TrackedChild::new(child.id());
+        status.write(
+            LauncherPhase::WatchdogActive,
+            Some(child.id()),
+            budget.attempts,
+        );
+        // Read from the start so the pipes never fill; dropped on clean exits.
+        let (stderr_tail, app_output) = capture_app_streams(&mut child, app);
+        write_pid_file(pid_path, child.id(), log_path);
+        log_event(
+            log_path,
+            "Python process spawned, waiting for it to be ready",
+        );
+        let readiness = wait_for_startup(app, instance.port, &mut child, log_path);
+        log_event(
+            log_path,
+            &format!(
+                "Startup wait ended: {} - watchdog active",
+                readiness.describe()
+            ),
+        );
+
+        let exit_code = match wait_for_exit(
+            &mut child,
+            app.heartbeat.as_ref(),
+            watcher.as_mut(),
+            &mut helpers,
+            events,
+            instance.port,
+            log_path,
+        )? {
+            WaitOutcome::Exited(code) => code,
+            WaitOutcome::ConfigChanged => return Ok(SuperviseExit::ConfigChanged),
+            WaitOutcome::StopRequested => {
+                // The app recorded a user exit on the way out; consume it here.
+                read_and_clear_launcher_state(&launcher_state_path, log_path);
+                log_event(
+                    log_path,
+                    "Installer stop request handled - stopping restart",
+                );
+                break;
+            }
+        };
+        drop(tracked);
+        let elapsed = start_time.elapsed();
+
+        if is_crash_exit(exit_code) {
+            let startup_error = take_startup_error(&startup_error_path, log_path);
+            if let Some(error) = &startup_error {
+                log_event(
+                    log_path,
+                    &format!(
+                        "App reported startup error (code {}): {}",
+                        exit_code, error.reason
+                    ),
+                );
+            }
+            let tail = stderr_tail.and_then(StderrTail::finish);
+            if let Some(tail) = &tail {
+                log_event(
+                    log_path,
+                    &format!("App stderr before exit (code {}):\n{}", exit_code, tail),
+                );
+            }
+            let record = CrashRecord {
+                ts: now_secs(),
+                exit_code,
+                startup_error: startup_error.clone(),
+                stderr_tail: tail.clone(),
+            };
+            record_crash(&crash_history_path, &record, log_path);
+            save_app_output(app_output.and_then(AppOutput::finish), &record, log_path);
+            if let Some(log_file) = log_path {
+                let crash = CrashContext {
+                    ts: record.ts,
+                    exit_code,
+                    startup_error: startup_error.as_ref(),
+                    stderr_tail: tail.as_deref(),
+                    log_file: Some(log_file),
+                    context_path: &context_path,
+                };
+                let log_dir = log_file.parent().unwrap_or(base_dir);
+                match snapshot_on_exit(policy.crash_snapshots, log_dir, &crash, env::vars()) {
+                    Some(Ok(path)) => {
+                        log_event(log_path, &format!("Crash snapshot written: {:?}", path))
+                    }
+                    Some(Err(err)) => log_event(
+                        log_path,
+                        &format!("Failed to write crash snapshot: {}", err),
+                    ),
+                    None => {}
+                }
+            }
+            last_startup_error = startup_error;
+            last_stderr_tail = tail;
+        }
+
+        if let Some(reason) = read_and_clear_launcher_state(&launcher_state_path, log_path) {
+            log_event(
+                log_path,
+                &format!("Launcher state detected ({}) - stopping restart", reason),
+            );
+            break;
+        }
+
+        if events.stop_requested() {
+            log_event(
+                log_path,
+                &format!(
+                    "Installer stop requested (exit code {}) - stopping restart",
+                    exit_code
+                ),
+            );
+            break;
+        }
+
+        if exit_code == USER_EXIT_CODE {
+            log_event(
+                log_path,
+                "Explicit user exit detected (exit code 10) - stopping restart",
+            );
+            break;
+        }
+
+        if exit_code == INSTANCE_ALREADY_RUNNING_CODE {
+            log_event(
+                log_path,
+                "Existing instance detected (exit code 11) - stopping restart",
+            );
+            break;
+        }
+
+        if exit_code == UPDATE_IN_PROGRESS_CODE {
+            log_event(
+                log_path,
+                "Update in progress detected (exit code 20) - stopping restart",
+            );
+            break;
+        }
+
+        if policy.no_restart {
+            log_event(
+                log_path,
+                &format!(
+                    "--no-restart: app exited (code {}) - not restarting",
+                    exit_code
+                ),
+            );
+            if is_crash_exit(exit_code) {
+                if let Some(message) = crash_dialog_message(
+                    exit_code,
+                    last_startup_error.as_ref(),
+                    last_stderr_tail.as_deref(),
+                ) {
+                    show_error(&message);
+                }
+            }
+            break;
+        }
+
+        // Guard against duplicate launches during process handoff.
+        // Example: app.py can intentionally spawn a successor process and exit 0.
+        // If a valid instance is already alive, do not start another process.
+        if instance_running(instance, policy.allow_multi_instance, &context_path) {
+            log_event(
+                log_path,
+                &format!(
+                    "Detected active instance after child exit (code {}) - stopping restart",
+                    exit_code
+                ),
+            );
+            break;
+        }
+
+        // Do not fight updates run during the maintenance window: hold the
+        // restart until it closes, then stand down if the update relaunched the app.
+        if let Some(window) = &policy.maintenance_window {
+            let now = local_second_of_day();
+            if window.contains(now) {
+                let wait = window.seconds_until_end(now);
+                log_event(
+                    log_path,
+                    &format!(
+                        "UI exited (code {}) during maintenance window {} - waiting {}s before restart",
+                        exit_code, window, wait
+                    ),
+                );
+                status.write(LauncherPhase::Restarting, None, budget.attempts);
+                sleep_polling(&mut helpers, Duration::from_secs(wait as u64), log_path);
+                if instance_running(instance, policy.allow_multi_instance, &context_path) {
+                    log_event(
+                        log_path,
+                        "Instance started during maintenance window - stopping restart",
+                    );
+                    break;
+                }
+                budget.reset();
+                continue;
+            }
+        }
+
+        if elapsed > policy.restart_limits.reset_after {
+            budget.reset();
+        }
+
+        match budget.next(policy, Instant::now()) {
+            RestartDecision::Restart { delay, attempt } => {
+                let limit = if policy.restart_always {
+                    "unlimited".to_string()
+                } else {
+                    policy.restart_limits.max_restarts.to_string()
+                };
+                log_event(
+                    log_path,
+                    &format!(
+                        "UI exited (code {}), restarting in {:.1}s (attempt {}/{})",
+                        exit_code,
+                        delay.as_secs_f64(),
+                        attempt,
+                        limit
+                    ),
+                );
+                status.write(LauncherPhase::Restarting, None, attempt);
+                sleep_polling(&mut helpers, delay, log_path);
+            }
+            RestartDecision::Cooldown(cooldown) => {
+                log_event(
+                    log_path,
+                    &format!(
+                        "Restart limit reached (exit code {}) - cooling down {}s before retrying",
+                        exit_code,
+                        cooldown.as_secs()
+                    ),
+                );
+                status.write(LauncherPhase::Restarting, None, budget.attempts);
+                sleep_polling(&mut helpers, cooldown, log_path);
+                if instance_running(instance, policy.allow_multi_instance, &context_path) {
+                    log_event(
+                        log_path,
+                        "Instance started during restart cooldown - stopping restart",
+                    );
+                    break;
+                }
+                log_event(log_path, "Restart cooldown over - restart budget reset");
+            }
+            decision @ (RestartDecision::GiveUp | RestartDecision::OutOfTime) => {
+                let reason = if decision == RestartDecision::OutOfTime {
+                    "Restart time limit reached"
+                } else {
+                    "Restart limit reached"
+                };
+                log_event(
+                    log_path,
+                    &format!("{} (exit code {}) - watchdog stopping", reason, exit_code),
+                );
+                status.write(LauncherPhase::GaveUp, None, budget.attempts);
+                if let Some(message) = crash_dialog_message(
+                    exit_code,
+                    last_startup_error.as_ref(),
+                    last_stderr_tail.as_deref(),
+                ) {
+                    show_error(&message);
+                }
+                break;
+            }
+        }
+    }
+
+    remove_pid_file(pid_path);
+    Ok(SuperviseExit::Stopped)
+}
+
+/// Start the readers for whatever the app's pipes were opened for. With both
+/// captures on, stderr feeds the tail and the output ring through one reader.
+fn capture_app_streams(
+    child: &mut AppProcess,
+    app: &AppSpec,
+) -> (Option<StderrTail>, Option<AppOutput>) {
+    let mut output = app.capture_output.then(|| AppOutput::new(APP_OUTPUT_BYTES));
+    if let (Some(output), Some(stdout)) = (output.as_mut(), child.take_stdout()) {
+        output.drain(stdout);
+    }
+    let tail = match (child.take_stderr(), output.as_mut()) {
+        (Some(stderr), Some(output)) if app.capture_stderr => {
+            Some(StderrTail::capture(output.tee(stderr), STDERR_TAIL_LINES))
+        }
+        (Some(stderr), Some(output)) => {
+            output.drain(stderr);
+            None
+        }
+        (Some(stderr), None) => Some(StderrTail::capture(stderr, STDERR_TAIL_LINES)),
+        (None, _) => None,
+    };
+    (tail, output)
+}
+
+/// Write a crashed run's output to `app.log` next to `launcher.log`.
+fn save_app_output(output: Option<String>, record: &CrashRecord, log_path: &Option<PathBuf>) {
+    let (Some(output), Some(log_dir)) = (output, log_path.as_ref().and_then(|p| p.parent())) else {
+        return;
+    };
+    match write_app_output(log_dir, record.ts, record.exit_code, &output) {
+        Ok(path) => log_event(
+            log_path,
+            &format!(
+                "App output before exit (code {}) saved to {:?}",
+                record.exit_code, path
+            ),
+        ),
+        Err(err) => log_event(log_path, &format!("Failed to save app output: {}", err)),
+    }
+}
+
+/// Sleep while keeping helpers supervised.
+fn sleep_polling<S>(
+    helpers: &mut HelperSupervisor<S>,
+    duration: Duration,
+    log_path: &Option<PathBuf>,
+) where
+    S: FnMut(&HelperSpec) -> Result<Box<dyn ChildProcess>, String>,
+{
+    if helpers.is_empty() {
+        thread::sleep(duration);
+        return;
+    }
+    let deadline = Instant::now() + duration;
+    while Instant::now() < deadline {
+        helpers.poll(Instant::now(), log_path);
+        thread::sleep(
+            deadline
+                .saturating_duration_since(Instant::now())
+                .min(Duration::from_millis(HEARTBEAT_POLL_MS)),
+        );
+    }
+}
+
+/// Whether a valid instance (mutex owner or answering app, on its reported
+/// port or ours) is alive.
+fn instance_running(
+    instance: &InstanceScope,
+    allow_multi_instance: bool,
+    context_path: &Path,
+) -> bool {
+    let mutex_present = !allow_multi_instance && is_instance_mutex_present(&instance.mutex_name);
+    mutex_present
+        || read_reported_port(context_path, instance.port)
+            .is_some_and(|port| check_app_status(port) == AppStatus::Running)
+        || check_app_status(instance.port) == AppStatus::Running
+}
+
+/// Wait for the app to exit. With a heartbeat, config watcher, helpers or
+/// installer events, poll instead: a stale heartbeat kills the app so the
+/// restart path runs, a settled config change or installer stop request
+/// stops it gracefully, and exited helpers are restarted.
+fn wait_for_exit<S>(
+    child: &mut AppProcess,
+    heartbeat: Option<&Heartbeat>,
+    mut watcher: Option<&mut ConfigWatcher>,
+    helpers: &mut HelperSupervisor<S>,
+    events: &InstallerEvents,
+    port: u16,
+    log_path: &Option<PathBuf>,
+) -> Result<WaitOutcome, String>
+where
+    S: FnMut(&HelperSpec) -> Result<Box<dyn ChildProcess>, String>,
+{
+    if heartbeat.is_none() && watcher.is_none() && helpers.is_empty() && !events.is_active() {
+        return child.wait().map(WaitOutcome::Exited);
+    }
+    let started = SystemTime::now();
+    loop {
+        if let Some(exit_code) = child.try_wait()? {
+            return Ok(WaitOutcome::Exited(exit_code));
+        }
+        if let Some(heartbeat) = heartbeat {
+            let last_beat = file_mtime(&heartbeat.path);
+            if is_heartbeat_stale(last_beat, started, SystemTime::now(), heartbeat.timeout) {
+                log_event(
+                    log_path,
+                    &format!(
+                        "Heartbeat older than {}s - app is hung, stopping it",
+                        heartbeat.timeout.as_secs()
+                    ),
+                );
+                child.kill()?;
+                return child.wait().map(WaitOutcome::Exited);
+            }
+        }
+        if let Some(watcher) = watcher.as_deref_mut() {
+            if watcher.poll(Instant::now()) {
+                log_event(
+                    log_path,
+                    &format!(
+                        "Config change detected in {:?} - restarting app",
+                        watcher.files()
+                    ),
+                );
+                stop_gracefully(child, port, "config_change", true, log_path)?;
+                return Ok(WaitOutcome::ConfigChanged);
+            }
+        }
+        if events.stop_requested() {
+            log_event(log_path, "Installer stop request received - stopping app");
+            stop_gracefully(child, port, "installer_stop", false, log_path)?;
+            return Ok(WaitOutcome::StopRequested);
+        }
+        helpers.poll(Instant::now(), log_path);
+        thread::sleep(Duration::from_millis(HEARTBEAT_POLL_MS));
+    }
+}
+
+/// Ask the app to exit (for a restart, or for good); kill it if it does not
+/// within the grace period.
+fn stop_gracefully(
+    child: &mut AppProcess,
+    port: u16,
+    reason: &str,
+    restart: bool,
+    log_path: &Option<PathBuf>,
+) -> Result<(), String> {
+    if request_shutdown(port, reason, restart) {
+        let deadline = Instant::now() + Duration::from_secs(GRACEFUL_STOP_SEC);
+        while Instant::now() < deadline {
+            if child.try_wait()?.is_some() {
+                return Ok(());
+            }
+            thread::sleep(Duration::from_millis(HEARTBEAT_POLL_MS));
+        }
+        log_event(
+            log_path,
+            "App did not exit after shutdown request - stopping it",
+        );
+    }
+    child.kill()?;
+    child.wait().map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spend(budget: &mut RestartBudget, policy: &WatchdogPolicy) -> Vec<RestartDecision> {
+        (0..policy.restart_limits.max_restarts)
+            .map(|_| budget.next(policy, Instant::now()))
+            .collect()
+    }
+
+    /// A budget whose delays are the unjittered backoff.
+    fn exact_budget(policy: &WatchdogPolicy) -> RestartBudget {
+        RestartBudget {
+            jitter: || 0.0,
+            ..RestartBudget::new(&policy.restart_limits)
+        }
+    }
+
+    #[test]
+    fn budget_gives_up_by_default() {
+        let policy = WatchdogPolicy::default();
+        let mut budget = exact_budget(&policy);
+        let delays: Vec<_> = spend(&mut budget, &policy)
+            .into_iter()
+            .map(|decision| match decision {
+                RestartDecision::Restart { delay, .. } => delay.as_secs(),
+                other => panic!("unexpected {:?}", other),
+            })
+            .collect();
+        assert_eq!(delays, vec![1, 2, 4]);
+        assert_eq!(
+            budget.next(&policy, Instant::now()),
+            RestartDecision::GiveUp
+        );
+    }
+
+    #[test]
+    fn configured_limits_replace_the_defaults() {
+        let policy = WatchdogPolicy {
+            restart_limits: RestartLimits {
+                max_restarts: 5,
+                backoff_base: Duration::from_secs(10),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut budget = exact_budget(&policy);
+        let delays: Vec<_> = spend(&mut budget, &policy)
+            .into_iter()
+            .map(|decision| match decision {
+                RestartDecision::Restart { delay, .. } => delay.as_secs(),
+                other => panic!("unexpected {:?}", other),
+            })
+            .collect();
+        assert_eq!(delays, vec![10, 20, 30, 30, 30]);
+        assert_eq!(
+            budget.next(&policy, Instant::now()),
+            RestartDecision::GiveUp
+        );
+        budget.reset();
+        assert_eq!(
+            budget.next(&policy, Instant::now()),
+            RestartDecision::Restart {
+                delay: Duration::from_secs(10),
+                attempt: 1
+            }
+        );
+    }
+
+    #[test]
+    fn cooldown_resets_the_budget() {
+        let policy = WatchdogPolicy {
+            restart_cooldown: Some(Duration::from_secs(300)),
+            ..Default::default()
+        };
+        let mut budget = exact_budget(&policy);
+        spend(&mut budget, &policy);
+        assert_eq!(
+            budget.next(&policy, Instant::now()),
+            RestartDecision::Cooldown(Duration::from_secs(300))
+        );
+        assert_eq!(
+            budget.next(&policy, Instant::now()),
+            RestartDecision::Restart {
+                delay: Duration::from_secs(RESTART_BACKOFF_BASE_SEC),
+                attempt: 1
+            }
+        );
+    }
+
+    #[test]
+    fn restart_always_never_cools_down() {
+        let policy = WatchdogPolicy {
+            restart_always: true,
+            restart_cooldown: Some(Duration::from_secs(300)),
+            ..Default::default()
+        };
+        let mut budget = exact_budget(&policy);
+        for _ in 0..10 {
+            assert!(matches!(
+                budget.next(&policy, Instant::now()),
+                RestartDecision::Restart { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds_and_under_the_cap() {
+        let policy = WatchdogPolicy {
+            restart_always: true,
+            ..Default::default()
+        };
+        for _ in 0..50 {
+            let mut budget = RestartBudget::new(&policy.restart_limits);
+            let mut backoff = 1.0;
+            for _ in 0..8 {
+                let RestartDecision::Restart { delay, .. } = budget.next(&policy, Instant::now())
+                else {
+                    panic!("restart_always restarts");
+                };
+                let delay = delay.as_secs_f64();
+                let cap = RESTART_BACKOFF_MAX_SEC as f64;
+                assert!(delay >= (backoff * (1.0 - RESTART_JITTER)).min(cap) - 1e-9);
+                assert!(delay <= (backoff * (1.0 + RESTART_JITTER)).min(cap) + 1e-9);
+                backoff = (backoff * 2.0).min(cap);
+            }
+        }
+
+        let max = Duration::from_secs(30);
+        let delay = Duration::from_secs(8);
+        assert_eq!(jittered(delay, -1.0, max), Duration::from_secs(6));
+        assert_eq!(jittered(delay, 1.0, max), Duration::from_secs(10));
+        assert_eq!(jittered(Duration::from_secs(28), 1.0, max), max);
+        assert_eq!(jittered(delay, 7.0, max), Duration::from_secs(10));
+        assert!((-1.0..=1.0).contains(&random_unit()));
+    }
+
+    #[test]
+    fn single_backoff_never_exceeds_the_configured_cap() {
+        let policy = WatchdogPolicy {
+            restart_always: true,
+            restart_limits: RestartLimits {
+                backoff_base: Duration::from_secs(20),
+                backoff_max: Duration::from_secs(5),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut budget = RestartBudget {
+            jitter: || 1.0,
+            ..RestartBudget::new(&policy.restart_limits)
+        };
+        for _ in 0..5 {
+            assert!(matches!(
+                budget.next(&policy, Instant::now()),
+                RestartDecision::Restart { delay, .. } if delay == Duration::from_secs(5)
+            ));
+        }
+    }
+
+    #[test]
+    fn time_budget_gives_up_regardless_of_attempts() {
+        let policy = WatchdogPolicy {
+            restart_always: true,
+            restart_limits: RestartLimits {
+                give_up_after: Some(Duration::from_secs(600)),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut budget = exact_budget(&policy);
+        let start = Instant::now();
+        assert!(matches!(
+            budget.next(&policy, start),
+            RestartDecision::Restart { attempt: 1, .. }
+        ));
+        assert!(matches!(
+            budget.next(&policy, start + Duration::from_secs(599)),
+            RestartDecision::Restart { attempt: 2, .. }
+        ));
+        assert_eq!(
+            budget.next(&policy, start + Duration::from_secs(600)),
+            RestartDecision::OutOfTime
+        );
+
+        // A healthy run restarts the clock as well as the count.
+        budget.reset();
+        assert_eq!(
+            budget.next(&policy, start + Duration::from_secs(900)),
+            RestartDecision::Restart {
+                delay: Duration::from_secs(RESTART_BACKOFF_BASE_SEC),
+                attempt: 1
+            }
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Polling watcher that restarts the app after config files change.
+//!
+//! Editors and deployment tools often write a file several times in a row,
+//! so a change only fires once the files have been quiet for the debounce.
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::paths::file_mtime;
+
+/// Quiet period after the last observed write before restarting.
+pub const CONFIG_CHANGE_DEBOUNCE_MS: u64 = 2000;
+
+#[derive(Debug, Clone)]
+pub struct ConfigWatcher {
+    files: Vec<PathBuf>,
+    last_seen: Vec<Option<SystemTime>>,
+    pending_since: Option<Instant>,
+    debounce: Duration,
+}
+
+impl ConfigWatcher {
+    /// Start watching `files`, taking their current state as the baseline.
+    pub fn new(files: Vec<PathBuf>) -> Self {
+        let last_seen = files.iter().map(|path| file_mtime(path)).collect();
+        ConfigWatcher {
+            files,
+            last_seen,
+            pending_since: None,
+            debounce: Duration::from_millis(CONFIG_CHANGE_DEBOUNCE_MS),
+        }
+    }
+
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Re-read the files' mtimes; true once a change has settled.
+    pub fn poll(&mut self, now: Instant) -> bool {
+        let mtimes = self.files.iter().map(|path| file_mtime(path)).collect();
+        self.observe(mtimes, now)
+    }
+
+    /// Feed observed mtimes (one per file). Any difference from the last
+    /// observation (re)starts the debounce; returns true exactly once per
+    /// settled burst of changes.
+    pub fn observe(&mut self, mtimes: Vec<Option<SystemTime>>, now: Instant) -> bool {
+        if mtimes != self.last_seen {
+            self.last_seen = mtimes;
+            self.pending_since = Some(now);
+            return false;
+        }
+        match self.pending_since {
+            Some(since) if now.duration_since(since) >= self.debounce => {
+                self.pending_since = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::fs;
+
+    fn mtime(secs: u64) -> Option<SystemTime> {
+        Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + secs))
+    }
+
+    #[test]
+    fn burst_of_writes_triggers_one_restart_after_debounce() {
+        let dir = temp_dir("watcher-burst");
+        let path = dir.join("launcher.toml");
+        fs::write(&path, "").unwrap();
+        let mut watcher = ConfigWatcher::new(vec![path]);
+        watcher.last_seen = vec![mtime(0)];
+        let start = Instant::now();
+        let ms = |value: u64| start + Duration::from_millis(value);
+
+        assert!(!watcher.observe(vec![mtime(0)], ms(0)));
+        // Three quick writes keep pushing the restart out.
+        assert!(!watcher.observe(vec![mtime(1)], ms(100)));
+        assert!(!watcher.observe(vec![mtime(2)], ms(600)));
+        assert!(!watcher.observe(vec![mtime(3)], ms(1200)));
+        assert!(!watcher.observe(vec![mtime(3)], ms(3100)));
+
+        let fired: Vec<bool> = (0..5)
+            .map(|step| watcher.observe(vec![mtime(3)], ms(3200 + step * 1000)))
+            .collect();
+        assert_eq!(fired, vec![true, false, false, false, false]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unchanged_files_never_trigger() {
+        let dir = temp_dir("watcher-idle");
+        let path = dir.join("launcher.toml");
+        fs::write(&path, "").unwrap();
+        let mut watcher = ConfigWatcher::new(vec![path, dir.join("missing.json")]);
+        let start = Instant::now();
+        for step in 0..5 {
+            assert!(!watcher.poll(start + Duration::from_secs(step * 3)));
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn created_file_counts_as_change() {
+        let mut watcher = ConfigWatcher::new(vec![PathBuf::from("app.json")]);
+        watcher.last_seen = vec![None];
+        let start = Instant::now();
+        assert!(!watcher.observe(vec![mtime(0)], start));
+        assert!(watcher.observe(vec![mtime(0)], start + Duration::from_secs(3)));
+    }
+}
@@ -0,0 +1,90 @@
+"""Tests for app.py values handed down by the native launcher."""
+
+import asyncio
+import time
+
+import app
+
+
+def test_app_port_defaults_without_launcher(monkeypatch) -> None:
+    monkeypatch.delenv("YAKULINGO_PORT", raising=False)
+    assert app._get_app_port() == 8765
+
+
+def test_app_port_uses_launcher_value(monkeypatch) -> None:
+    monkeypatch.setenv("YAKULINGO_PORT", "8770")
+    assert app._get_app_port() == 8770
+
+
+def test_app_port_ignores_invalid_value(monkeypatch) -> None:
+    monkeypatch.setenv("YAKULINGO_PORT", "not-a-port")
+    assert app._get_app_port() == 8765
+    monkeypatch.setenv("YAKULINGO_PORT", "70000")
+    assert app._get_app_port() == 8765
+
+
+def test_instance_mutex_name_follows_launcher_scope(monkeypatch) -> None:
+    monkeypatch.delenv("YAKULINGO_INSTANCE_MUTEX", raising=False)
+    # Same name as the launcher's default (per-machine) scope.
+    assert app._get_instance_mutex_name() == "Global\\YakuLingoSingleton"
+    monkeypatch.setenv("YAKULINGO_INSTANCE_MUTEX", "Local\\YakuLingoSingleton")
+    assert app._get_instance_mutex_name() == "Local\\YakuLingoSingleton"
+
+
+def test_heartbeat_disabled_without_launcher(monkeypatch) -> None:
+    monkeypatch.delenv("YAKULINGO_HEARTBEAT_FILE", raising=False)
+    assert app._start_heartbeat() is False
+
+
+def test_heartbeat_touches_file(monkeypatch, tmp_path) -> None:
+    heartbeat = tmp_path / "state" / "heartbeat"
+    monkeypatch.setenv("YAKULINGO_HEARTBEAT_FILE", str(heartbeat))
+    monkeypatch.setenv("YAKULINGO_HEARTBEAT_INTERVAL_SEC", "1")
+    assert app._start_heartbeat() is True
+    deadline = time.monotonic() + 5
+    while not heartbeat.exists() and time.monotonic() < deadline:
+        time.sleep(0.05)
+    assert heartbeat.exists()
+
+
+def test_heartbeat_moves_to_event_loop(monkeypatch, tmp_path) -> None:
+    heartbeat = tmp_path / "heartbeat"
+    monkeypatch.setenv("YAKULINGO_HEARTBEAT_FILE", str(heartbeat))
+    monkeypatch.setenv("YAKULINGO_HEARTBEAT_INTERVAL_SEC", "1")
+    assert app._start_heartbeat() is True
+
+    async def _serve() -> None:
+        app._hand_heartbeat_to_event_loop()
+        await asyncio.sleep(0.1)
+        heartbeat.unlink(missing_ok=True)
+        await asyncio.sleep(1.5)
+        assert heartbeat.exists(), "the running loop keeps beating"
+
+    asyncio.run(_serve())
+    # Loop gone, startup thread stopped: nothing beats for a hung app.
+    heartbeat.unlink()
+    time.sleep(1.5)
+    assert not heartbeat.exists()
+
+
+def test_setup_progress_disabled_without_launcher(monkeypatch, tmp_path) -> None:
+    monkeypatch.delenv("YAKULINGO_SETUP_PROGRESS_FILE", raising=False)
+    app._report_setup_progress("Loading YakuLingo components")
+    assert list(tmp_path.iterdir()) == []
+
+
+def test_setup_progress_is_written_for_launcher(monkeypatch, tmp_path) -> None:
+    import json
+
+    progress = tmp_path / "state" / "setup_progress.json"
+    monkeypatch.setenv("YAKULINGO_SETUP_PROGRESS_FILE", str(progress))
+    app._report_setup_progress("Loading YakuLingo components")
+    assert json.loads(progress.read_text(encoding="utf-8")) == {
+        "message": "Loading YakuLingo components"
+    }
+    app._report_setup_progress("Starting YakuLingo", 90)
+    assert json.loads(progress.read_text(encoding="utf-8")) == {
+        "message": "Starting YakuLingo",
+        "percent": 90,
+    }
+    assert [path.name for path in progress.parent.iterdir()] == [progress.name]
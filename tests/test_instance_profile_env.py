@@ -0,0 +1,45 @@
+"""Tests for the named-instance profile and title handed down by the launcher."""
+
+from pathlib import Path
+
+from yakulingo.config.settings import AppSettings, get_profile_dir
+from yakulingo.storage.history_db import get_default_db_path
+
+
+def test_profile_dir_defaults_to_home(monkeypatch) -> None:
+    monkeypatch.delenv("YAKULINGO_PROFILE_DIR", raising=False)
+    assert get_profile_dir() == Path.home() / ".yakulingo"
+
+
+def test_profile_dir_uses_launcher_value(monkeypatch, tmp_path) -> None:
+    profile = tmp_path / "instances" / "work"
+    monkeypatch.setenv("YAKULINGO_PROFILE_DIR", str(profile))
+    assert get_profile_dir() == profile
+    assert get_default_db_path() == profile / "history.db"
+
+
+def test_user_settings_live_in_profile(monkeypatch, tmp_path) -> None:
+    config_dir = tmp_path / "config"
+    profile = tmp_path / "instances" / "work"
+    monkeypatch.setenv("YAKULINGO_PROFILE_DIR", str(profile))
+    settings_path = config_dir / "settings.json"
+
+    settings = AppSettings()
+    settings.last_tab = "file"
+    settings.save(settings_path)
+
+    assert (profile / "user_settings.json").exists()
+    assert not (config_dir / "user_settings.json").exists()
+    loaded = AppSettings.load(settings_path, use_cache=False)
+    assert loaded.last_tab == "file"
+
+
+def test_window_title_follows_launcher_instance(monkeypatch) -> None:
+    import yakulingo.ui.app as app_module
+
+    monkeypatch.delenv("YAKULINGO_WINDOW_TITLE", raising=False)
+    assert app_module._get_app_window_title() == "YakuLingo"
+    monkeypatch.setenv("YAKULINGO_WINDOW_TITLE", "YakuLingo (work)")
+    assert app_module._get_app_window_title() == "YakuLingo (work)"
+    assert app_module._is_yakulingo_window_title("YakuLingo (work) - a.txt")
+    assert not app_module._is_yakulingo_window_title("YakuLingo")
@@ -0,0 +1,69 @@
+"""Tests for the state files shared with the native launcher."""
+
+import json
+from pathlib import Path
+
+from yakulingo.ui import utils
+
+
+def test_state_dir_defaults_to_home(monkeypatch, tmp_path) -> None:
+    monkeypatch.delenv("YAKULINGO_DATA_DIR", raising=False)
+    monkeypatch.setattr(Path, "home", lambda: tmp_path)
+    assert utils.get_state_dir() == tmp_path / ".yakulingo"
+    monkeypatch.setenv("YAKULINGO_DATA_DIR", "")
+    assert utils.get_state_dir() == tmp_path / ".yakulingo"
+
+
+def test_launcher_state_follows_data_dir(monkeypatch, tmp_path) -> None:
+    data_dir = tmp_path / "data"
+    monkeypatch.setenv("YAKULINGO_DATA_DIR", str(data_dir))
+    monkeypatch.setenv("YAKULINGO_SHUTDOWN_REQUESTED", "")
+    assert utils.get_launcher_state_path() == data_dir / "launcher_state.json"
+
+    utils.write_launcher_state("user_exit")
+    state = json.loads((data_dir / "launcher_state.json").read_text(encoding="utf-8"))
+    assert state["reason"] == "user_exit"
+
+    utils.clear_launcher_state()
+    assert not (data_dir / "launcher_state.json").exists()
+
+
+def test_app_port_report_keeps_other_context_keys(monkeypatch, tmp_path) -> None:
+    monkeypatch.setenv("YAKULINGO_DATA_DIR", str(tmp_path))
+    context_path = tmp_path / "launcher_context.json"
+    context_path.write_text(json.dumps({"python": {"version": "3.11"}}), encoding="utf-8")
+
+    utils.report_app_port(8765, 49152)
+
+    context = json.loads(context_path.read_text(encoding="utf-8"))
+    assert context["python"] == {"version": "3.11"}
+    report = context["app_ports"]["8765"]
+    assert report["port"] == 49152
+    assert report["pid"] > 0
+    assert report["ts"] > 0
+    assert not list(tmp_path.glob("*.tmp"))
+
+
+def test_app_port_report_is_refreshed_before_it_expires(monkeypatch) -> None:
+    calls: list[tuple[int, int]] = []
+    monkeypatch.setattr(
+        utils, "report_app_port", lambda requested, actual: calls.append((requested, actual))
+    )
+    reporter = utils.AppPortReporter(8765, 49152)
+
+    assert reporter.report(now=0.0)
+    assert not reporter.report(now=60.0)
+    assert reporter.report(now=utils.APP_PORT_REPORT_REFRESH_SEC)
+    assert calls == [(8765, 49152), (8765, 49152)]
+
+
+def test_taken_port_falls_back_to_a_free_one() -> None:
+    import socket
+
+    with socket.socket(socket.AF_INET, socket.SOCK_STREAM) as taken:
+        taken.bind(("127.0.0.1", 0))
+        taken.listen()
+        busy = taken.getsockname()[1]
+        fallback = utils.pick_app_port("127.0.0.1", busy)
+        assert fallback not in (busy, 0)
+    assert utils.pick_app_port("127.0.0.1", fallback) == fallback
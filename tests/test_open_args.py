@@ -0,0 +1,86 @@
+"""Tests for files forwarded by a second launcher start (/api/open-args)."""
+
+import asyncio
+import threading
+from pathlib import Path
+
+import pytest
+
+from yakulingo.ui.utils import resolve_open_args
+
+SUPPORTED = {".docx", ".pdf"}
+
+
+def test_relative_paths_resolve_against_the_launch_cwd(tmp_path) -> None:
+    (tmp_path / "report.docx").write_bytes(b"")
+    (tmp_path / "notes.txt").write_bytes(b"")
+    other = tmp_path / "other"
+    other.mkdir()
+    (other / "paper.PDF").write_bytes(b"")
+
+    paths = resolve_open_args(
+        ["--instance", "report.docx", "notes.txt", str(other / "paper.PDF")],
+        str(tmp_path),
+        SUPPORTED,
+    )
+
+    assert paths == [tmp_path / "report.docx", other / "paper.PDF"]
+
+
+def test_missing_files_and_bad_values_are_dropped(tmp_path) -> None:
+    (tmp_path / "report.docx").write_bytes(b"")
+
+    assert resolve_open_args(["missing.pdf", 42, ""], str(tmp_path), SUPPORTED) == []
+    # Without a working directory only absolute paths can be opened.
+    assert resolve_open_args(["report.docx"], None, SUPPORTED) == []
+    assert resolve_open_args([str(tmp_path / "report.docx")], None, SUPPORTED) == [
+        tmp_path / "report.docx"
+    ]
+
+
+@pytest.mark.asyncio
+async def test_files_wait_for_the_reopened_ui(monkeypatch, tmp_path) -> None:
+    import yakulingo.ui.app as app_module
+    from yakulingo.ui.app import YakuLingoApp
+
+    app = YakuLingoApp.__new__(YakuLingoApp)
+    app._client_lock = threading.Lock()
+    app._client = None
+    app._ui_ready_event = asyncio.Event()
+    app._pending_open_paths = None
+    app._resident_mode = True
+    app._resident_show_requested = False
+
+    shown: list[str] = []
+    selected: list[list[Path]] = []
+    tasks: list = []
+
+    async def ensure_visible(reason: str) -> bool:
+        shown.append(reason)
+        return True
+
+    async def select_file(paths) -> None:  # type: ignore[no-untyped-def]
+        selected.append(paths)
+
+    monkeypatch.setattr(app, "_mark_manual_show", lambda _reason: None)
+    monkeypatch.setattr(app, "_ensure_resident_ui_visible", ensure_visible)
+    monkeypatch.setattr(app, "_select_file", select_file)
+    monkeypatch.setattr(
+        app_module, "_create_logged_task", lambda coro, *, name: tasks.append(coro)
+    )
+
+    paths = [tmp_path / "report.docx"]
+    await app._open_files_from_args(paths)
+    assert shown == ["open_args"]
+    assert selected == []
+
+    class DummyClient:
+        has_socket_connection = True
+
+    client = DummyClient()
+    app._client = client
+    assert app._mark_ui_ready(client)
+    for coro in tasks:
+        await coro
+    assert selected == [paths]
+    assert app._pending_open_paths is None
@@ -7,10 +7,12 @@ from .settings import (
     AppSettings,
     get_default_settings_path,
     get_default_prompts_dir,
+    get_profile_dir,
 )
 
 __all__ = [
     "AppSettings",
     "get_default_settings_path",
     "get_default_prompts_dir",
+    "get_profile_dir",
 ]
@@ -432,7 +432,7 @@ class AppSettings:
         # Determine base config directory
         config_dir = path.parent
         template_path = config_dir / "settings.template.json"
-        user_settings_path = config_dir / "user_settings.json"
+        user_settings_path = get_user_settings_path(config_dir)
 
         cache_key = str(path.resolve())
 
@@ -1130,10 +1130,10 @@ class AppSettings:
                   実際にはconfig/user_settings.jsonに保存
         """
         config_dir = path.parent
-        user_settings_path = config_dir / "user_settings.json"
+        user_settings_path = get_user_settings_path(config_dir)
         template_path = config_dir / "settings.template.json"
 
-        config_dir.mkdir(parents=True, exist_ok=True)
+        user_settings_path.parent.mkdir(parents=True, exist_ok=True)
 
         # Only save user-changeable settings
         data = {}
@@ -1176,6 +1176,25 @@ def get_default_settings_path() -> Path:
     return Path(__file__).parent.parent.parent / "config" / "settings.json"
 
 
+def get_profile_dir() -> Path:
+    """Per-user data directory (history, pins, window profile).
+
+    The launcher sets YAKULINGO_PROFILE_DIR for named instances so each keeps
+    its own data; otherwise ~/.yakulingo.
+    """
+    profile_dir = os.environ.get("YAKULINGO_PROFILE_DIR")
+    if profile_dir:
+        return Path(profile_dir)
+    return Path.home() / ".yakulingo"
+
+
+def get_user_settings_path(config_dir: Path) -> Path:
+    """user_settings.json: in the named instance's profile, else next to the template."""
+    if os.environ.get("YAKULINGO_PROFILE_DIR"):
+        return get_profile_dir() / "user_settings.json"
+    return config_dir / "user_settings.json"
+
+
 def get_default_prompts_dir() -> Path:
     """Get default prompts directory"""
     return Path(__file__).parent.parent.parent / "prompts"
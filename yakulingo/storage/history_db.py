@@ -10,6 +10,7 @@ import threading
 from pathlib import Path
 from typing import Optional
 
+from yakulingo.config.settings import get_profile_dir
 from yakulingo.models.types import (
     HistoryEntry,
     TextTranslationResult,
@@ -23,8 +24,8 @@ logger = logging.getLogger(__name__)
 
 
 def get_default_db_path() -> Path:
-    """Get default database path in user's home directory"""
-    db_dir = Path.home() / ".yakulingo"
+    """Get default database path in the user's profile directory"""
+    db_dir = get_profile_dir()
     db_dir.mkdir(parents=True, exist_ok=True)
     return db_dir / "history.db"
 
@@ -193,8 +193,13 @@ def _is_window_title_with_boundary(title: str, base_title: str) -> bool:
     return title[len(base_title)].isspace()
 
 
+def _get_app_window_title() -> str:
+    """Native window title; named launcher instances use "YakuLingo (<name>)"."""
+    return os.environ.get("YAKULINGO_WINDOW_TITLE") or "YakuLingo"
+
+
 def _is_yakulingo_window_title(title: str) -> bool:
-    return _is_window_title_with_boundary(title, "YakuLingo")
+    return _is_window_title_with_boundary(title, _get_app_window_title())
 
 
 def _find_window_handle_by_title_win32(window_title: str) -> int | None:
@@ -1236,6 +1241,7 @@ from yakulingo.config.settings import (  # noqa: E402
     AppSettings,
     get_default_settings_path,
     get_default_prompts_dir,
+    get_profile_dir,
     resolve_browser_display_mode,
 )
 
@@ -1255,6 +1261,7 @@ if TYPE_CHECKING:
     from yakulingo.services.local_llama_server import LocalAIServerRuntime
     from yakulingo.services.translation_service import TranslationService
     from yakulingo.ui.components.update_notification import UpdateNotification
+    from yakulingo.ui.utils import AppPortReporter
 
 
 # App constants
@@ -1636,6 +1643,10 @@ class YakuLingoApp:
         self._result_panel_scroll_handle: "asyncio.Handle | None" = None
         self._shutdown_requested = False
         self._resident_heartbeat_task: "asyncio.Task | None" = None
+        # Set when the server fell back to another port (see run_app).
+        self._app_port_reporter: "AppPortReporter | None" = None
+        # Files from /api/open-args held until a UI client is ready (guarded by _client_lock).
+        self._pending_open_paths: list[Path] | None = None
         self._resident_startup_active = False
         self._resident_startup_ready = False
         self._resident_startup_prompt_ready = False
@@ -1678,7 +1689,7 @@ class YakuLingoApp:
         self._global_drop_indicator = None
 
         # History pins (persisted locally)
-        self._history_pins_path = Path.home() / ".yakulingo" / "history_pins.json"
+        self._history_pins_path = get_profile_dir() / "history_pins.json"
         self._history_pins: set[str] = set()
         self._load_history_pins()
 
@@ -1815,6 +1826,9 @@ class YakuLingoApp:
     async def _resident_heartbeat_loop(self, interval_sec: float) -> None:
         try:
             while not self._shutdown_requested:
+                reporter = self._app_port_reporter
+                if reporter is not None:
+                    reporter.report()
                 client = None
                 with self._client_lock:
                     client = self._client
@@ -4868,9 +4882,10 @@ class YakuLingoApp:
 
     def _get_ui_window_title(self) -> str:
         native_mode = self._native_mode_enabled
+        app_title = _get_app_window_title()
         if native_mode is None:
-            return "YakuLingo"
-        return "YakuLingo" if native_mode else "YakuLingo (UI)"
+            return app_title
+        return app_title if native_mode else f"{app_title} (UI)"
 
     def _find_ui_window_handle_win32(
         self, *, include_hidden: bool = True
@@ -5315,7 +5330,7 @@ class YakuLingoApp:
                 "Failed to hide resident window via pywebview (%s): %s", reason, e
             )
         try:
-            _hide_native_window_offscreen_win32("YakuLingo")
+            _hide_native_window_offscreen_win32(_get_app_window_title())
         except Exception as e:
             logger.debug("Failed to hide resident window offscreen (%s): %s", reason, e)
 
@@ -5348,7 +5363,7 @@ class YakuLingoApp:
                         return
                 self._set_ui_taskbar_visibility_win32(False, f"{reason}:{attempt}")
                 try:
-                    _hide_native_window_offscreen_win32("YakuLingo")
+                    _hide_native_window_offscreen_win32(_get_app_window_title())
                 except Exception:
                     pass
                 time.sleep(delay_sec)
@@ -5666,6 +5681,17 @@ class YakuLingoApp:
             self._ui_ready_event.set()
         except Exception:
             pass
+        with self._client_lock:
+            pending_paths, self._pending_open_paths = self._pending_open_paths, None
+        if pending_paths:
+            logger.info(
+                "Selecting files queued before the UI connected: %s",
+                [str(p) for p in pending_paths],
+            )
+            _create_logged_task(
+                self._select_file(pending_paths),
+                name="pending_open_args_select_file",
+            )
         return True
 
     def _clear_ui_ready(self) -> None:
@@ -10012,6 +10038,35 @@ class YakuLingoApp:
                     pass
             self._file_panel_refresh_timer = None
 
+    async def _open_files_from_args(self, paths: list[Path]) -> None:
+        """Select files handed over by a second launch, opening the UI first if needed.
+
+        Without a ready client (resident/tray state) the paths are queued and
+        selected by _mark_ui_ready once the reopened UI is up.
+        """
+        with self._client_lock:
+            client = self._client
+            ready = client is not None and self._ui_ready_event.is_set()
+            if not ready:
+                self._pending_open_paths = list(paths)
+        if ready:
+            await self._select_file(paths)
+            return
+
+        reason = "open_args"
+        if self._resident_mode:
+            self._mark_manual_show(reason)
+            self._resident_show_requested = True
+            try:
+                await self._ensure_resident_ui_visible(reason)
+            except Exception as e:
+                logger.debug("Failed to ensure resident UI visible (%s): %s", reason, e)
+        else:
+            try:
+                await self._bring_window_to_front(position_edge=True)
+            except Exception as e:
+                logger.debug("Failed to bring window to front (%s): %s", reason, e)
+
     async def _select_file(self, file_path: Path | list[Path]):
         """Select file(s) for translation with auto language detection (async)."""
         with self._client_lock:
@@ -11241,6 +11296,7 @@ def run_app(
     port: int = 8765,
     native: bool = True,
     on_ready: callable = None,
+    on_server_start: callable = None,
 ):
     """Run the application.
 
@@ -11250,6 +11306,8 @@ def run_app(
         native: Use native window mode (pywebview)
         on_ready: Callback to call after the UI becomes visible.
                   Use this to close splash screens for seamless transition.
+        on_server_start: Callback to call on the server's event loop once it
+                  has started (before clients connect).
     """
     import multiprocessing
 
@@ -11312,6 +11370,17 @@ def run_app(
     # Validate NiceGUI version after import
     _ensure_nicegui_version()
 
+    # A foreign process on the requested port: serve on a free one instead and
+    # report it (on startup) so the launcher can still find this instance.
+    from yakulingo.ui.utils import AppPortReporter, pick_app_port
+
+    requested_port = port
+    port = pick_app_port(host, requested_port)
+    if port != requested_port:
+        logger.warning(
+            "Port %d is in use; listening on port %d instead", requested_port, port
+        )
+
     # Patch NiceGUI native_mode to pass window_args to child process
     # This must be done before ui.run() is called
     if native:
@@ -11450,6 +11519,9 @@ def run_app(
     browser_open_in_progress = False
 
     def _get_profile_dir_for_browser_app() -> Path:
+        # Named instances keep their Edge profile inside their own profile dir.
+        if os.environ.get("YAKULINGO_PROFILE_DIR"):
+            return get_profile_dir() / "app-window-profile"
         local_app_data = os.environ.get("LOCALAPPDATA", "")
         if local_app_data:
             return Path(local_app_data) / "YakuLingo" / "AppWindowProfile"
@@ -11809,7 +11881,8 @@ def run_app(
                 title = ctypes.create_unicode_buffer(title_length + 1)
                 user32.GetWindowTextW(hwnd, title, title_length + 1)
                 window_title = title.value
-                if _is_window_title_with_boundary(window_title, "YakuLingo (UI)"):
+                ui_title = f"{_get_app_window_title()} (UI)"
+                if _is_window_title_with_boundary(window_title, ui_title):
                     user32.PostMessageW(hwnd, WM_CLOSE, 0, 0)
                 return True
 
@@ -12458,6 +12531,46 @@ def run_app(
 
             return {"ok": True}
 
+        @nicegui_app.post("/api/open-args")
+        async def open_args_api(request: StarletteRequest):  # type: ignore[misc]
+            """Open the files a second launch was given (local machine only)."""
+            try:
+                client_host = getattr(getattr(request, "client", None), "host", None)
+                if client_host not in ("127.0.0.1", "::1"):
+                    raise HTTPException(status_code=403, detail="forbidden")
+                open_header = request.headers.get("X-YakuLingo-Open")
+                if open_header != "1":
+                    raise HTTPException(status_code=403, detail="forbidden")
+            except HTTPException:
+                raise
+            except Exception:
+                raise HTTPException(status_code=403, detail="forbidden")
+
+            from yakulingo.ui.components.file_panel import SUPPORTED_EXTENSIONS
+            from yakulingo.ui.utils import resolve_open_args
+
+            try:
+                data = await request.json()
+            except Exception:
+                data = None
+            if not isinstance(data, dict) or not isinstance(data.get("args"), list):
+                raise HTTPException(status_code=400, detail="args is required")
+            cwd = data.get("cwd")
+            paths = resolve_open_args(
+                data["args"],
+                cwd if isinstance(cwd, str) else None,
+                SUPPORTED_EXTENSIONS,
+            )
+            if not paths:
+                raise HTTPException(status_code=400, detail="no supported file")
+
+            logger.info("Open args API received: %s", [str(p) for p in paths])
+            _create_logged_task(
+                yakulingo_app._open_files_from_args(paths),
+                name="open_args_select_file",
+            )
+            return {"ok": True, "files": len(paths)}
+
         @nicegui_app.post("/api/ui-close")
         async def ui_close_api(request: StarletteRequest):  # type: ignore[misc]
             """Switch to resident mode when the UI window is closed (local machine only)."""
@@ -12787,7 +12900,7 @@ def run_app(
                 ):
                     return
                 # Find YakuLingo window by title (exact match first, then fallback to partial match).
-                hwnd = user32.FindWindowW(None, "YakuLingo")
+                hwnd = user32.FindWindowW(None, _get_app_window_title())
                 if not hwnd:
                     EnumWindowsProc = ctypes.WINFUNCTYPE(
                         ctypes.c_bool, wintypes.HWND, wintypes.LPARAM
@@ -12837,7 +12950,7 @@ def run_app(
 
                     if resident_mode:
                         _set_window_taskbar_visibility_win32(hwnd, False)
-                        _hide_native_window_offscreen_win32("YakuLingo")
+                        _hide_native_window_offscreen_win32(_get_app_window_title())
                         logger.debug(
                             "[EARLY_POSITION] Resident mode: window kept offscreen"
                         )
@@ -12980,6 +13093,15 @@ def run_app(
         """Called when NiceGUI server starts (before clients connect)."""
         startup_backend = "local"
 
+        if on_server_start is not None:
+            try:
+                on_server_start()
+            except Exception as e:
+                logger.debug("on_server_start callback failed: %s", e)
+        if port != requested_port:
+            yakulingo_app._app_port_reporter = AppPortReporter(requested_port, port)
+            yakulingo_app._app_port_reporter.report()
+
         # Start hotkey listener immediately so hotkey translation works even without the UI.
         yakulingo_app.start_hotkey_listener()
         yakulingo_app._start_resident_heartbeat()
@@ -14022,7 +14144,8 @@ document.fonts.ready.then(function() {
     # 3. _position_window_early_sync() polls for window, positions it while hidden, then shows it
     # This approach ensures the window appears at the correct position from the start.
 
-    window_title = "YakuLingo" if native else "YakuLingo (UI)"
+    app_title = _get_app_window_title()
+    window_title = app_title if native else f"{app_title} (UI)"
     # Browser mode: prefer SVG favicon for a sharper Edge --app taskbar icon.
     ui.run(
         host=host,
@@ -12,6 +12,7 @@ import os
 import platform
 import re
 import shutil
+import socket
 import tempfile
 import time
 from contextlib import contextmanager
@@ -61,8 +62,16 @@ def format_bytes(size_bytes: int) -> str:
     return f"{size_bytes / (1024 * 1024 * 1024):.1f} GB"
 
 
+def get_state_dir() -> Path:
+    """Directory shared with the launcher: YAKULINGO_DATA_DIR, else ~/.yakulingo."""
+    data_dir = os.environ.get("YAKULINGO_DATA_DIR")
+    if data_dir:
+        return Path(data_dir)
+    return Path.home() / ".yakulingo"
+
+
 def get_launcher_state_path() -> Path:
-    return Path.home() / ".yakulingo" / "launcher_state.json"
+    return get_state_dir() / "launcher_state.json"
 
 
 def write_launcher_state(reason: str) -> None:
@@ -87,6 +96,95 @@ def clear_launcher_state() -> None:
         logger.debug("Failed to clear launcher state: %s", e)
 
 
+def resolve_open_args(
+    args: list, cwd: Optional[str], supported_extensions: Set[str]
+) -> list[Path]:
+    """Files named by a second launch's arguments, relative ones resolved against
+    its working directory. Flags and missing or unsupported files are dropped."""
+    base = Path(cwd) if cwd else None
+    paths: list[Path] = []
+    for arg in args:
+        if not isinstance(arg, str) or not arg or arg.startswith("-"):
+            continue
+        path = Path(arg)
+        if not path.is_absolute():
+            if base is None:
+                continue
+            path = base / path
+        if path.suffix.lower() in supported_extensions and path.is_file():
+            paths.append(path)
+    return paths
+
+
+def get_launcher_context_path() -> Path:
+    return get_state_dir() / "launcher_context.json"
+
+
+def pick_app_port(host: str, port: int) -> int:
+    """Return `port` if it can be bound on `host`, else an OS-assigned free port."""
+    with socket.socket(socket.AF_INET, socket.SOCK_STREAM) as sock:
+        try:
+            sock.bind((host, port))
+            return port
+        except OSError:
+            pass
+    with socket.socket(socket.AF_INET, socket.SOCK_STREAM) as sock:
+        sock.bind((host, 0))
+        return sock.getsockname()[1]
+
+
+def report_app_port(requested: int, actual: int) -> None:
+    """Tell the launcher that `requested` is served on `actual`.
+
+    Merged into launcher_context.json as app_ports[requested], keeping the
+    other keys, and replaced atomically so the launcher never reads half a file.
+    """
+    try:
+        path = get_launcher_context_path()
+        try:
+            context = json.loads(path.read_text(encoding="utf-8"))
+        except (OSError, ValueError):
+            context = {}
+        if not isinstance(context, dict):
+            context = {}
+        ports = context.get("app_ports")
+        if not isinstance(ports, dict):
+            ports = {}
+        ports[str(requested)] = {"port": actual, "pid": os.getpid(), "ts": time.time()}
+        context["app_ports"] = ports
+        path.parent.mkdir(parents=True, exist_ok=True)
+        staged = path.with_name(f"{path.name}.{os.getpid()}.tmp")
+        staged.write_text(json.dumps(context), encoding="utf-8")
+        os.replace(staged, path)
+    except Exception as e:
+        logger.debug("Failed to report app port: %s", e)
+
+
+# The launcher ignores reports older than 24h (PID reuse); refresh well before.
+APP_PORT_REPORT_REFRESH_SEC = 60 * 60
+
+
+class AppPortReporter:
+    """Reports the fallback port and keeps the report fresh for a long-running app."""
+
+    def __init__(self, requested: int, actual: int) -> None:
+        self.requested = requested
+        self.actual = actual
+        self._reported_at: Optional[float] = None
+
+    def report(self, now: Optional[float] = None) -> bool:
+        """Write the report when it was never written or is due; returns whether it was."""
+        now = time.monotonic() if now is None else now
+        if (
+            self._reported_at is not None
+            and now - self._reported_at < APP_PORT_REPORT_REFRESH_SEC
+        ):
+            return False
+        report_app_port(self.requested, self.actual)
+        self._reported_at = now
+        return True
+
+
 class TempFileManager:
     """
     Manages temporary files created during UI operations.